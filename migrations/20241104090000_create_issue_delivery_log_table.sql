@@ -0,0 +1,33 @@
+-- Add migration script here
+CREATE TABLE issue_delivery_log (
+    newsletter_issue_id uuid NOT NULL
+        REFERENCES newsletter_issues (newsletter_issue_id),
+    subscriber_email TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT NULL,
+    queued_at timestamptz NOT NULL,
+    last_attempted_at timestamptz NULL,
+    sent_at timestamptz NULL,
+    PRIMARY KEY(newsletter_issue_id, subscriber_email)
+);
+
+-- Backfill the log for deliveries that are still pending or dead-lettered
+INSERT INTO issue_delivery_log (newsletter_issue_id, subscriber_email, status, queued_at)
+SELECT q.newsletter_issue_id, q.subscriber_email, 'queued', i.published_at
+FROM issue_delivery_queue q
+JOIN newsletter_issues i ON i.newsletter_issue_id = q.newsletter_issue_id;
+
+INSERT INTO issue_delivery_log (
+    newsletter_issue_id,
+    subscriber_email,
+    status,
+    attempts,
+    last_error,
+    queued_at,
+    last_attempted_at
+)
+SELECT d.newsletter_issue_id, d.subscriber_email, 'failed', 1, d.last_error, i.published_at, d.failed_at
+FROM issue_delivery_dead_letters d
+JOIN newsletter_issues i ON i.newsletter_issue_id = d.newsletter_issue_id
+ON CONFLICT DO NOTHING;
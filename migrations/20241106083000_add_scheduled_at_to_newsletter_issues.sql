@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE newsletter_issues ADD COLUMN scheduled_at timestamptz NULL;
+ALTER TABLE newsletter_issues ALTER COLUMN published_at DROP NOT NULL;
@@ -0,0 +1,13 @@
+-- Add migration script here
+ALTER TABLE newsletter_issues ADD COLUMN slug TEXT NULL UNIQUE;
+ALTER TABLE newsletter_issues ADD COLUMN hidden_from_archive BOOLEAN NOT NULL DEFAULT false;
+
+-- Give every issue that was already published a slug, built the same way as new ones:
+-- the slugified title followed by the first 8 characters of the issue id
+UPDATE newsletter_issues
+SET slug = concat_ws(
+    '-',
+    NULLIF(trim(both '-' from left(regexp_replace(lower(title), '[^a-z0-9]+', '-', 'g'), 60)), ''),
+    left(replace(newsletter_issue_id::text, '-', ''), 8)
+)
+WHERE published_at IS NOT NULL;
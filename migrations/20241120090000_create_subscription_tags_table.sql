@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE subscription_tags (
+    subscriber_id uuid NOT NULL
+        REFERENCES subscriptions (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (subscriber_id, tag)
+);
+CREATE INDEX subscription_tags_tag_idx ON subscription_tags (tag);
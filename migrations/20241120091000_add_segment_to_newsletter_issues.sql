@@ -0,0 +1,3 @@
+-- Add migration script here
+-- Segment the issue was sent to, NULL when it went to every confirmed subscriber
+ALTER TABLE newsletter_issues ADD COLUMN segment TEXT NULL;
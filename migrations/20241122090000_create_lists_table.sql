@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE TABLE lists (
+    list_id uuid NOT NULL,
+    PRIMARY KEY (list_id),
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT false,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+-- Subscriptions and issues that do not name a list go to the default one
+CREATE UNIQUE INDEX lists_single_default_idx ON lists (is_default) WHERE is_default;
+INSERT INTO lists (list_id, slug, name, is_default)
+VALUES (gen_random_uuid(), 'newsletter', 'Newsletter', true);
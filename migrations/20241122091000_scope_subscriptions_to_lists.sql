@@ -0,0 +1,15 @@
+-- Add migration script here
+-- A subscription is now the membership of an email in a list: the same email can
+-- subscribe to several lists, each with its own status, token and tags
+BEGIN;
+    ALTER TABLE subscriptions ADD COLUMN list_id uuid NULL REFERENCES lists (list_id);
+    UPDATE subscriptions SET list_id = (SELECT list_id FROM lists WHERE is_default);
+    ALTER TABLE subscriptions ALTER COLUMN list_id SET NOT NULL;
+    ALTER TABLE subscriptions DROP CONSTRAINT subscriptions_email_key;
+    ALTER TABLE subscriptions ADD CONSTRAINT subscriptions_list_id_email_key UNIQUE (list_id, email);
+
+    -- Drafts pick their list when they are published
+    ALTER TABLE newsletter_issues ADD COLUMN list_id uuid NULL REFERENCES lists (list_id);
+    UPDATE newsletter_issues SET list_id = (SELECT list_id FROM lists WHERE is_default)
+    WHERE published_at IS NOT NULL OR scheduled_at IS NOT NULL;
+COMMIT;
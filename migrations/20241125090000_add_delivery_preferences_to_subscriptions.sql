@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE subscriptions ADD COLUMN frequency TEXT NOT NULL DEFAULT 'every_issue';
+ALTER TABLE subscriptions ADD COLUMN paused_until timestamptz NULL;
+-- Issues published after this point are part of the next weekly digest
+ALTER TABLE subscriptions ADD COLUMN last_digest_at timestamptz NULL;
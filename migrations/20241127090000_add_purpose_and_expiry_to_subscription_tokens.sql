@@ -0,0 +1,22 @@
+-- Add migration script here
+ALTER TABLE subscription_tokens
+    ADD COLUMN purpose TEXT NULL,
+    ADD COLUMN created_at timestamptz NOT NULL DEFAULT now(),
+    ADD COLUMN expires_at timestamptz NULL,
+    ADD COLUMN used_at timestamptz NULL;
+
+-- Tokens of pending subscriptions were sent in confirmation emails,
+-- the other ones at the bottom of newsletters
+UPDATE subscription_tokens t
+SET purpose = CASE WHEN s.status = 'pending_confirmation' THEN 'confirm' ELSE 'unsubscribe' END,
+    expires_at = CASE
+        WHEN s.status = 'pending_confirmation' THEN now() + interval '7 days'
+        ELSE now() + interval '60 days'
+    END
+FROM subscriptions s
+WHERE s.id = t.subscriber_id;
+
+ALTER TABLE subscription_tokens ALTER COLUMN purpose SET NOT NULL;
+ALTER TABLE subscription_tokens ALTER COLUMN expires_at SET NOT NULL;
+
+CREATE INDEX subscription_tokens_subscriber_id_idx ON subscription_tokens (subscriber_id);
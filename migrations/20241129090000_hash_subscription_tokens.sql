@@ -0,0 +1,14 @@
+-- Add migration script here
+-- Tokens are stored as a keyed hash. The key lives in the configuration, so
+-- rows issued before this migration keep their raw value in `legacy_token`
+-- until the `hash_legacy_tokens` binary hashes them. The application refuses
+-- to start while such rows remain.
+ALTER TABLE subscription_tokens RENAME COLUMN subscription_token TO legacy_token;
+ALTER TABLE subscription_tokens DROP CONSTRAINT subscription_tokens_pkey;
+ALTER TABLE subscription_tokens ALTER COLUMN legacy_token DROP NOT NULL;
+ALTER TABLE subscription_tokens ADD COLUMN token_hash TEXT NULL;
+ALTER TABLE subscription_tokens
+    ADD CONSTRAINT subscription_tokens_hashed_or_legacy
+    CHECK (token_hash IS NOT NULL OR legacy_token IS NOT NULL);
+
+CREATE UNIQUE INDEX subscription_tokens_token_hash_idx ON subscription_tokens (token_hash);
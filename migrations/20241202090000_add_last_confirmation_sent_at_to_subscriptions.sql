@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE subscriptions ADD COLUMN last_confirmation_sent_at timestamptz NULL;
+
+UPDATE subscriptions SET last_confirmation_sent_at = subscribed_at
+WHERE status = 'pending_confirmation';
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE subscriptions ADD COLUMN cancellation_requested_at timestamptz NULL;
+
+-- When earlier cancellations were requested is unknown, their retention
+-- period starts now
+UPDATE subscriptions SET cancellation_requested_at = now()
+WHERE status = 'cancellation_pending';
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE subscriptions
+    ADD COLUMN suppressed_at timestamptz NULL,
+    ADD COLUMN suppression_description TEXT NULL,
+    ADD COLUMN status_before_suppression TEXT NULL;
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE blocked_addresses(
+    blocked_id uuid NOT NULL,
+    PRIMARY KEY (blocked_id),
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    UNIQUE (kind, value)
+);
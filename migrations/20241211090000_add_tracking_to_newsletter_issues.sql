@@ -0,0 +1,24 @@
+-- Add migration script here
+ALTER TABLE newsletter_issues ADD COLUMN tracking_enabled BOOLEAN NOT NULL DEFAULT false;
+
+-- One link per recipient of a tracked issue: the open pixel has no URL,
+-- every other link redirects to the URL it replaces
+CREATE TABLE newsletter_tracking_links(
+    tracking_id uuid NOT NULL,
+    PRIMARY KEY (tracking_id),
+    newsletter_issue_id uuid NOT NULL
+        REFERENCES newsletter_issues (newsletter_issue_id) ON DELETE CASCADE,
+    subscriber_email TEXT NOT NULL,
+    url TEXT,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX newsletter_tracking_links_issue_idx
+    ON newsletter_tracking_links (newsletter_issue_id);
+
+CREATE TABLE newsletter_tracking_events(
+    tracking_id uuid NOT NULL
+        REFERENCES newsletter_tracking_links (tracking_id) ON DELETE CASCADE,
+    occurred_at timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX newsletter_tracking_events_tracking_id_idx
+    ON newsletter_tracking_events (tracking_id);
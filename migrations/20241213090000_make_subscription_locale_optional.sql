@@ -0,0 +1,4 @@
+-- A subscription without a locale follows the default locale of the deployment,
+-- resolved when an email is sent rather than when the row is inserted
+ALTER TABLE subscriptions ALTER COLUMN locale DROP NOT NULL;
+ALTER TABLE subscriptions ALTER COLUMN locale DROP DEFAULT;
@@ -0,0 +1,4 @@
+-- A claimed weekly digest is hidden from other workers until this point. The
+-- digest is only marked as sent once it went out, so a failed send is retried
+-- when the lease expires.
+ALTER TABLE subscriptions ADD COLUMN digest_locked_until timestamptz NULL;
@@ -0,0 +1,6 @@
+-- Unsubscribe links must keep working long after an issue was sent: they now
+-- expire two years after being issued, which also revives the ones that
+-- expired after 60 days
+UPDATE subscription_tokens
+SET expires_at = created_at + interval '730 days'
+WHERE purpose = 'unsubscribe';
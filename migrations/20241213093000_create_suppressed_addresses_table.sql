@@ -0,0 +1,14 @@
+-- Suppression applies to an address: it is recorded even when the address has
+-- no subscription, and keeps it from subscribing to any list
+CREATE TABLE suppressed_addresses(
+    email TEXT NOT NULL PRIMARY KEY,
+    reason TEXT NOT NULL,
+    description TEXT NOT NULL,
+    suppressed_at timestamptz NOT NULL DEFAULT now()
+);
+
+INSERT INTO suppressed_addresses (email, reason, description, suppressed_at)
+SELECT DISTINCT ON (email) email, status, suppression_description, suppressed_at
+FROM subscriptions
+WHERE status IN ('bounced', 'complained')
+ORDER BY email, suppressed_at DESC;
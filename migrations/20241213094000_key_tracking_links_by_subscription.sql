@@ -0,0 +1,28 @@
+-- Tracking links belong to the subscription they were sent to and go away with
+-- it, instead of keeping the address of former subscribers forever. A recipient
+-- has a single set of links per issue, reused when a delivery is retried.
+ALTER TABLE newsletter_tracking_links
+    ADD COLUMN subscriber_id uuid NULL REFERENCES subscriptions (id) ON DELETE CASCADE;
+
+UPDATE newsletter_tracking_links k
+SET subscriber_id = s.id
+FROM newsletter_issues i
+JOIN subscriptions s ON s.list_id = i.list_id
+WHERE i.newsletter_issue_id = k.newsletter_issue_id AND s.email = k.subscriber_email;
+
+DELETE FROM newsletter_tracking_links WHERE subscriber_id IS NULL;
+
+-- Links issued again by retries are merged into the first ones
+DELETE FROM newsletter_tracking_links k
+USING newsletter_tracking_links first
+WHERE first.newsletter_issue_id = k.newsletter_issue_id
+    AND first.subscriber_id = k.subscriber_id
+    AND first.url IS NOT DISTINCT FROM k.url
+    AND (first.created_at, first.tracking_id) < (k.created_at, k.tracking_id);
+
+ALTER TABLE newsletter_tracking_links
+    ALTER COLUMN subscriber_id SET NOT NULL,
+    DROP COLUMN subscriber_email;
+
+CREATE UNIQUE INDEX newsletter_tracking_links_recipient_idx
+    ON newsletter_tracking_links (newsletter_issue_id, subscriber_id, url) NULLS NOT DISTINCT;
@@ -0,0 +1,25 @@
+//! One-off step of the migration to hashed subscription tokens: hashes the raw
+//! tokens stored before it with the key of the configuration. Run it once after
+//! `20241129090000_hash_subscription_tokens.sql`, with the same configuration as
+//! the application.
+use zero2prod::configuration::get_configuration;
+use zero2prod::outbound::db::postgres_db::PostgresDb;
+use zero2prod::outbound::telemetry::init_logger;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let configuration = get_configuration().expect("Failed to read configuration");
+    init_logger(
+        "hash_legacy_tokens",
+        &configuration.log_level(),
+        std::io::stdout,
+    );
+
+    let repo = PostgresDb::new(
+        &configuration.database,
+        configuration.application.token_hmac_secret,
+    );
+    let hashed_tokens = repo.hash_legacy_tokens().await?;
+    tracing::info!(hashed_tokens, "Hashed legacy subscription tokens");
+    Ok(())
+}
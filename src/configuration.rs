@@ -1,4 +1,8 @@
+use crate::domain::new_subscriber::models::confirmation::ConfirmationPolicy;
 use crate::domain::new_subscriber::models::email::{EmailError, SubscriberEmail};
+use crate::domain::new_subscriber::models::locale::{Locale, LocaleError};
+use crate::domain::new_subscriber::models::retention::RetentionPolicy;
+use chrono::TimeDelta;
 use secrecy::ExposeSecret;
 use secrecy::Secret;
 use serde_aux::field_attributes::deserialize_number_from_string;
@@ -39,6 +43,7 @@ pub struct Settings {
     pub database: DatabaseSettings,
     pub application: ApplicationSettings,
     pub email_client: EmailClientSettings,
+    pub subscriptions: SubscriptionSettings,
 }
 
 impl Settings {
@@ -50,6 +55,50 @@ impl Settings {
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct GeneralSettings {
     pub log_level: String,
+    /// Locale of the subscribers that did not pick a supported one, and of the
+    /// issues as they are written
+    pub default_locale: String,
+}
+
+impl GeneralSettings {
+    pub fn default_locale(&self) -> Result<Locale, LocaleError> {
+        Locale::parse(&self.default_locale)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct SubscriptionSettings {
+    /// Minutes before a pending subscriber who signs up again gets another confirmation email
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub confirmation_cooldown_minutes: i64,
+    /// Days after which subscriptions that were never confirmed are removed
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub pending_confirmation_days: i64,
+    /// Days after which unconfirmed cancellations are completed
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub cancellation_pending_days: i64,
+    /// Minutes between two runs of the retention job
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub retention_interval_minutes: u64,
+}
+
+impl SubscriptionSettings {
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        ConfirmationPolicy {
+            resend_cooldown: TimeDelta::minutes(self.confirmation_cooldown_minutes),
+        }
+    }
+
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            pending_confirmation: TimeDelta::days(self.pending_confirmation_days),
+            cancellation_pending: TimeDelta::days(self.cancellation_pending_days),
+        }
+    }
+
+    pub fn retention_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.retention_interval_minutes * 60)
+    }
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -58,6 +107,18 @@ pub struct EmailClientSettings {
     pub sender_email: String,
     pub authorization_token: Secret<String>,
     pub timeout_milliseconds: u64,
+    pub retry: RetrySettings,
+    /// Directory whose files override the default templates of `templates/emails`,
+    /// laid out the same way: one folder per locale
+    #[serde(default)]
+    pub templates_directory: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct RetrySettings {
+    pub max_attempts: u32,
+    pub initial_backoff_milliseconds: u64,
+    pub max_backoff_milliseconds: u64,
 }
 
 impl EmailClientSettings {
@@ -76,6 +137,11 @@ pub struct ApplicationSettings {
     pub base_url: String,
     pub hmac_secret: Secret<String>,
     pub redis_uri: Secret<String>,
+    /// Shared with the email provider, which sends it in the `X-Webhook-Secret`
+    /// header of its bounce and spam complaint webhooks
+    pub webhook_secret: Secret<String>,
+    /// Key of the HMAC under which subscription tokens are stored
+    pub token_hmac_secret: Secret<String>,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
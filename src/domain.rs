@@ -1,3 +1,4 @@
 pub mod auth;
+pub mod idempotency;
 pub mod new_subscriber;
 pub mod newsletter;
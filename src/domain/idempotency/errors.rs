@@ -0,0 +1,9 @@
+#[derive(thiserror::Error, Debug)]
+pub enum IdempotencyError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    #[error("Request is still being processed: {0}")]
+    Conflict(String),
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
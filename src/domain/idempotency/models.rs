@@ -0,0 +1,86 @@
+use super::errors::IdempotencyError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    const MAX_LENGTH: usize = 50;
+
+    pub fn parse(s: String) -> Result<IdempotencyKey, IdempotencyError> {
+        if s.is_empty() {
+            return Err(IdempotencyError::ValidationError(
+                "The idempotency key cannot be empty".to_string(),
+            ));
+        }
+        if s.len() >= Self::MAX_LENGTH {
+            return Err(IdempotencyError::ValidationError(format!(
+                "The idempotency key must be shorter than {} characters",
+                Self::MAX_LENGTH
+            )));
+        }
+        Ok(Self(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for IdempotencyKey {
+    type Error = IdempotencyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        IdempotencyKey::parse(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderPair {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+/// Response returned to the first request made with a given idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedResponse {
+    pub status_code: u16,
+    pub headers: Vec<HeaderPair>,
+    pub body: Vec<u8>,
+}
+
+/// Response to a request that is saved in the same transaction as the changes
+/// the request made: a retry either replays it or finds that nothing changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentResponse {
+    pub user_id: uuid::Uuid,
+    pub key: IdempotencyKey,
+    pub response: SavedResponse,
+}
+
+pub enum NextAction {
+    StartProcessing,
+    ReturnSavedResponse(SavedResponse),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdempotencyKey;
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn empty_idempotency_key_is_rejected() {
+        assert_err!(IdempotencyKey::parse("".to_string()));
+    }
+
+    #[test]
+    fn long_idempotency_key_is_rejected() {
+        let key = "a".repeat(IdempotencyKey::MAX_LENGTH);
+        assert_err!(IdempotencyKey::parse(key));
+    }
+
+    #[test]
+    fn uuid_idempotency_key_is_accepted() {
+        let key = uuid::Uuid::new_v4().to_string();
+        assert_ok!(IdempotencyKey::parse(key));
+    }
+}
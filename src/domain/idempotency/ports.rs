@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+
+use super::{
+    errors::IdempotencyError,
+    models::{IdempotencyKey, NextAction, SavedResponse},
+};
+
+#[async_trait]
+pub trait IdempotencyRepository: Clone + Send + Sync + 'static {
+    /// Reserves the key for the given user. Returns `false` if another request
+    /// already holds the key and has not been idle for longer than `stale_after`.
+    async fn try_reserve(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        stale_after: std::time::Duration,
+    ) -> Result<bool, IdempotencyError>;
+
+    async fn get_saved_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<Option<SavedResponse>, IdempotencyError>;
+
+    async fn save_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        response: &SavedResponse,
+    ) -> Result<(), IdempotencyError>;
+
+    /// Drops a reservation that never got a response so the key can be reused
+    async fn release(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<(), IdempotencyError>;
+}
+
+#[async_trait]
+pub trait IdempotencyService: Clone + Send + Sync + 'static {
+    /// Decides whether a request should be processed or answered with a previously saved response.
+    /// Requests arriving while another one with the same key is in flight wait for its response.
+    async fn try_processing(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<NextAction, IdempotencyError>;
+
+    async fn save_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        response: &SavedResponse,
+    ) -> Result<(), IdempotencyError>;
+
+    async fn release(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<(), IdempotencyError>;
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+
+use super::{
+    errors::IdempotencyError,
+    models::{IdempotencyKey, NextAction, SavedResponse},
+    ports::{IdempotencyRepository, IdempotencyService},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct BlogIdempotency<R>
+where
+    R: IdempotencyRepository,
+{
+    pub repo: Arc<R>,
+}
+
+impl<R> BlogIdempotency<R>
+where
+    R: IdempotencyRepository,
+{
+    /// Time after which an unanswered reservation is considered abandoned
+    const STALE_AFTER: Duration = Duration::from_secs(60);
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    const MAX_POLLS: usize = 100;
+
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R> IdempotencyService for BlogIdempotency<R>
+where
+    R: IdempotencyRepository,
+{
+    #[tracing::instrument(name = "Try processing idempotent request", skip(self, key))]
+    async fn try_processing(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<NextAction, IdempotencyError> {
+        for _ in 0..Self::MAX_POLLS {
+            if self
+                .repo
+                .try_reserve(user_id, key, Self::STALE_AFTER)
+                .await?
+            {
+                return Ok(NextAction::StartProcessing);
+            }
+            if let Some(saved_response) = self.repo.get_saved_response(user_id, key).await? {
+                return Ok(NextAction::ReturnSavedResponse(saved_response));
+            }
+            tokio::time::sleep(Self::POLL_INTERVAL).await;
+        }
+
+        Err(IdempotencyError::Conflict(format!(
+            "Idempotency key {} is held by a request in progress",
+            key.as_str()
+        )))
+    }
+
+    async fn save_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        response: &SavedResponse,
+    ) -> Result<(), IdempotencyError> {
+        self.repo.save_response(user_id, key, response).await
+    }
+
+    async fn release(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<(), IdempotencyError> {
+        self.repo.release(user_id, key).await
+    }
+}
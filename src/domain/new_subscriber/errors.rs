@@ -1,4 +1,8 @@
-use crate::domain::new_subscriber::models::{email::EmailError, name::SubscriberNameError};
+use crate::domain::new_subscriber::models::{
+    blocklist::BlocklistError, email::EmailError, list::ListError, locale::LocaleError,
+    name::SubscriberNameError, preferences::PreferencesError, suppression::SuppressionError,
+    tag::TagError,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum SubscriberError {
@@ -12,6 +16,12 @@ pub enum SubscriberError {
     Unexpected(#[from] anyhow::Error),
 }
 
+impl From<BlocklistError> for SubscriberError {
+    fn from(value: BlocklistError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
 impl From<EmailError> for SubscriberError {
     fn from(value: EmailError) -> Self {
         Self::ValidationError(value.to_string())
@@ -23,3 +33,33 @@ impl From<SubscriberNameError> for SubscriberError {
         Self::ValidationError(value.to_string())
     }
 }
+
+impl From<ListError> for SubscriberError {
+    fn from(value: ListError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
+impl From<LocaleError> for SubscriberError {
+    fn from(value: LocaleError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
+impl From<PreferencesError> for SubscriberError {
+    fn from(value: PreferencesError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
+impl From<TagError> for SubscriberError {
+    fn from(value: TagError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
+impl From<SuppressionError> for SubscriberError {
+    fn from(value: SuppressionError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
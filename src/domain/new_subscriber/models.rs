@@ -1,4 +1,13 @@
+pub mod blocklist;
+pub mod confirmation;
 pub mod email;
+pub mod list;
+pub mod locale;
 pub mod name;
+pub mod outcome;
+pub mod preferences;
+pub mod retention;
 pub mod subscriber;
+pub mod suppression;
+pub mod tag;
 pub mod token;
@@ -0,0 +1,194 @@
+use super::email::{EmailError, SubscriberEmail};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlocklistError {
+    #[error("Invalid blocked address: {0}")]
+    InvalidAddress(#[from] EmailError),
+    #[error("Invalid blocked domain: {0}")]
+    InvalidDomain(String),
+    #[error("Unknown kind of blocked pattern: {0}")]
+    UnknownKind(String),
+}
+
+/// What the blocklist rejects: one address, or every address of a domain and
+/// of its subdomains
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockedPattern {
+    Address(String),
+    Domain(String),
+}
+
+impl BlockedPattern {
+    pub const ADDRESS: &'static str = "address";
+    pub const DOMAIN: &'static str = "domain";
+
+    /// Parses an address (`spam@example.com`) or a domain (`example.com` or
+    /// `@example.com`). Patterns are compared case-insensitively.
+    pub fn parse(pattern: &str) -> Result<Self, BlocklistError> {
+        let pattern = pattern.trim().to_lowercase();
+        match pattern.strip_prefix('@') {
+            Some(domain) => Self::parse_domain(domain),
+            None if pattern.contains('@') => Ok(Self::Address(
+                SubscriberEmail::parse(pattern)?.as_str().to_string(),
+            )),
+            None => Self::parse_domain(&pattern),
+        }
+    }
+
+    fn parse_domain(domain: &str) -> Result<Self, BlocklistError> {
+        let is_valid = domain.contains('.')
+            && domain.split('.').all(|label| {
+                !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        if !is_valid {
+            return Err(BlocklistError::InvalidDomain(domain.to_string()));
+        }
+        Ok(Self::Domain(domain.to_string()))
+    }
+
+    /// Rebuilds a pattern as stored
+    pub fn from_parts(kind: &str, value: String) -> Result<Self, BlocklistError> {
+        match kind {
+            Self::ADDRESS => Ok(Self::Address(value)),
+            Self::DOMAIN => Ok(Self::Domain(value)),
+            other => Err(BlocklistError::UnknownKind(other.to_string())),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Address(_) => Self::ADDRESS,
+            Self::Domain(_) => Self::DOMAIN,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Address(value) | Self::Domain(value) => value,
+        }
+    }
+
+    /// The domain patterns that block `email`: its domain and every parent
+    /// domain, e.g. `mail.example.com` and `example.com`
+    pub fn blocking_domains(email: &SubscriberEmail) -> Vec<String> {
+        let address = email.as_str().to_lowercase();
+        let Some((_, domain)) = address.rsplit_once('@') else {
+            return Vec::new();
+        };
+        let mut domains = vec![domain.to_string()];
+        let mut rest = domain;
+        while let Some((_, parent)) = rest.split_once('.') {
+            if !parent.contains('.') {
+                break;
+            }
+            domains.push(parent.to_string());
+            rest = parent;
+        }
+        domains
+    }
+}
+
+impl std::fmt::Display for BlockedPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{}", address),
+            Self::Domain(domain) => write!(f, "@{}", domain),
+        }
+    }
+}
+
+/// Entry added from the admin pages
+#[derive(serde::Deserialize, Debug)]
+pub struct BlockRequest {
+    pub pattern: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockedEntry {
+    pub blocked_id: uuid::Uuid,
+    pub pattern: BlockedPattern,
+    /// Why the pattern was blocked, e.g. a legal request
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockedPattern, BlocklistError};
+    use crate::domain::new_subscriber::models::email::SubscriberEmail;
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn addresses_are_parsed_in_lowercase() {
+        let pattern = assert_ok!(BlockedPattern::parse(" Ursula@Domain.com "));
+
+        assert_eq!(
+            pattern,
+            BlockedPattern::Address("ursula@domain.com".to_string())
+        );
+    }
+
+    #[test]
+    fn domains_are_parsed_with_or_without_an_at_sign() {
+        for input in ["example.com", "@Example.com"] {
+            let pattern = assert_ok!(BlockedPattern::parse(input));
+
+            assert_eq!(pattern, BlockedPattern::Domain("example.com".to_string()));
+            assert_eq!(pattern.to_string(), "@example.com");
+        }
+    }
+
+    #[test]
+    fn invalid_domains_are_rejected() {
+        for input in [
+            "",
+            "@",
+            "localhost",
+            "example..com",
+            "exa mple.com",
+            "@.com",
+        ] {
+            assert!(
+                matches!(
+                    BlockedPattern::parse(input),
+                    Err(BlocklistError::InvalidDomain(_))
+                ),
+                "{} was accepted",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_addresses_are_rejected() {
+        assert_err!(BlockedPattern::parse("ursula@"));
+    }
+
+    #[test]
+    fn an_address_is_blocked_by_its_domain_and_parent_domains() {
+        let email = SubscriberEmail::parse("ursula@Mail.Example.com".to_string()).unwrap();
+
+        assert_eq!(
+            BlockedPattern::blocking_domains(&email),
+            vec!["mail.example.com", "example.com"]
+        );
+    }
+
+    #[test]
+    fn patterns_round_trip_through_their_stored_parts() {
+        for pattern in [
+            BlockedPattern::Address("ursula@domain.com".to_string()),
+            BlockedPattern::Domain("domain.com".to_string()),
+        ] {
+            let stored = assert_ok!(BlockedPattern::from_parts(
+                pattern.kind(),
+                pattern.value().to_string()
+            ));
+
+            assert_eq!(stored, pattern);
+        }
+    }
+}
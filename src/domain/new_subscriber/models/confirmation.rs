@@ -0,0 +1,9 @@
+use chrono::TimeDelta;
+
+/// How often confirmation emails may be sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfirmationPolicy {
+    /// Minimum delay between two confirmation emails sent to a subscription.
+    /// Subscribing again within it sends nothing.
+    pub resend_cooldown: TimeDelta,
+}
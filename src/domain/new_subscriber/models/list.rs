@@ -0,0 +1,107 @@
+use serde::Deserialize;
+
+pub type ListId = uuid::Uuid;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListError {
+    #[error(
+        "List names must have between 1 and {} characters",
+        ListName::MAX_LENGTH
+    )]
+    InvalidName,
+    #[error(
+        "List slugs must have between 1 and {} lowercase letters, digits or '-': {0}",
+        ListSlug::MAX_LENGTH
+    )]
+    InvalidSlug(String),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NewListRequest {
+    pub name: String,
+    pub slug: String,
+}
+
+/// A publication subscribers sign up to. An email can subscribe to several
+/// lists, each subscription having its own status.
+#[derive(Debug, Clone)]
+pub struct MailingList {
+    pub list_id: ListId,
+    pub slug: ListSlug,
+    pub name: ListName,
+    /// Receives the subscriptions and issues that do not name a list
+    pub is_default: bool,
+}
+
+/// Identifies a list in URLs and on the subscribe forms, e.g. `rust-weekly`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListSlug(String);
+
+impl ListSlug {
+    const MAX_LENGTH: usize = 64;
+
+    pub fn parse(slug: &str) -> Result<ListSlug, ListError> {
+        let slug = slug.trim();
+        if slug.is_empty()
+            || slug.len() > Self::MAX_LENGTH
+            || !slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(ListError::InvalidSlug(slug.to_string()));
+        }
+        Ok(Self(slug.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListName(String);
+
+impl ListName {
+    const MAX_LENGTH: usize = 100;
+
+    pub fn parse(name: &str) -> Result<ListName, ListError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > Self::MAX_LENGTH {
+            return Err(ListError::InvalidName);
+        }
+        Ok(Self(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ListName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListName, ListSlug};
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn slugs_are_lowercase_words_separated_by_dashes() {
+        assert_ok!(ListSlug::parse("rust-weekly"));
+        assert_ok!(ListSlug::parse(" newsletter2 "));
+        assert_err!(ListSlug::parse("Rust Weekly"));
+        assert_err!(ListSlug::parse("rust_weekly"));
+        assert_err!(ListSlug::parse(""));
+        assert_err!(ListSlug::parse(&"a".repeat(65)));
+    }
+
+    #[test]
+    fn names_cannot_be_blank_or_too_long() {
+        assert_eq!(ListName::parse(" Rust ").unwrap().as_str(), "Rust");
+        assert_err!(ListName::parse("  "));
+        assert_err!(ListName::parse(&"a".repeat(101)));
+    }
+}
@@ -0,0 +1,80 @@
+#[derive(Debug, thiserror::Error)]
+pub enum LocaleError {
+    #[error("Unsupported locale: {0}")]
+    Unsupported(String),
+}
+
+/// Language subscribers receive their emails in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::French];
+
+    /// Accepts a language tag such as `fr` or `fr-CA`: only the primary
+    /// language subtag is taken into account
+    pub fn parse(tag: &str) -> Result<Locale, LocaleError> {
+        let language = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.as_str() == language)
+            .ok_or_else(|| LocaleError::Unsupported(tag.to_string()))
+    }
+
+    /// ISO 639-1 code of the language
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::French => "fr",
+        }
+    }
+
+    /// Name of the language, written in that language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::French => "Français",
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+    use claim::assert_err;
+
+    #[test]
+    fn region_subtags_are_ignored() {
+        assert_eq!(Locale::parse("fr-CA").unwrap(), Locale::French);
+        assert_eq!(Locale::parse("en_GB").unwrap(), Locale::English);
+        assert_eq!(Locale::parse(" FR ").unwrap(), Locale::French);
+    }
+
+    #[test]
+    fn unsupported_languages_are_rejected() {
+        assert_err!(Locale::parse("de"));
+        assert_err!(Locale::parse(""));
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for locale in Locale::ALL {
+            assert_eq!(Locale::parse(locale.as_str()).unwrap(), locale);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+/// What a request of a subscriber did, as shown to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionOutcome {
+    /// A confirmation email is on its way. Whether one was sent recently or the
+    /// subscription was already confirmed is not revealed.
+    SignedUp,
+    Confirmed,
+    /// The confirmation link was followed again
+    AlreadyConfirmed,
+    /// The unsubscribe link was followed once, following it again unsubscribes
+    CancellationRequested,
+    Unsubscribed,
+}
+
+impl SubscriptionOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SignedUp => "signed_up",
+            Self::Confirmed => "confirmed",
+            Self::AlreadyConfirmed => "already_confirmed",
+            Self::CancellationRequested => "cancellation_requested",
+            Self::Unsubscribed => "unsubscribed",
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::domain::new_subscriber::models::{
+    email::SubscriberEmail,
+    list::{ListId, MailingList},
+    name::SubscriberName,
+};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreferencesError {
+    #[error("Unknown delivery frequency: {0}")]
+    UnknownFrequency(String),
+    #[error("Subscriptions can be paused for 0 to {} weeks: {0}", Pause::MAX_WEEKS)]
+    InvalidPause(String),
+}
+
+/// How often a subscriber receives the issues of their lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryFrequency {
+    /// Each issue is sent as soon as it is published
+    #[default]
+    EveryIssue,
+    /// The issues of the week are sent together, once a week
+    WeeklyDigest,
+}
+
+impl DeliveryFrequency {
+    pub const ALL: [DeliveryFrequency; 2] = [
+        DeliveryFrequency::EveryIssue,
+        DeliveryFrequency::WeeklyDigest,
+    ];
+
+    pub fn parse(frequency: &str) -> Result<DeliveryFrequency, PreferencesError> {
+        Self::ALL
+            .into_iter()
+            .find(|f| f.as_str() == frequency.trim())
+            .ok_or_else(|| PreferencesError::UnknownFrequency(frequency.to_string()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryFrequency::EveryIssue => "every_issue",
+            DeliveryFrequency::WeeklyDigest => "weekly_digest",
+        }
+    }
+
+    /// Label shown on the preferences page
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeliveryFrequency::EveryIssue => "Every issue, as soon as it is published",
+            DeliveryFrequency::WeeklyDigest => "A weekly digest",
+        }
+    }
+}
+
+/// Change to the pause of a subscription
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pause {
+    /// The subscription stays paused, or not, as it is
+    Unchanged,
+    /// Emails are sent again right away
+    Resume,
+    /// No email is sent for that many weeks
+    Weeks(u32),
+}
+
+impl Pause {
+    const MAX_WEEKS: u32 = 52;
+
+    /// Parses the number of weeks typed in the form, empty meaning unchanged
+    /// and 0 meaning resume
+    pub fn parse(weeks: &str) -> Result<Pause, PreferencesError> {
+        let weeks = weeks.trim();
+        if weeks.is_empty() {
+            return Ok(Self::Unchanged);
+        }
+        match weeks.parse::<u32>() {
+            Ok(0) => Ok(Self::Resume),
+            Ok(n) if n <= Self::MAX_WEEKS => Ok(Self::Weeks(n)),
+            _ => Err(PreferencesError::InvalidPause(weeks.to_string())),
+        }
+    }
+
+    /// End of the pause, `None` when emails are sent again
+    pub fn paused_until(
+        &self,
+        now: DateTime<Utc>,
+        current: Option<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Unchanged => current,
+            Self::Resume => None,
+            Self::Weeks(weeks) => Some(now + chrono::Duration::weeks(i64::from(*weeks))),
+        }
+    }
+}
+
+/// Values submitted on the preferences page
+#[derive(Debug)]
+pub struct PreferencesRequest {
+    pub subscription_token: String,
+    pub name: String,
+    /// Lists that are checked
+    pub lists: Vec<ListId>,
+    pub frequency: String,
+    /// Weeks to pause the subscriptions for, see [`Pause::parse`]
+    pub pause_weeks: String,
+}
+
+/// Validated preferences, applied to every subscription of an email
+#[derive(Debug)]
+pub struct UpdatedPreferences {
+    pub name: SubscriberName,
+    pub lists: Vec<ListId>,
+    pub frequency: DeliveryFrequency,
+    pub pause: Pause,
+}
+
+/// What a subscriber sees on the preferences page
+#[derive(Debug)]
+pub struct SubscriberPreferences {
+    pub email: SubscriberEmail,
+    pub name: SubscriberName,
+    /// List of the subscription the token belongs to. Subscribers leave it
+    /// through the unsubscribe link.
+    pub list_id: ListId,
+    /// Every list, with whether the email is subscribed to it
+    pub lists: Vec<(MailingList, bool)>,
+    pub frequency: DeliveryFrequency,
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl SubscriberPreferences {
+    /// Whether emails are currently held back
+    pub fn is_paused(&self, now: DateTime<Utc>) -> bool {
+        self.paused_until.is_some_and(|until| until > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeliveryFrequency, Pause};
+    use chrono::{TimeZone, Utc};
+    use claim::assert_err;
+
+    #[test]
+    fn frequencies_round_trip() {
+        for frequency in DeliveryFrequency::ALL {
+            assert_eq!(
+                DeliveryFrequency::parse(frequency.as_str()).unwrap(),
+                frequency
+            );
+        }
+        assert_err!(DeliveryFrequency::parse("daily"));
+    }
+
+    #[test]
+    fn pauses_are_parsed_from_a_number_of_weeks() {
+        assert_eq!(Pause::parse(" ").unwrap(), Pause::Unchanged);
+        assert_eq!(Pause::parse("0").unwrap(), Pause::Resume);
+        assert_eq!(Pause::parse("4").unwrap(), Pause::Weeks(4));
+        assert_err!(Pause::parse("53"));
+        assert_err!(Pause::parse("-1"));
+        assert_err!(Pause::parse("two"));
+    }
+
+    #[test]
+    fn pauses_end_after_the_requested_number_of_weeks() {
+        let now = Utc.with_ymd_and_hms(2024, 11, 25, 9, 0, 0).unwrap();
+        let current = Some(Utc.with_ymd_and_hms(2024, 12, 1, 9, 0, 0).unwrap());
+
+        assert_eq!(
+            Pause::Weeks(2).paused_until(now, current),
+            Some(Utc.with_ymd_and_hms(2024, 12, 9, 9, 0, 0).unwrap())
+        );
+        assert_eq!(Pause::Unchanged.paused_until(now, current), current);
+        assert_eq!(Pause::Resume.paused_until(now, current), None);
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// How long subscriptions may wait for their subscriber to act before they
+/// are removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    /// Subscriptions still unconfirmed this long after signing up are removed
+    pub pending_confirmation: TimeDelta,
+    /// Subscriptions whose cancellation was requested this long ago without
+    /// being confirmed are removed, completing the cancellation
+    pub cancellation_pending: TimeDelta,
+}
+
+impl RetentionPolicy {
+    /// Subscriptions made before this instant and still unconfirmed are removed
+    pub fn pending_confirmation_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.pending_confirmation
+    }
+
+    /// Cancellations requested before this instant and still unconfirmed are completed
+    pub fn cancellation_pending_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.cancellation_pending
+    }
+}
+
+/// What a run of the retention job removed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RetentionReport {
+    pub unconfirmed_subscriptions: u64,
+    pub pending_cancellations: u64,
+    /// Tokens of the removed subscriptions
+    pub tokens: u64,
+    /// Tokens of remaining subscriptions that expired
+    pub expired_tokens: u64,
+}
+
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetentionPolicy, RetentionReport};
+    use chrono::{TimeDelta, Utc};
+
+    #[test]
+    fn each_state_has_its_own_cutoff() {
+        let policy = RetentionPolicy {
+            pending_confirmation: TimeDelta::days(7),
+            cancellation_pending: TimeDelta::days(30),
+        };
+        let now = Utc::now();
+
+        assert_eq!(
+            policy.pending_confirmation_cutoff(now),
+            now - TimeDelta::days(7)
+        );
+        assert_eq!(
+            policy.cancellation_pending_cutoff(now),
+            now - TimeDelta::days(30)
+        );
+    }
+
+    #[test]
+    fn a_report_is_empty_when_nothing_was_removed() {
+        assert!(RetentionReport::default().is_empty());
+        assert!(!RetentionReport {
+            tokens: 1,
+            ..RetentionReport::default()
+        }
+        .is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use super::{
     email::{EmailError, SubscriberEmail},
+    locale::{Locale, LocaleError},
     name::{SubscriberName, SubscriberNameError},
+    tag::{SubscriberTag, TagError},
 };
 use crate::domain::new_subscriber::errors::SubscriberError;
 
@@ -8,6 +10,15 @@ use crate::domain::new_subscriber::errors::SubscriberError;
 pub struct NewSubscriberRequest {
     pub email: String,
     pub name: String,
+    /// Language tag picked on the subscribe form or negotiated from `Accept-Language`
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Tags separated by commas, e.g. the topics picked on the subscribe form
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Slug of the list to subscribe to, the default list when missing
+    #[serde(default)]
+    pub list: Option<String>,
 }
 
 impl NewSubscriberRequest {
@@ -15,6 +26,9 @@ impl NewSubscriberRequest {
         Self {
             email: email.to_string(),
             name: name.to_string(),
+            locale: None,
+            tags: None,
+            list: None,
         }
     }
 }
@@ -27,6 +41,10 @@ pub struct NewSubscriber {
     pub email: SubscriberEmail,
     pub name: SubscriberName,
     pub status: SubscriberStatus,
+    /// `None` until the subscriber picks a locale: their emails are then sent
+    /// in the default locale of the deployment
+    pub locale: Option<Locale>,
+    pub tags: Vec<SubscriberTag>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -42,6 +60,10 @@ pub enum SubscriberStatus {
     SubscriptionConfirmed,
     CancellationPendingConfirmation,
     CancellationConfirmed,
+    /// The address permanently rejected our emails
+    Bounced,
+    /// The recipient reported one of our emails as spam
+    Complained,
 }
 
 impl SubscriberStatus {
@@ -50,6 +72,8 @@ impl SubscriberStatus {
     const SUBSCRIBER_NOT_INSERTED: &'static str = "not_inserted";
     const CANCELLATION_PENDING_CONFIRMATION: &'static str = "cancellation_pending";
     const CANCELLATION_CONFIRMED: &'static str = "cancellation_confirmed";
+    const BOUNCED: &'static str = "bounced";
+    const COMPLAINED: &'static str = "complained";
 
     pub fn parse(status: &str) -> Result<SubscriberStatus, SubscriberStatusError> {
         match status {
@@ -62,9 +86,19 @@ impl SubscriberStatus {
                 Ok(SubscriberStatus::CancellationPendingConfirmation)
             }
             Self::CANCELLATION_CONFIRMED => Ok(SubscriberStatus::CancellationConfirmed),
+            Self::BOUNCED => Ok(SubscriberStatus::Bounced),
+            Self::COMPLAINED => Ok(SubscriberStatus::Complained),
             _ => Err(SubscriberStatusError::UnknownStatus(status.into())),
         }
     }
+
+    /// Suppressed subscriptions receive no emails until an administrator reinstates them
+    pub fn is_suppressed(&self) -> bool {
+        matches!(
+            self,
+            SubscriberStatus::Bounced | SubscriberStatus::Complained
+        )
+    }
 }
 
 impl From<SubscriberStatusError> for SubscriberError {
@@ -88,6 +122,8 @@ impl From<SubscriberStatus> for String {
             SubscriberStatus::CancellationConfirmed => {
                 SubscriberStatus::CANCELLATION_CONFIRMED.into()
             }
+            SubscriberStatus::Bounced => SubscriberStatus::BOUNCED.into(),
+            SubscriberStatus::Complained => SubscriberStatus::COMPLAINED.into(),
         }
     }
 }
@@ -98,6 +134,10 @@ pub enum SubscriberValidationError {
     InvalidName(#[from] SubscriberNameError),
     #[error("Invalid subscriber email: {0}")]
     InvalidEmail(#[from] EmailError),
+    #[error("Invalid subscriber locale: {0}")]
+    InvalidLocale(#[from] LocaleError),
+    #[error("Invalid subscriber tags: {0}")]
+    InvalidTags(#[from] TagError),
 }
 
 impl NewSubscriber {
@@ -109,6 +149,8 @@ impl NewSubscriber {
             name: SubscriberName::parse(req.name)
                 .map_err(SubscriberValidationError::InvalidName)?,
             status: SubscriberStatus::NotInserted,
+            locale: req.locale.as_deref().map(Locale::parse).transpose()?,
+            tags: SubscriberTag::parse_list(req.tags.as_deref().unwrap_or_default())?,
         })
     }
 
@@ -118,6 +160,8 @@ impl NewSubscriber {
             status: SubscriberStatus::NotInserted,
             name,
             email,
+            locale: None,
+            tags: Vec::new(),
         }
     }
 
@@ -128,6 +172,14 @@ impl NewSubscriber {
     pub fn with_status(self, status: SubscriberStatus) -> Self {
         Self { status, ..self }
     }
+
+    pub fn with_locale(self, locale: Option<Locale>) -> Self {
+        Self { locale, ..self }
+    }
+
+    pub fn with_tags(self, tags: Vec<SubscriberTag>) -> Self {
+        Self { tags, ..self }
+    }
 }
 
 impl TryFrom<NewSubscriberRequest> for NewSubscriber {
@@ -146,6 +198,7 @@ impl From<SubscriberValidationError> for SubscriberError {
 #[cfg(test)]
 mod tests {
     use super::{NewSubscriber, NewSubscriberRequest, SubscriberStatus, SubscriberValidationError};
+    use crate::domain::new_subscriber::models::locale::Locale;
 
     #[test]
     fn new_subscriber_from_request_with_invalid_name_fails() {
@@ -208,7 +261,7 @@ mod tests {
         assert_eq!(subscriber.email.as_str(), email);
         assert_eq!(subscriber.name.as_str(), name);
         assert_eq!(subscriber.status, SubscriberStatus::NotInserted,);
-        assert!(matches!(subscriber.id, None));
+        assert!(subscriber.id.is_none());
     }
 
     #[test]
@@ -221,6 +274,39 @@ mod tests {
         assert_eq!(subscriber.email.as_str(), email);
         assert_eq!(subscriber.name.as_str(), name);
         assert_eq!(subscriber.status, SubscriberStatus::NotInserted,);
-        assert!(matches!(subscriber.id, None));
+        assert!(subscriber.id.is_none());
+    }
+
+    #[test]
+    fn new_subscriber_keeps_the_requested_locale() {
+        let mut subscriber_request = NewSubscriberRequest::new("dada@ds.com", "dada");
+        subscriber_request.locale = Some("fr-FR".into());
+        let subscriber = NewSubscriber::new(subscriber_request).unwrap();
+
+        assert_eq!(subscriber.locale, Some(Locale::French));
+    }
+
+    #[test]
+    fn new_subscriber_request_with_unsupported_locale_fails() {
+        let mut subscriber_request = NewSubscriberRequest::new("dada@ds.com", "dada");
+        subscriber_request.locale = Some("xx".into());
+        let subscriber = NewSubscriber::new(subscriber_request);
+
+        assert!(matches!(
+            subscriber,
+            Err(SubscriberValidationError::InvalidLocale(_))
+        ));
+    }
+
+    #[test]
+    fn new_subscriber_request_with_invalid_tags_fails() {
+        let mut subscriber_request = NewSubscriberRequest::new("dada@ds.com", "dada");
+        subscriber_request.tags = Some("rust, c++".into());
+        let subscriber = NewSubscriber::new(subscriber_request);
+
+        assert!(matches!(
+            subscriber,
+            Err(SubscriberValidationError::InvalidTags(_))
+        ));
     }
 }
@@ -0,0 +1,172 @@
+use super::{
+    email::{EmailError, SubscriberEmail},
+    list::ListName,
+    subscriber::SubscriberStatus,
+};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SuppressionError {
+    #[error("Invalid suppressed email: {0}")]
+    InvalidEmail(#[from] EmailError),
+    #[error("Unknown suppression reason: {0}")]
+    UnknownReason(String),
+}
+
+/// Why no more emails are sent to an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionReason {
+    /// The address permanently rejected our emails
+    Bounced,
+    /// The recipient reported one of our emails as spam
+    Complained,
+}
+
+impl SuppressionReason {
+    const BOUNCED: &'static str = "bounced";
+    const COMPLAINED: &'static str = "complained";
+
+    pub fn parse(reason: &str) -> Result<Self, SuppressionError> {
+        match reason.trim() {
+            Self::BOUNCED => Ok(Self::Bounced),
+            Self::COMPLAINED => Ok(Self::Complained),
+            other => Err(SuppressionError::UnknownReason(other.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bounced => Self::BOUNCED,
+            Self::Complained => Self::COMPLAINED,
+        }
+    }
+
+    /// Status of the subscriptions of a suppressed address
+    pub fn status(&self) -> SubscriberStatus {
+        match self {
+            Self::Bounced => SubscriberStatus::Bounced,
+            Self::Complained => SubscriberStatus::Complained,
+        }
+    }
+
+    pub fn from_status(status: &SubscriberStatus) -> Option<Self> {
+        match status {
+            SubscriberStatus::Bounced => Some(Self::Bounced),
+            SubscriberStatus::Complained => Some(Self::Complained),
+            _ => None,
+        }
+    }
+}
+
+/// Stops the emails sent to an address: its subscriptions are suppressed and it
+/// can no longer subscribe, whether it had subscriptions or not
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    pub email: SubscriberEmail,
+    pub reason: SuppressionReason,
+    /// What the email provider, or the administrator, reported
+    pub description: String,
+}
+
+/// Suppression added by hand from the admin pages
+#[derive(serde::Deserialize, Debug)]
+pub struct SuppressionRequest {
+    pub email: String,
+    pub reason: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl TryFrom<SuppressionRequest> for Suppression {
+    type Error = SuppressionError;
+
+    fn try_from(req: SuppressionRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            email: SubscriberEmail::parse(req.email.trim().to_string())?,
+            reason: SuppressionReason::parse(&req.reason)?,
+            description: req
+                .description
+                .map(|description| description.trim().to_string())
+                .filter(|description| !description.is_empty())
+                .unwrap_or_else(|| "Added by an administrator".to_string()),
+        })
+    }
+}
+
+/// Suppression lifted by an administrator
+#[derive(serde::Deserialize, Debug)]
+pub struct ReinstateRequest {
+    pub email: String,
+}
+
+/// An address that no longer receives emails, as listed to administrators
+#[derive(Debug, Clone)]
+pub struct SuppressedAddress {
+    pub email: SubscriberEmail,
+    /// Lists of the subscriptions suppressed with the address
+    pub lists: Vec<ListName>,
+    pub reason: SuppressionReason,
+    pub description: String,
+    pub suppressed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Suppression, SuppressionError, SuppressionReason, SuppressionRequest};
+    use crate::domain::new_subscriber::models::subscriber::SubscriberStatus;
+    use claim::{assert_err, assert_ok};
+
+    fn request(email: &str, reason: &str, description: Option<&str>) -> SuppressionRequest {
+        SuppressionRequest {
+            email: email.to_string(),
+            reason: reason.to_string(),
+            description: description.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn reasons_round_trip_through_their_status() {
+        for reason in [SuppressionReason::Bounced, SuppressionReason::Complained] {
+            assert_eq!(
+                SuppressionReason::from_status(&reason.status()),
+                Some(reason)
+            );
+            assert_eq!(
+                assert_ok!(SuppressionReason::parse(reason.as_str())),
+                reason
+            );
+        }
+    }
+
+    #[test]
+    fn active_statuses_are_not_suppressed() {
+        assert_eq!(
+            SuppressionReason::from_status(&SubscriberStatus::SubscriptionConfirmed),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_reasons_are_rejected() {
+        let result = Suppression::try_from(request("ursula@domain.com", "annoyed", None));
+
+        assert!(matches!(result, Err(SuppressionError::UnknownReason(_))));
+    }
+
+    #[test]
+    fn invalid_emails_are_rejected() {
+        assert_err!(Suppression::try_from(request("ursula", "bounced", None)));
+    }
+
+    #[test]
+    fn a_blank_description_falls_back_to_a_default_one() {
+        let suppression = assert_ok!(Suppression::try_from(request(
+            "ursula@domain.com",
+            "bounced",
+            Some("  ")
+        )));
+
+        assert_eq!(suppression.description, "Added by an administrator");
+        assert_eq!(suppression.reason, SuppressionReason::Bounced);
+    }
+}
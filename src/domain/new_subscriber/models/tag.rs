@@ -0,0 +1,114 @@
+#[derive(Debug, thiserror::Error)]
+pub enum TagError {
+    #[error(
+        "Tags must have between 1 and {} characters: {0}",
+        SubscriberTag::MAX_LENGTH
+    )]
+    InvalidLength(String),
+    #[error("Tags can only contain letters, digits, '-' and '_': {0}")]
+    InvalidCharacters(String),
+    #[error("{0} is a segment operator and cannot be used as a tag")]
+    Reserved(String),
+    #[error("A subscriber can have at most {} tags", SubscriberTag::MAX_TAGS)]
+    TooMany,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SubscriberTagsRequest {
+    /// Tags separated by commas or whitespace
+    #[serde(default)]
+    pub tags: String,
+}
+
+/// Label used to target a part of the subscriber list, e.g. `rust` or `beta`.
+/// Tags are case-insensitive and stored in lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriberTag(String);
+
+impl SubscriberTag {
+    const MAX_LENGTH: usize = 32;
+    const MAX_TAGS: usize = 20;
+    /// Words that have a meaning in segment expressions
+    const RESERVED: [&'static str; 3] = ["and", "or", "not"];
+
+    pub fn parse(tag: &str) -> Result<SubscriberTag, TagError> {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.chars().count() > Self::MAX_LENGTH {
+            return Err(TagError::InvalidLength(tag));
+        }
+        if !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(TagError::InvalidCharacters(tag));
+        }
+        if Self::RESERVED.contains(&tag.as_str()) {
+            return Err(TagError::Reserved(tag));
+        }
+        Ok(Self(tag))
+    }
+
+    /// Parses tags separated by commas or whitespace, as typed in a form.
+    /// Duplicates are dropped and the tags are sorted.
+    pub fn parse_list(tags: &str) -> Result<Vec<SubscriberTag>, TagError> {
+        let mut parsed = tags
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|tag| !tag.is_empty())
+            .map(SubscriberTag::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        parsed.sort();
+        parsed.dedup();
+        if parsed.len() > Self::MAX_TAGS {
+            return Err(TagError::TooMany);
+        }
+        Ok(parsed)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for SubscriberTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubscriberTag;
+    use claim::assert_err;
+
+    #[test]
+    fn tags_are_lowercased() {
+        assert_eq!(SubscriberTag::parse(" Rust ").unwrap().as_str(), "rust");
+    }
+
+    #[test]
+    fn invalid_tags_are_rejected() {
+        assert_err!(SubscriberTag::parse(""));
+        assert_err!(SubscriberTag::parse("early adopter"));
+        assert_err!(SubscriberTag::parse("c++"));
+        assert_err!(SubscriberTag::parse(&"a".repeat(33)));
+    }
+
+    #[test]
+    fn segment_operators_cannot_be_used_as_tags() {
+        assert_err!(SubscriberTag::parse("NOT"));
+        assert_err!(SubscriberTag::parse("and"));
+    }
+
+    #[test]
+    fn tag_lists_are_sorted_and_deduplicated() {
+        let tags = SubscriberTag::parse_list("rust, beta,Rust  early_adopter,").unwrap();
+
+        let tags: Vec<_> = tags.iter().map(SubscriberTag::as_str).collect();
+        assert_eq!(tags, ["beta", "early_adopter", "rust"]);
+    }
+
+    #[test]
+    fn empty_tag_lists_are_accepted() {
+        assert!(SubscriberTag::parse_list(" , ").unwrap().is_empty());
+    }
+}
@@ -1,5 +1,9 @@
 use crate::domain::new_subscriber::errors::SubscriberError;
+use chrono::{DateTime, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
 
 #[derive(thiserror::Error, Debug)]
 pub enum SubscriptionTokenError {
@@ -22,6 +26,127 @@ impl From<SubscriptionTokenError> for SubscriberError {
         Self::ValidationError(error.to_string())
     }
 }
+
+/// Why a stored token cannot be used
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum TokenRejection {
+    #[error("This link cannot be used to {}", .expected.action())]
+    WrongPurpose { expected: TokenPurpose },
+    #[error("This link has expired")]
+    Expired,
+    #[error("This link has already been used")]
+    AlreadyUsed,
+}
+
+impl From<TokenRejection> for SubscriberError {
+    fn from(error: TokenRejection) -> Self {
+        Self::AuthError(error.to_string())
+    }
+}
+
+/// What a token lets its holder do. Each token is issued for a single purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPurpose {
+    /// Sent in the confirmation email, used once
+    Confirm,
+    /// Sent at the bottom of each newsletter
+    Unsubscribe,
+    /// Sent at the bottom of each newsletter, opens the preferences page
+    Preferences,
+}
+
+impl TokenPurpose {
+    pub fn parse(purpose: &str) -> Result<TokenPurpose, SubscriberError> {
+        match purpose {
+            "confirm" => Ok(Self::Confirm),
+            "unsubscribe" => Ok(Self::Unsubscribe),
+            "preferences" => Ok(Self::Preferences),
+            _ => Err(SubscriberError::ValidationError(format!(
+                "Unknown token purpose: {}",
+                purpose
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Confirm => "confirm",
+            Self::Unsubscribe => "unsubscribe",
+            Self::Preferences => "preferences",
+        }
+    }
+
+    /// How long a token stays valid once issued. Links in newsletters keep
+    /// working long after the issue was sent, and unsubscribe links, which mail
+    /// clients also use for one-click unsubscribe, for years.
+    pub fn lifetime(&self) -> TimeDelta {
+        match self {
+            Self::Confirm => TimeDelta::days(7),
+            Self::Preferences => TimeDelta::days(60),
+            Self::Unsubscribe => TimeDelta::days(730),
+        }
+    }
+
+    /// Whether the token stops working once it has been used
+    pub fn is_single_use(&self) -> bool {
+        matches!(self, Self::Confirm)
+    }
+
+    fn action(&self) -> &'static str {
+        match self {
+            Self::Confirm => "confirm a subscription",
+            Self::Unsubscribe => "unsubscribe",
+            Self::Preferences => "manage preferences",
+        }
+    }
+}
+
+/// A token as stored when it was issued
+#[derive(Debug, Clone)]
+pub struct IssuedToken {
+    pub subscriber_id: uuid::Uuid,
+    pub purpose: TokenPurpose,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl IssuedToken {
+    /// Checks that the token was issued for `purpose` and can still be used at `now`
+    pub fn ensure_usable_for(
+        &self,
+        purpose: TokenPurpose,
+        now: DateTime<Utc>,
+    ) -> Result<(), TokenRejection> {
+        if self.purpose != purpose {
+            return Err(TokenRejection::WrongPurpose { expected: purpose });
+        }
+        if self.expires_at <= now {
+            return Err(TokenRejection::Expired);
+        }
+        if self.purpose.is_single_use() && self.used_at.is_some() {
+            return Err(TokenRejection::AlreadyUsed);
+        }
+        Ok(())
+    }
+}
+
+/// Tokens of the links at the bottom of an email sent to a confirmed subscriber
+#[derive(Debug, Clone)]
+pub struct LinkTokens {
+    pub unsubscribe: SubscriptionToken,
+    pub preferences: SubscriptionToken,
+}
+
+impl LinkTokens {
+    /// Random tokens that were never stored, for emails that are not addressed
+    /// to a subscriber: their links lead nowhere
+    pub fn placeholder() -> Self {
+        Self {
+            unsubscribe: SubscriptionToken::new(),
+            preferences: SubscriptionToken::new(),
+        }
+    }
+}
 #[derive(serde::Deserialize, Debug)]
 pub struct SubscriptionTokenRequest {
     pub subscription_token: String,
@@ -34,8 +159,10 @@ impl TryFrom<SubscriptionTokenRequest> for SubscriptionToken {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SubscriptionToken(String);
+/// A token as sent in an email. The raw value is never stored, only its
+/// keyed hash, and it is wiped from memory once dropped.
+#[derive(Debug, Clone)]
+pub struct SubscriptionToken(Secret<String>);
 
 impl SubscriptionToken {
     const MAX_LENGTH: usize = 40;
@@ -54,7 +181,7 @@ impl SubscriptionToken {
                 s.clone(),
             ));
         }
-        Ok(Self(s))
+        Ok(Self(Secret::new(s)))
     }
 
     pub fn new() -> Self {
@@ -67,7 +194,22 @@ impl SubscriptionToken {
     }
 
     pub fn as_str(&self) -> &str {
-        &self.0
+        self.0.expose_secret()
+    }
+
+    /// HMAC-SHA256 of the token under `key`, hex encoded. Tokens are stored and
+    /// looked up by this value only.
+    pub fn hash(&self, key: &Secret<String>) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.expose_secret().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(self.as_str().as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl PartialEq for SubscriptionToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
     }
 }
 
@@ -91,9 +233,87 @@ fn is_alphanumeric(token: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::SubscriptionToken;
+    use super::{IssuedToken, SubscriptionToken, TokenPurpose, TokenRejection};
+    use chrono::{TimeDelta, Utc};
     use claim::{assert_err, assert_ok};
     use rand::{distributions::Alphanumeric, seq::SliceRandom, Rng};
+    use secrecy::Secret;
+
+    fn issued(purpose: TokenPurpose) -> IssuedToken {
+        IssuedToken {
+            subscriber_id: uuid::Uuid::new_v4(),
+            purpose,
+            expires_at: Utc::now() + purpose.lifetime(),
+            used_at: None,
+        }
+    }
+
+    #[test]
+    fn tokens_are_only_usable_for_their_purpose() {
+        let token = issued(TokenPurpose::Confirm);
+
+        assert_ok!(token.ensure_usable_for(TokenPurpose::Confirm, Utc::now()));
+        assert_eq!(
+            token.ensure_usable_for(TokenPurpose::Unsubscribe, Utc::now()),
+            Err(TokenRejection::WrongPurpose {
+                expected: TokenPurpose::Unsubscribe
+            })
+        );
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let token = issued(TokenPurpose::Unsubscribe);
+        let later = Utc::now() + TokenPurpose::Unsubscribe.lifetime() + TimeDelta::minutes(1);
+
+        assert_eq!(
+            token.ensure_usable_for(TokenPurpose::Unsubscribe, later),
+            Err(TokenRejection::Expired)
+        );
+    }
+
+    #[test]
+    fn only_single_use_tokens_are_rejected_once_used() {
+        for purpose in [
+            TokenPurpose::Confirm,
+            TokenPurpose::Unsubscribe,
+            TokenPurpose::Preferences,
+        ] {
+            let token = IssuedToken {
+                used_at: Some(Utc::now()),
+                ..issued(purpose)
+            };
+
+            let outcome = token.ensure_usable_for(purpose, Utc::now());
+
+            if purpose.is_single_use() {
+                assert_eq!(outcome, Err(TokenRejection::AlreadyUsed));
+            } else {
+                assert_ok!(outcome);
+            }
+        }
+    }
+
+    #[test]
+    fn hashes_depend_on_the_token_and_the_key() {
+        let key = Secret::new("key".to_string());
+        let token = SubscriptionToken::new();
+
+        assert_eq!(token.hash(&key), token.clone().hash(&key));
+        assert_ne!(token.hash(&key), SubscriptionToken::new().hash(&key));
+        assert_ne!(
+            token.hash(&key),
+            token.hash(&Secret::new("another key".to_string()))
+        );
+        assert!(!token.hash(&key).contains(token.as_str()));
+    }
+
+    #[test]
+    fn debug_output_does_not_reveal_the_token() {
+        let token = SubscriptionToken::new();
+
+        assert!(!format!("{:?}", token).contains(token.as_str()));
+    }
 
     #[test]
     fn empty_token_is_invalid() {
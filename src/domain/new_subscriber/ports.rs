@@ -1,35 +1,145 @@
 use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
 
 use super::{
     errors::SubscriberError,
     models::{
+        blocklist::{BlockRequest, BlockedEntry, BlockedPattern},
         email::SubscriberEmail,
+        list::{ListId, ListName, ListSlug, MailingList, NewListRequest},
+        locale::Locale,
+        outcome::SubscriptionOutcome,
+        preferences::{PreferencesRequest, SubscriberPreferences, UpdatedPreferences},
+        retention::RetentionReport,
         subscriber::{NewSubscriber, NewSubscriberRequest},
-        token::{SubscriptionToken, SubscriptionTokenRequest},
+        suppression::{ReinstateRequest, SuppressedAddress, Suppression},
+        tag::{SubscriberTag, SubscriberTagsRequest},
+        token::{IssuedToken, SubscriptionToken, SubscriptionTokenRequest, TokenPurpose},
     },
 };
 
+#[async_trait]
+/// Represents a store of mailing lists, shared by subscriptions and newsletter issues
+pub trait ListRepository: Clone + Send + Sync + 'static {
+    /// Lists the mailing lists, the default one first
+    async fn get_lists(&self) -> Result<Vec<MailingList>, SubscriberError>;
+
+    async fn get_list(&self, list_id: ListId) -> Result<MailingList, SubscriberError>;
+
+    async fn get_list_by_slug(&self, slug: &ListSlug) -> Result<MailingList, SubscriberError>;
+
+    async fn get_default_list(&self) -> Result<MailingList, SubscriberError>;
+
+    /// Creates a mailing list. Slugs are unique.
+    async fn add_list(
+        &self,
+        name: &ListName,
+        slug: &ListSlug,
+    ) -> Result<MailingList, SubscriberError>;
+}
+
 #[async_trait]
 ///  Represents a store of subscriber data
-pub trait SubscriberRepository: Clone + Send + Sync + 'static {
-    /// Asynchronously retrieves a subscriber and token if it exists,
-    ///  or creates a new entry for the provided `NewSubscriberRequest`
+pub trait SubscriberRepository: ListRepository {
+    /// Asynchronously retrieves a subscriber of the list if it exists, or creates a new
+    /// entry for the provided `NewSubscriberRequest`. When the subscription is new, or
+    /// waiting for a confirmation and no confirmation email was sent within
+    /// `resend_cooldown`, `token` is stored as a confirmation token, the email is recorded
+    /// as sent and the token is handed back to be sent.
     async fn retrieve_or_insert(
         &self,
+        list_id: ListId,
         subscriber: NewSubscriberRequest,
         token: SubscriptionToken,
-    ) -> Result<(NewSubscriber, SubscriptionToken), SubscriberError>;
+        resend_cooldown: TimeDelta,
+    ) -> Result<(NewSubscriber, Option<SubscriptionToken>), SubscriberError>;
 
     /// Asynchronously updates a subscriber in repository
     async fn update(&self, subscriber: NewSubscriber) -> Result<(), SubscriberError>;
 
-    /// Asynchronously retrieve a subscriber from a token
+    /// Asynchronously retrieves a stored token with the subscriber it was issued to
     async fn retrieve_from_token(
         &self,
         token: &SubscriptionToken,
-    ) -> Result<NewSubscriber, SubscriberError>;
+    ) -> Result<(NewSubscriber, IssuedToken), SubscriberError>;
+
+    /// Stores a new token of a subscription, valid for the lifetime of `purpose`
+    async fn issue_token(
+        &self,
+        subscriber_id: uuid::Uuid,
+        purpose: TokenPurpose,
+    ) -> Result<SubscriptionToken, SubscriberError>;
+
+    /// Records that a token has been used, which matters for single-use tokens
+    async fn mark_token_used(&self, token: &SubscriptionToken) -> Result<(), SubscriberError>;
 
     async fn delete(&self, subscriber: NewSubscriber) -> Result<(), SubscriberError>;
+
+    /// Deletes, with their tokens, the subscriptions made before `subscribed_before`
+    /// that are still waiting for a confirmation, and the ones whose cancellation was
+    /// requested before `cancellation_requested_before` and is still waiting for one.
+    /// Expired tokens of the other subscriptions are deleted as well.
+    async fn delete_stale(
+        &self,
+        subscribed_before: DateTime<Utc>,
+        cancellation_requested_before: DateTime<Utc>,
+    ) -> Result<RetentionReport, SubscriberError>;
+
+    /// Records the suppression of an address and suppresses its active subscriptions,
+    /// keeping their status to reinstate them later. For a bounce, the deliveries
+    /// sent to the address are marked as bounced. Returns how many subscriptions
+    /// were suppressed.
+    async fn suppress(&self, suppression: &Suppression) -> Result<u64, SubscriberError>;
+
+    /// Whether the address was suppressed and not reinstated since
+    async fn is_suppressed(&self, email: &SubscriberEmail) -> Result<bool, SubscriberError>;
+
+    /// Retrieves the suppressed addresses, the latest suppressed first
+    async fn retrieve_suppressed(&self) -> Result<Vec<SuppressedAddress>, SubscriberError>;
+
+    /// Lifts the suppression of an address and gives its subscriptions back the
+    /// status they had before being suppressed
+    async fn reinstate(&self, email: &SubscriberEmail) -> Result<(), SubscriberError>;
+
+    /// Whether the blocklist holds the address or one of its domains
+    async fn is_blocked(&self, email: &SubscriberEmail) -> Result<bool, SubscriberError>;
+
+    /// Adds a pattern to the blocklist, or updates the reason of a pattern already there
+    async fn add_blocked(
+        &self,
+        pattern: &BlockedPattern,
+        reason: &str,
+    ) -> Result<(), SubscriberError>;
+
+    /// Retrieves the blocklist, the latest entries first
+    async fn retrieve_blocklist(&self) -> Result<Vec<BlockedEntry>, SubscriberError>;
+
+    async fn remove_blocked(&self, blocked_id: uuid::Uuid) -> Result<(), SubscriberError>;
+
+    /// Asynchronously retrieves every subscriber of a list, ordered by email
+    async fn retrieve_all(&self, list_id: ListId) -> Result<Vec<NewSubscriber>, SubscriberError>;
+
+    /// Asynchronously replaces the tags of a subscriber
+    async fn update_tags(
+        &self,
+        subscriber_id: uuid::Uuid,
+        tags: &[SubscriberTag],
+    ) -> Result<(), SubscriberError>;
+
+    /// Retrieves the preferences of the email a subscription belongs to
+    async fn retrieve_preferences(
+        &self,
+        subscriber_id: uuid::Uuid,
+    ) -> Result<SubscriberPreferences, SubscriberError>;
+
+    /// Applies preferences to every subscription of the email a subscription belongs to.
+    /// Checked lists the email is not subscribed to yet are subscribed to right away,
+    /// the other lists are left, except the one of the subscription itself.
+    async fn update_preferences(
+        &self,
+        subscriber_id: uuid::Uuid,
+        preferences: &UpdatedPreferences,
+    ) -> Result<(), SubscriberError>;
 }
 
 #[async_trait]
@@ -39,13 +149,72 @@ pub trait SubscriptionService: Clone + Send + Sync + 'static {
         req: NewSubscriberRequest,
     ) -> Result<NewSubscriber, SubscriberError>;
 
+    /// Confirms a subscription with a confirmation token. Following the link
+    /// again once confirmed is harmless.
     async fn confirm(
         &self,
         req: SubscriptionTokenRequest,
-    ) -> Result<NewSubscriber, SubscriberError>;
+    ) -> Result<SubscriptionOutcome, SubscriberError>;
+
+    /// Cancels a subscription with an unsubscribe token, in two steps: the first
+    /// request asks for a confirmation, the second one removes the subscription
+    async fn delete(
+        &self,
+        req: SubscriptionTokenRequest,
+    ) -> Result<SubscriptionOutcome, SubscriberError>;
+
+    /// Cancels a subscription with an unsubscribe token in a single step, as mail
+    /// clients do with one-click unsubscribe (RFC 8058)
+    async fn delete_immediately(
+        &self,
+        req: SubscriptionTokenRequest,
+    ) -> Result<SubscriptionOutcome, SubscriberError>;
+
+    /// Removes the subscriptions that waited too long for their subscriber to confirm
+    /// a subscription or a cancellation
+    async fn apply_retention(&self) -> Result<RetentionReport, SubscriberError>;
+
+    /// Stops sending emails to an address the email provider reported a hard bounce
+    /// or a spam complaint for, or an administrator suppressed, even if it has no
+    /// subscription yet. Returns how many subscriptions were suppressed.
+    async fn suppress(&self, suppression: Suppression) -> Result<u64, SubscriberError>;
+
+    async fn get_suppressed(&self) -> Result<Vec<SuppressedAddress>, SubscriberError>;
+
+    /// Lifts the suppression of an address
+    async fn reinstate(&self, req: ReinstateRequest) -> Result<(), SubscriberError>;
+
+    /// Blocks an address or a whole domain: it can no longer subscribe, and no email
+    /// is sent to it
+    async fn block(&self, req: BlockRequest) -> Result<BlockedPattern, SubscriberError>;
+
+    async fn get_blocklist(&self) -> Result<Vec<BlockedEntry>, SubscriberError>;
+
+    async fn unblock(&self, blocked_id: uuid::Uuid) -> Result<(), SubscriberError>;
+
+    /// Retrieves a list, the default one when `list_id` is `None`, with its subscribers
+    async fn get_subscribers(
+        &self,
+        list_id: Option<ListId>,
+    ) -> Result<(MailingList, Vec<NewSubscriber>), SubscriberError>;
+
+    async fn set_tags(
+        &self,
+        subscriber_id: uuid::Uuid,
+        req: SubscriberTagsRequest,
+    ) -> Result<Vec<SubscriberTag>, SubscriberError>;
+
+    async fn get_lists(&self) -> Result<Vec<MailingList>, SubscriberError>;
+
+    async fn create_list(&self, req: NewListRequest) -> Result<MailingList, SubscriberError>;
+
+    /// Preferences of the confirmed subscriber a token belongs to
+    async fn get_preferences(
+        &self,
+        req: SubscriptionTokenRequest,
+    ) -> Result<SubscriberPreferences, SubscriberError>;
 
-    async fn delete(&self, req: SubscriptionTokenRequest)
-        -> Result<NewSubscriber, SubscriberError>;
+    async fn update_preferences(&self, req: PreferencesRequest) -> Result<(), SubscriberError>;
 }
 
 #[async_trait]
@@ -54,5 +223,6 @@ pub trait SubscriptionNotifier: Clone + Send + Sync + 'static {
         &self,
         recipient: &SubscriberEmail,
         token: SubscriptionToken,
+        locale: Locale,
     ) -> Result<(), SubscriberError>;
 }
@@ -3,9 +3,23 @@ use async_trait::async_trait;
 use super::{
     errors::SubscriberError,
     models::{
+        blocklist::{BlockRequest, BlockedEntry, BlockedPattern},
+        confirmation::ConfirmationPolicy,
+        email::SubscriberEmail,
+        list::{ListId, ListName, ListSlug, MailingList, NewListRequest},
+        locale::Locale,
+        name::SubscriberName,
+        outcome::SubscriptionOutcome,
+        preferences::{
+            DeliveryFrequency, Pause, PreferencesRequest, SubscriberPreferences, UpdatedPreferences,
+        },
+        retention::{RetentionPolicy, RetentionReport},
         subscriber::{NewSubscriber, NewSubscriberRequest, SubscriberStatus},
-        token::SubscriptionToken,
-        token::SubscriptionTokenRequest,
+        suppression::{ReinstateRequest, SuppressedAddress, Suppression},
+        tag::{SubscriberTag, SubscriberTagsRequest},
+        token::{
+            IssuedToken, SubscriptionToken, SubscriptionTokenRequest, TokenPurpose, TokenRejection,
+        },
     },
     ports::{SubscriberRepository, SubscriptionNotifier, SubscriptionService},
 };
@@ -19,6 +33,10 @@ where
 {
     pub repo: Arc<R>,
     pub notifier: Arc<N>,
+    /// Locale of the subscribers that did not ask for a supported one
+    pub default_locale: Locale,
+    pub confirmation_policy: ConfirmationPolicy,
+    pub retention_policy: RetentionPolicy,
 }
 
 impl<R, N> BlogSubscription<R, N>
@@ -26,8 +44,55 @@ where
     R: SubscriberRepository,
     N: SubscriptionNotifier,
 {
-    pub fn new(repo: Arc<R>, notifier: Arc<N>) -> Self {
-        Self { repo, notifier }
+    pub fn new(
+        repo: Arc<R>,
+        notifier: Arc<N>,
+        default_locale: Locale,
+        confirmation_policy: ConfirmationPolicy,
+        retention_policy: RetentionPolicy,
+    ) -> Self {
+        Self {
+            repo,
+            notifier,
+            default_locale,
+            confirmation_policy,
+            retention_policy,
+        }
+    }
+
+    /// Retrieves the subscriber a token was issued to, checking that the token
+    /// can be used for `purpose`
+    async fn retrieve_from_token(
+        &self,
+        subscription_token: &SubscriptionToken,
+        purpose: TokenPurpose,
+    ) -> Result<(NewSubscriber, IssuedToken), SubscriberError> {
+        let (subscriber, issued) = self.repo.retrieve_from_token(subscription_token).await?;
+        issued.ensure_usable_for(purpose, chrono::Utc::now())?;
+        Ok((subscriber, issued))
+    }
+
+    /// Only confirmed subscribers of an address that is not suppressed have
+    /// preferences to manage
+    async fn retrieve_confirmed_from_token(
+        &self,
+        subscription_token: String,
+    ) -> Result<uuid::Uuid, SubscriberError> {
+        let subscription_token = SubscriptionToken::parse(subscription_token)?;
+        let (subscriber, _) = self
+            .retrieve_from_token(&subscription_token, TokenPurpose::Preferences)
+            .await?;
+        if self.repo.is_suppressed(&subscriber.email).await? {
+            return Err(SubscriberError::AuthError(
+                "The address is suppressed".to_string(),
+            ));
+        }
+        match (subscriber.id, subscriber.status) {
+            (Some(subscriber_id), SubscriberStatus::SubscriptionConfirmed) => Ok(subscriber_id),
+            _ => Err(SubscriberError::AuthError(
+                "The subscription is not confirmed".to_string(),
+            )),
+        }
     }
 }
 
@@ -41,15 +106,45 @@ where
         &self,
         subscriber_request: NewSubscriberRequest,
     ) -> Result<NewSubscriber, SubscriberError> {
+        let list = match subscriber_request
+            .list
+            .as_deref()
+            .map(str::trim)
+            .filter(|slug| !slug.is_empty())
+        {
+            Some(slug) => self.repo.get_list_by_slug(&ListSlug::parse(slug)?).await?,
+            None => self.repo.get_default_list().await?,
+        };
+        // Blocked addresses get the same response as everyone else, but are not stored
+        let email = SubscriberEmail::parse(subscriber_request.email.clone())?;
+        if self.repo.is_blocked(&email).await? {
+            tracing::info!("Ignored a subscription from a blocked address");
+            return Ok(NewSubscriber::new(subscriber_request)?);
+        }
+        // Neither is a suppressed address, whichever list it signs up to
+        if self.repo.is_suppressed(&email).await? {
+            tracing::info!("Ignored a subscription from a suppressed address");
+            return Ok(NewSubscriber::new(subscriber_request)?);
+        }
         let subscription_token = SubscriptionToken::default();
         let (subscriber, token) = self
             .repo
-            .retrieve_or_insert(subscriber_request, subscription_token)
+            .retrieve_or_insert(
+                list.list_id,
+                subscriber_request,
+                subscription_token,
+                self.confirmation_policy.resend_cooldown,
+            )
             .await?;
 
-        if subscriber.status == SubscriberStatus::SubscriptionPendingConfirmation {
+        // Within the cooldown the response is the same, but no email is sent
+        if let Some(token) = token {
             self.notifier
-                .send_subscriber_notification(&subscriber.email, token)
+                .send_subscriber_notification(
+                    &subscriber.email,
+                    token,
+                    subscriber.locale.unwrap_or(self.default_locale),
+                )
                 .await?
         }
         Ok(subscriber)
@@ -58,31 +153,187 @@ where
     async fn confirm(
         &self,
         req: SubscriptionTokenRequest,
-    ) -> Result<NewSubscriber, SubscriberError> {
+    ) -> Result<SubscriptionOutcome, SubscriberError> {
         let subscription_token = SubscriptionTokenRequest::try_into(req)?;
 
-        let mut subscriber = self.repo.retrieve_from_token(&subscription_token).await?;
+        let (subscriber, issued) = self.repo.retrieve_from_token(&subscription_token).await?;
+        match issued.ensure_usable_for(TokenPurpose::Confirm, chrono::Utc::now()) {
+            Err(TokenRejection::AlreadyUsed)
+                if subscriber.status == SubscriberStatus::SubscriptionConfirmed =>
+            {
+                return Ok(SubscriptionOutcome::AlreadyConfirmed);
+            }
+            outcome => outcome?,
+        }
+        if subscriber.status.is_suppressed() {
+            return Err(SubscriberError::AuthError(
+                "This address no longer receives our emails".to_string(),
+            ));
+        }
 
-        subscriber = subscriber.with_status(SubscriberStatus::SubscriptionConfirmed);
-        self.repo.update(subscriber.clone()).await?;
-        Ok(subscriber)
+        let subscriber = subscriber.with_status(SubscriberStatus::SubscriptionConfirmed);
+        self.repo.update(subscriber).await?;
+        self.repo.mark_token_used(&subscription_token).await?;
+        Ok(SubscriptionOutcome::Confirmed)
     }
 
     async fn delete(
         &self,
         req: SubscriptionTokenRequest,
-    ) -> Result<NewSubscriber, SubscriberError> {
+    ) -> Result<SubscriptionOutcome, SubscriberError> {
         let subscription_token = SubscriptionTokenRequest::try_into(req)?;
 
-        let mut subscriber = self.repo.retrieve_from_token(&subscription_token).await?;
+        let (subscriber, _) = self
+            .retrieve_from_token(&subscription_token, TokenPurpose::Unsubscribe)
+            .await?;
 
+        // Suppressed subscriptions already receive nothing, and are kept so that
+        // the address stays suppressed
+        if subscriber.status.is_suppressed() {
+            return Ok(SubscriptionOutcome::Unsubscribed);
+        }
         if subscriber.status == SubscriberStatus::CancellationPendingConfirmation {
-            self.repo.delete(subscriber.clone()).await?;
+            self.repo.delete(subscriber).await?;
+            Ok(SubscriptionOutcome::Unsubscribed)
         } else {
-            subscriber = subscriber.with_status(SubscriberStatus::CancellationPendingConfirmation);
-            self.repo.update(subscriber.clone()).await?;
+            let subscriber =
+                subscriber.with_status(SubscriberStatus::CancellationPendingConfirmation);
+            self.repo.update(subscriber).await?;
+            Ok(SubscriptionOutcome::CancellationRequested)
         }
+    }
 
-        Ok(subscriber)
+    async fn delete_immediately(
+        &self,
+        req: SubscriptionTokenRequest,
+    ) -> Result<SubscriptionOutcome, SubscriberError> {
+        let subscription_token = SubscriptionTokenRequest::try_into(req)?;
+
+        let (subscriber, _) = self
+            .retrieve_from_token(&subscription_token, TokenPurpose::Unsubscribe)
+            .await?;
+        if !subscriber.status.is_suppressed() {
+            self.repo.delete(subscriber).await?;
+        }
+
+        Ok(SubscriptionOutcome::Unsubscribed)
+    }
+
+    #[tracing::instrument(name = "Apply retention policy", skip(self))]
+    async fn apply_retention(&self) -> Result<RetentionReport, SubscriberError> {
+        let now = chrono::Utc::now();
+        let report = self
+            .repo
+            .delete_stale(
+                self.retention_policy.pending_confirmation_cutoff(now),
+                self.retention_policy.cancellation_pending_cutoff(now),
+            )
+            .await?;
+        tracing::info!(
+            unconfirmed_subscriptions = report.unconfirmed_subscriptions,
+            pending_cancellations = report.pending_cancellations,
+            tokens = report.tokens,
+            expired_tokens = report.expired_tokens,
+            pending_confirmation_days = self.retention_policy.pending_confirmation.num_days(),
+            cancellation_pending_days = self.retention_policy.cancellation_pending.num_days(),
+            "Removed stale subscriptions"
+        );
+        Ok(report)
+    }
+
+    #[tracing::instrument(
+        name = "Suppress an address",
+        skip(self, suppression),
+        fields(reason = suppression.reason.as_str())
+    )]
+    async fn suppress(&self, suppression: Suppression) -> Result<u64, SubscriberError> {
+        let suppressed = self.repo.suppress(&suppression).await?;
+        tracing::info!(
+            suppressed_subscriptions = suppressed,
+            description = %suppression.description,
+            "Suppressed the subscriptions of an address"
+        );
+        Ok(suppressed)
+    }
+
+    async fn get_suppressed(&self) -> Result<Vec<SuppressedAddress>, SubscriberError> {
+        self.repo.retrieve_suppressed().await
+    }
+
+    async fn reinstate(&self, req: ReinstateRequest) -> Result<(), SubscriberError> {
+        let email = SubscriberEmail::parse(req.email.trim().to_string())?;
+        self.repo.reinstate(&email).await
+    }
+
+    #[tracing::instrument(name = "Block an address or a domain", skip(self))]
+    async fn block(&self, req: BlockRequest) -> Result<BlockedPattern, SubscriberError> {
+        let pattern = BlockedPattern::parse(&req.pattern)?;
+        self.repo.add_blocked(&pattern, req.reason.trim()).await?;
+        Ok(pattern)
+    }
+
+    async fn get_blocklist(&self) -> Result<Vec<BlockedEntry>, SubscriberError> {
+        self.repo.retrieve_blocklist().await
+    }
+
+    async fn unblock(&self, blocked_id: uuid::Uuid) -> Result<(), SubscriberError> {
+        self.repo.remove_blocked(blocked_id).await
+    }
+
+    async fn get_subscribers(
+        &self,
+        list_id: Option<ListId>,
+    ) -> Result<(MailingList, Vec<NewSubscriber>), SubscriberError> {
+        let list = match list_id {
+            Some(list_id) => self.repo.get_list(list_id).await?,
+            None => self.repo.get_default_list().await?,
+        };
+        let subscribers = self.repo.retrieve_all(list.list_id).await?;
+        Ok((list, subscribers))
+    }
+
+    async fn set_tags(
+        &self,
+        subscriber_id: uuid::Uuid,
+        req: SubscriberTagsRequest,
+    ) -> Result<Vec<SubscriberTag>, SubscriberError> {
+        let tags = SubscriberTag::parse_list(&req.tags)?;
+        self.repo.update_tags(subscriber_id, &tags).await?;
+        Ok(tags)
+    }
+
+    async fn get_lists(&self) -> Result<Vec<MailingList>, SubscriberError> {
+        self.repo.get_lists().await
+    }
+
+    async fn create_list(&self, req: NewListRequest) -> Result<MailingList, SubscriberError> {
+        let name = ListName::parse(&req.name)?;
+        let slug = ListSlug::parse(&req.slug)?;
+        self.repo.add_list(&name, &slug).await
+    }
+
+    async fn get_preferences(
+        &self,
+        req: SubscriptionTokenRequest,
+    ) -> Result<SubscriberPreferences, SubscriberError> {
+        let subscriber_id = self
+            .retrieve_confirmed_from_token(req.subscription_token)
+            .await?;
+        self.repo.retrieve_preferences(subscriber_id).await
+    }
+
+    async fn update_preferences(&self, req: PreferencesRequest) -> Result<(), SubscriberError> {
+        let subscriber_id = self
+            .retrieve_confirmed_from_token(req.subscription_token)
+            .await?;
+        let preferences = UpdatedPreferences {
+            name: SubscriberName::parse(req.name)?,
+            lists: req.lists,
+            frequency: DeliveryFrequency::parse(&req.frequency)?,
+            pause: Pause::parse(&req.pause_weeks)?,
+        };
+        self.repo
+            .update_preferences(subscriber_id, &preferences)
+            .await
     }
 }
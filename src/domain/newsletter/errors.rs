@@ -11,6 +11,11 @@ pub enum NewsletterError {
     NotFound(String),
     #[error("Subscriber not authenticated: {0}")]
     AuthError(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    /// The recipient is on the blocklist, so nothing was sent
+    #[error("Recipient is blocked")]
+    BlockedRecipient,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -1,2 +1,14 @@
+pub mod archive;
 pub mod confirmed_subscribers;
+pub mod digest;
+pub mod draft;
+pub mod feed;
+pub mod issue_delivery;
+pub mod markdown;
+pub mod merge_tags;
 pub mod newsletter;
+pub mod scheduled_issue;
+pub mod segment;
+pub mod test_issue;
+pub mod tracking;
+pub mod translation;
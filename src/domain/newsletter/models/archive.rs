@@ -0,0 +1,194 @@
+use crate::domain::new_subscriber::models::list::ListId;
+use crate::domain::new_subscriber::models::locale::Locale;
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::issue_delivery::NewsletterIssueId;
+use crate::domain::newsletter::models::newsletter::{Newsletter, NewsletterTitle};
+use crate::domain::newsletter::models::segment::Segment;
+use crate::domain::newsletter::models::translation::IssueTranslation;
+
+/// URL-friendly identifier of a published issue in the public archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueSlug(String);
+
+impl IssueSlug {
+    const MAX_TITLE_LENGTH: usize = 60;
+    const ID_SUFFIX_LENGTH: usize = 8;
+    const MAX_LENGTH: usize = Self::MAX_TITLE_LENGTH + 1 + Self::ID_SUFFIX_LENGTH;
+
+    /// Builds the slug of an issue from its title. A prefix of the issue id is
+    /// appended so that issues sharing a title get different slugs.
+    pub fn new(title: &NewsletterTitle, newsletter_issue_id: NewsletterIssueId) -> IssueSlug {
+        let mut slug = String::with_capacity(Self::MAX_LENGTH);
+        for c in title.as_str().to_lowercase().chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c);
+            } else if !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let mut slug: String = slug.chars().take(Self::MAX_TITLE_LENGTH).collect();
+        slug = slug.trim_matches('-').to_string();
+
+        let id = newsletter_issue_id.simple().to_string();
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&id[..Self::ID_SUFFIX_LENGTH]);
+        Self(slug)
+    }
+
+    pub fn parse(slug: String) -> Result<IssueSlug, NewsletterError> {
+        let is_valid = !slug.is_empty()
+            && slug.len() <= Self::MAX_LENGTH
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !is_valid {
+            return Err(NewsletterError::ValidationError(format!(
+                "Invalid issue slug: {}",
+                slug
+            )));
+        }
+        Ok(Self(slug))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for IssueSlug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A published issue together with the slug of its web version
+#[derive(Debug)]
+pub struct PublishedIssue {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub newsletter: Newsletter,
+    /// List whose subscribers the issue is delivered to
+    pub list_id: ListId,
+    pub segment: Segment,
+    pub slug: IssueSlug,
+    pub translations: Vec<IssueTranslation>,
+    /// Whether the links of the issue record opens and clicks
+    pub tracking_enabled: bool,
+}
+
+impl PublishedIssue {
+    /// Version of the issue sent to subscribers of the given locale: issues
+    /// that were not translated into it are sent as written
+    pub fn newsletter_for(&self, locale: Locale) -> &Newsletter {
+        self.translations
+            .iter()
+            .find(|translation| translation.locale == locale)
+            .map(|translation| &translation.newsletter)
+            .unwrap_or(&self.newsletter)
+    }
+}
+
+/// An issue listed in the public archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub slug: IssueSlug,
+    pub title: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Web version of a published issue. The HTML is the stored one, as written
+/// by the editor: it must be sanitized before being rendered.
+#[derive(Debug, Clone)]
+pub struct ArchivedIssue {
+    pub slug: IssueSlug,
+    pub title: String,
+    pub html_content: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct IssueVisibilityDto {
+    pub hidden: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IssueSlug, PublishedIssue, Segment};
+    use crate::domain::new_subscriber::models::locale::Locale;
+    use crate::domain::newsletter::models::newsletter::{
+        Newsletter, NewsletterContentDto, NewsletterTitle,
+    };
+    use crate::domain::newsletter::models::translation::IssueTranslation;
+    use claim::{assert_err, assert_ok};
+
+    fn slug(title: &str) -> String {
+        let title = NewsletterTitle::parse(title.into()).unwrap();
+        let id = uuid::Uuid::parse_str("0a1b2c3d-4e5f-6789-abcd-ef0123456789").unwrap();
+        IssueSlug::new(&title, id).as_str().to_string()
+    }
+
+    #[test]
+    fn slug_is_built_from_the_title_and_the_issue_id() {
+        assert_eq!(slug("Hello, World!"), "hello-world-0a1b2c3d");
+    }
+
+    #[test]
+    fn punctuation_and_whitespace_are_collapsed() {
+        assert_eq!(slug("  Rust --  & Friends  "), "rust-friends-0a1b2c3d");
+    }
+
+    #[test]
+    fn titles_without_ascii_characters_only_keep_the_id() {
+        assert_eq!(slug("ニュース"), "0a1b2c3d");
+    }
+
+    #[test]
+    fn long_titles_are_truncated() {
+        let slug = slug(&"a".repeat(150));
+
+        assert_eq!(slug.len(), IssueSlug::MAX_LENGTH);
+        assert_ok!(IssueSlug::parse(slug));
+    }
+
+    #[test]
+    fn invalid_slugs_are_rejected() {
+        assert_err!(IssueSlug::parse("".into()));
+        assert_err!(IssueSlug::parse("Upper-Case".into()));
+        assert_err!(IssueSlug::parse("../etc/passwd".into()));
+    }
+
+    #[test]
+    fn subscribers_get_the_translation_of_their_locale_if_there_is_one() {
+        let newsletter = |title: &str| {
+            let content = NewsletterContentDto {
+                html: "<p>Body</p>".into(),
+                text: "Body".into(),
+                markdown: None,
+            };
+            Newsletter::parse(title.into(), content).unwrap()
+        };
+        let issue = PublishedIssue {
+            newsletter_issue_id: uuid::Uuid::new_v4(),
+            newsletter: newsletter("Hello"),
+            list_id: uuid::Uuid::new_v4(),
+            segment: Segment::everyone(),
+            slug: IssueSlug::parse("hello-0a1b2c3d".into()).unwrap(),
+            translations: vec![IssueTranslation {
+                locale: Locale::French,
+                newsletter: newsletter("Bonjour"),
+                markdown: None,
+            }],
+            tracking_enabled: false,
+        };
+
+        assert_eq!(
+            issue.newsletter_for(Locale::French).title.as_str(),
+            "Bonjour"
+        );
+        assert_eq!(
+            issue.newsletter_for(Locale::English).title.as_str(),
+            "Hello"
+        );
+    }
+}
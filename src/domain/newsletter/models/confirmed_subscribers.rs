@@ -1,19 +1,44 @@
 use crate::domain::new_subscriber::models::{
     email::SubscriberEmail,
+    locale::Locale,
+    name::SubscriberName,
     subscriber::{NewSubscriber, SubscriberStatus},
+    tag::SubscriberTag,
 };
-pub struct ConfirmedSubscriber(NewSubscriber);
+pub struct ConfirmedSubscriber {
+    id: uuid::Uuid,
+    subscriber: NewSubscriber,
+}
 
 impl ConfirmedSubscriber {
     pub fn new(subscriber: NewSubscriber) -> Result<Self, String> {
-        if subscriber.status == SubscriberStatus::SubscriptionConfirmed {
-            Ok(ConfirmedSubscriber(subscriber))
-        } else {
-            Err("Subscriber must be confirmed".to_string())
+        if subscriber.status != SubscriberStatus::SubscriptionConfirmed {
+            return Err("Subscriber must be confirmed".to_string());
+        }
+        match subscriber.id {
+            Some(id) => Ok(ConfirmedSubscriber { id, subscriber }),
+            None => Err("Subscriber must be stored".to_string()),
         }
     }
 
+    pub fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+
     pub fn email(&self) -> &SubscriberEmail {
-        &self.0.email
+        &self.subscriber.email
+    }
+
+    pub fn name(&self) -> &SubscriberName {
+        &self.subscriber.name
+    }
+
+    /// Locale picked by the subscriber, `None` meaning the default one
+    pub fn locale(&self) -> Option<Locale> {
+        self.subscriber.locale
+    }
+
+    pub fn tags(&self) -> &[SubscriberTag] {
+        &self.subscriber.tags
     }
 }
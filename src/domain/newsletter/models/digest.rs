@@ -0,0 +1,16 @@
+use crate::domain::new_subscriber::models::list::ListId;
+use crate::domain::newsletter::models::confirmed_subscribers::ConfirmedSubscriber;
+use chrono::{DateTime, Utc};
+
+/// How often subscribers who picked the weekly digest receive it
+pub const DIGEST_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::weeks(1);
+
+/// A weekly digest that is due, claimed by a worker
+pub struct DueDigest {
+    pub subscriber: ConfirmedSubscriber,
+    pub list_id: ListId,
+    /// The digest covers the issues published to the list after this point
+    pub since: DateTime<Utc>,
+    /// Start of the next digest, once this one is sent
+    pub claimed_at: DateTime<Utc>,
+}
@@ -0,0 +1,124 @@
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::issue_delivery::NewsletterIssueId;
+use crate::domain::newsletter::models::newsletter::{
+    Newsletter, NewsletterContentDto, NewsletterTitle,
+};
+use serde::Deserialize;
+use serde_aux::field_attributes::deserialize_bool_from_anything;
+
+#[derive(Deserialize, Debug)]
+pub struct DraftDto {
+    pub title: String,
+    #[serde(flatten)]
+    pub content: NewsletterContentDto,
+    /// Tick to record opens and clicks once the draft is published
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub tracking: bool,
+}
+
+/// Work in progress of an editor. Only the title is required: the bodies are
+/// validated when the draft is previewed or published.
+#[derive(Debug)]
+pub struct DraftContent {
+    pub title: NewsletterTitle,
+    pub text: String,
+    pub html: String,
+    /// Source the bodies were generated from, kept so that it can be edited again
+    pub markdown: Option<String>,
+    /// Whether opens and clicks are recorded once the draft is published
+    pub tracking_enabled: bool,
+}
+
+impl TryFrom<DraftDto> for DraftContent {
+    type Error = NewsletterError;
+
+    fn try_from(dto: DraftDto) -> Result<Self, Self::Error> {
+        let content = dto.content.render_markdown();
+        Ok(Self {
+            title: NewsletterTitle::parse(dto.title).map_err(NewsletterError::ValidationError)?,
+            text: content.text,
+            html: content.html,
+            markdown: content.markdown,
+            tracking_enabled: dto.tracking,
+        })
+    }
+}
+
+impl TryFrom<&DraftContent> for Newsletter {
+    type Error = NewsletterError;
+
+    fn try_from(draft: &DraftContent) -> Result<Self, Self::Error> {
+        Newsletter::parse(
+            draft.title.as_str().to_string(),
+            // The bodies were already generated from the Markdown source
+            NewsletterContentDto {
+                html: draft.html.clone(),
+                text: draft.text.clone(),
+                markdown: None,
+            },
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Draft {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub content: DraftContent,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A newsletter rendered the way a subscriber receives it
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsletterPreview {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DraftContent, DraftDto};
+    use crate::domain::newsletter::models::newsletter::{Newsletter, NewsletterContentDto};
+    use claim::{assert_err, assert_ok};
+
+    fn draft_dto(title: &str, text: &str, html: &str) -> DraftDto {
+        DraftDto {
+            title: title.into(),
+            content: NewsletterContentDto {
+                html: html.into(),
+                text: text.into(),
+                markdown: None,
+            },
+            tracking: false,
+        }
+    }
+
+    #[test]
+    fn drafts_with_empty_bodies_can_be_saved() {
+        assert_ok!(DraftContent::try_from(draft_dto("Title", "", "")));
+    }
+
+    #[test]
+    fn drafts_without_a_title_are_rejected() {
+        assert_err!(DraftContent::try_from(draft_dto("", "text", "<p>html</p>")));
+    }
+
+    #[test]
+    fn drafts_with_empty_bodies_cannot_be_turned_into_a_newsletter() {
+        let draft = DraftContent::try_from(draft_dto("Title", "", "<p>html</p>")).unwrap();
+
+        assert_err!(Newsletter::try_from(&draft));
+    }
+
+    #[test]
+    fn drafts_written_in_markdown_keep_their_source() {
+        let mut dto = draft_dto("Title", "", "");
+        dto.content.markdown = Some("Hello *world*".into());
+
+        let draft = DraftContent::try_from(dto).unwrap();
+        assert_eq!(draft.html, "<p>Hello <em>world</em></p>\n");
+        assert_eq!(draft.text, "Hello world");
+        assert_eq!(draft.markdown.as_deref(), Some("Hello *world*"));
+        assert_ok!(Newsletter::try_from(&draft));
+    }
+}
@@ -0,0 +1,24 @@
+use crate::domain::newsletter::models::archive::IssueSlug;
+use crate::domain::newsletter::models::newsletter::NewsletterTitle;
+
+/// A published issue syndicated in the RSS and Atom feeds
+#[derive(Debug)]
+pub struct FeedEntry {
+    pub slug: IssueSlug,
+    pub title: NewsletterTitle,
+    pub html_content: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The most recent issues of the public archive, as served to feed readers
+#[derive(Debug)]
+pub struct Feed {
+    pub entries: Vec<FeedEntry>,
+    /// Last time an issue was published to, or hidden from, the archive.
+    /// `None` until the first issue is published.
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Feed {
+    pub const MAX_ENTRIES: i64 = 20;
+}
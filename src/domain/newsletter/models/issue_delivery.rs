@@ -0,0 +1,191 @@
+use crate::domain::new_subscriber::models::email::SubscriberEmail;
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::archive::IssueSlug;
+use crate::domain::newsletter::models::segment::Segment;
+use crate::domain::newsletter::models::tracking::TrackingStats;
+
+pub type NewsletterIssueId = uuid::Uuid;
+
+/// A pending delivery of a stored newsletter issue to a single subscriber.
+#[derive(Debug, Clone)]
+pub struct DeliveryTask {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub subscriber_email: SubscriberEmail,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+/// How a delivery task ended once the worker is done with it
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryOutcome {
+    Sent,
+    /// Nothing was sent, e.g. because the recipient is no longer confirmed or is blocked
+    Skipped(String),
+    /// Sending failed for good; the task is moved to dead letters
+    Failed(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DeliveryStatusError {
+    #[error("Unknown delivery status: {0}")]
+    UnknownStatus(String),
+}
+
+/// State of a newsletter issue delivery to a single recipient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeliveryStatus {
+    Queued,
+    Sent,
+    Failed,
+    Bounced,
+    /// Nothing was sent because the recipient can no longer receive the issue
+    Skipped,
+}
+
+impl DeliveryStatus {
+    const QUEUED: &'static str = "queued";
+    const SENT: &'static str = "sent";
+    const FAILED: &'static str = "failed";
+    const BOUNCED: &'static str = "bounced";
+    const SKIPPED: &'static str = "skipped";
+
+    pub fn parse(status: &str) -> Result<DeliveryStatus, DeliveryStatusError> {
+        match status {
+            Self::QUEUED => Ok(DeliveryStatus::Queued),
+            Self::SENT => Ok(DeliveryStatus::Sent),
+            Self::FAILED => Ok(DeliveryStatus::Failed),
+            Self::BOUNCED => Ok(DeliveryStatus::Bounced),
+            Self::SKIPPED => Ok(DeliveryStatus::Skipped),
+            _ => Err(DeliveryStatusError::UnknownStatus(status.into())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Queued => Self::QUEUED,
+            DeliveryStatus::Sent => Self::SENT,
+            DeliveryStatus::Failed => Self::FAILED,
+            DeliveryStatus::Bounced => Self::BOUNCED,
+            DeliveryStatus::Skipped => Self::SKIPPED,
+        }
+    }
+}
+
+impl From<DeliveryStatus> for String {
+    fn from(value: DeliveryStatus) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<DeliveryStatusError> for NewsletterError {
+    fn from(error: DeliveryStatusError) -> Self {
+        Self::ValidationError(error.to_string())
+    }
+}
+
+/// Number of recipients of an issue in each delivery state
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeliveryTotals {
+    pub queued: i64,
+    pub sent: i64,
+    pub failed: i64,
+    pub bounced: i64,
+    pub skipped: i64,
+}
+
+impl DeliveryTotals {
+    pub fn total(&self) -> i64 {
+        self.queued + self.sent + self.failed + self.bounced + self.skipped
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IssueSummary {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub title: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+    pub slug: IssueSlug,
+    pub hidden_from_archive: bool,
+    /// Name of the list the issue was sent to
+    pub list_name: String,
+    /// Part of the confirmed subscribers the issue was sent to
+    pub segment: Segment,
+    /// Whether opens and clicks of the recipients are recorded
+    pub tracking_enabled: bool,
+    pub totals: DeliveryTotals,
+}
+
+/// Delivery log row for a single recipient of an issue
+#[derive(Debug, Clone)]
+pub struct DeliveryLogEntry {
+    pub subscriber_email: SubscriberEmail,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+    pub last_attempted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IssueDeliveryReport {
+    pub summary: IssueSummary,
+    /// Recipients whose delivery failed or bounced
+    pub undelivered: Vec<DeliveryLogEntry>,
+    /// Opens and clicks, for issues sent with tracking
+    pub tracking: Option<TrackingStats>,
+}
+
+/// A delivery that failed permanently or kept failing after all retries
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub newsletter_title: String,
+    pub subscriber_email: SubscriberEmail,
+    pub last_error: String,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct DeadLetterReplayDto {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub subscriber_email: String,
+}
+
+impl TryFrom<DeadLetterReplayDto> for DeliveryTask {
+    type Error = NewsletterError;
+
+    fn try_from(dto: DeadLetterReplayDto) -> Result<Self, Self::Error> {
+        Ok(Self {
+            newsletter_issue_id: dto.newsletter_issue_id,
+            subscriber_email: SubscriberEmail::parse(dto.subscriber_email)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeliveryStatus;
+    use claim::assert_err;
+
+    #[test]
+    fn delivery_status_round_trips_through_its_string_form() {
+        for status in [
+            DeliveryStatus::Queued,
+            DeliveryStatus::Sent,
+            DeliveryStatus::Failed,
+            DeliveryStatus::Bounced,
+        ] {
+            assert_eq!(DeliveryStatus::parse(status.as_str()).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn unknown_delivery_status_is_rejected() {
+        assert_err!(DeliveryStatus::parse("delivered"));
+    }
+}
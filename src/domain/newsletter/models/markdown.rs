@@ -0,0 +1,307 @@
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// Newsletter body written in Markdown, from which both the HTML and the
+/// plain text bodies of an issue are generated
+#[derive(Debug, PartialEq)]
+pub struct MarkdownBody(String);
+
+impl MarkdownBody {
+    pub fn parse(source: String) -> Result<MarkdownBody, String> {
+        if source.trim().is_empty() {
+            return Err("Markdown content cannot be empty".to_string());
+        }
+        Ok(Self(source))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn to_html(&self) -> String {
+        // Link targets are percent-encoded, which would break merge tags such
+        // as `[unsubscribe]({{unsubscribe_url}})`: those links are written as is
+        let mut merge_tag_links = Vec::new();
+        let events = self.events().map(|event| match event {
+            Event::Start(Tag::Link {
+                dest_url, title, ..
+            }) if dest_url.contains("{{") => {
+                merge_tag_links.push(true);
+                let mut link = format!(r#"<a href="{}""#, htmlescape::encode_minimal(&dest_url));
+                if !title.is_empty() {
+                    link.push_str(&format!(
+                        r#" title="{}""#,
+                        htmlescape::encode_minimal(&title)
+                    ));
+                }
+                link.push('>');
+                Event::InlineHtml(link.into())
+            }
+            Event::Start(Tag::Link { .. }) => {
+                merge_tag_links.push(false);
+                event
+            }
+            Event::End(TagEnd::Link) if merge_tag_links.pop() == Some(true) => {
+                Event::InlineHtml("</a>".into())
+            }
+            event => event,
+        });
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events);
+        html
+    }
+
+    /// Renders the body for plain text email clients: markup is dropped,
+    /// links are followed by their target and the document structure is
+    /// kept readable with blank lines, list markers and quote prefixes.
+    pub fn to_text(&self) -> String {
+        let mut writer = TextWriter::default();
+        for event in self.events() {
+            writer.handle(event);
+        }
+        writer.finish()
+    }
+
+    fn events(&self) -> Parser<'_> {
+        Parser::new_ext(&self.0, Options::ENABLE_STRIKETHROUGH)
+    }
+}
+
+#[derive(Default)]
+struct TextWriter {
+    text: String,
+    /// Next number of each enclosing list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    /// Target and start offset in `text` of each enclosing link or image
+    links: Vec<(String, usize)>,
+    heading_start: usize,
+    code_block: Option<String>,
+    /// Whether the last output was the start of a block or a list marker
+    at_block_start: bool,
+}
+
+impl TextWriter {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code_block {
+                Some(code) => code.push_str(&text),
+                None => self.push(&text),
+            },
+            Event::Code(code) => self.push(&code),
+            Event::SoftBreak => self.push(" "),
+            Event::HardBreak => self.newline(),
+            Event::Rule => {
+                self.block_start();
+                self.push("----");
+            }
+            Event::TaskListMarker(checked) => self.push(if checked { "[x] " } else { "[ ] " }),
+            // Raw HTML has no plain text equivalent
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.block_start(),
+            Tag::Heading { .. } => {
+                self.block_start();
+                self.heading_start = self.text.len();
+            }
+            Tag::BlockQuote(_) => {
+                self.block_start();
+                self.quote_depth += 1;
+                self.text.push_str("> ");
+            }
+            Tag::CodeBlock(_) => {
+                self.block_start();
+                self.code_block = Some(String::new());
+            }
+            Tag::List(first_number) => {
+                if self.lists.is_empty() {
+                    self.block_start();
+                }
+                self.lists.push(first_number);
+            }
+            Tag::Item => {
+                if !self.at_block_start {
+                    self.newline();
+                }
+                let indent = "   ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    }
+                    _ => format!("{}- ", indent),
+                };
+                self.text.push_str(&marker);
+                self.at_block_start = true;
+            }
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                self.links.push((dest_url.to_string(), self.text.len()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(level) => {
+                let underline = match level {
+                    HeadingLevel::H1 => Some('='),
+                    HeadingLevel::H2 => Some('-'),
+                    _ => None,
+                };
+                if let Some(underline) = underline {
+                    let width = self.text[self.heading_start..].chars().count();
+                    self.newline();
+                    self.text
+                        .push_str(&underline.to_string().repeat(width.max(1)));
+                }
+            }
+            TagEnd::BlockQuote(_) => self.quote_depth -= 1,
+            TagEnd::CodeBlock => {
+                let code = self.code_block.take().unwrap_or_default();
+                for (i, line) in code.trim_end_matches('\n').lines().enumerate() {
+                    if i > 0 {
+                        self.newline();
+                    }
+                    self.text.push_str("    ");
+                    self.text.push_str(line);
+                }
+                self.at_block_start = false;
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+            }
+            TagEnd::Link | TagEnd::Image => {
+                if let Some((target, start)) = self.links.pop() {
+                    let label = &self.text[start..];
+                    if label != target && !target.is_empty() {
+                        self.push(&format!(" ({})", target));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, s: &str) {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.newline();
+            }
+            self.text.push_str(line);
+        }
+        self.at_block_start = false;
+    }
+
+    fn newline(&mut self) {
+        self.text.push('\n');
+        self.text.push_str(&"> ".repeat(self.quote_depth));
+    }
+
+    /// Separates a block from the previous one with a blank line, unless it
+    /// directly follows the opening of its parent block
+    fn block_start(&mut self) {
+        if !self.text.is_empty() && !self.at_block_start {
+            self.newline();
+            self.newline();
+        }
+        self.at_block_start = true;
+    }
+
+    fn finish(self) -> String {
+        self.text
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MarkdownBody;
+    use claim::assert_err;
+
+    fn text(source: &str) -> String {
+        MarkdownBody::parse(source.into()).unwrap().to_text()
+    }
+
+    #[test]
+    fn blank_markdown_is_rejected() {
+        assert_err!(MarkdownBody::parse(" \n ".into()));
+    }
+
+    #[test]
+    fn markdown_is_rendered_to_html() {
+        let body = MarkdownBody::parse("# Hello\n\nSome *emphasis*.".into()).unwrap();
+
+        assert_eq!(
+            body.to_html(),
+            "<h1>Hello</h1>\n<p>Some <em>emphasis</em>.</p>\n"
+        );
+    }
+
+    #[test]
+    fn merge_tags_are_kept_in_link_targets() {
+        let body = MarkdownBody::parse("[Unsubscribe]({{unsubscribe_url}})".into()).unwrap();
+
+        assert_eq!(
+            body.to_html(),
+            "<p><a href=\"{{unsubscribe_url}}\">Unsubscribe</a></p>\n"
+        );
+        assert_eq!(body.to_text(), "Unsubscribe ({{unsubscribe_url}})");
+    }
+
+    #[test]
+    fn paragraphs_are_separated_by_blank_lines() {
+        assert_eq!(
+            text("First **bold**\nline.\n\nSecond."),
+            "First bold line.\n\nSecond."
+        );
+    }
+
+    #[test]
+    fn top_level_headings_are_underlined() {
+        assert_eq!(
+            text("# Title\n\n## Section\n\n### Detail"),
+            "Title\n=====\n\nSection\n-------\n\nDetail"
+        );
+    }
+
+    #[test]
+    fn links_are_followed_by_their_target() {
+        assert_eq!(
+            text("Read [the docs](https://example.com/docs) or <https://example.com>."),
+            "Read the docs (https://example.com/docs) or https://example.com."
+        );
+    }
+
+    #[test]
+    fn lists_keep_their_markers() {
+        assert_eq!(
+            text("Intro\n\n- one\n- two\n  1. nested\n  2. again\n\n3. three\n4. four"),
+            "Intro\n\n- one\n- two\n   1. nested\n   2. again\n\n3. three\n4. four"
+        );
+    }
+
+    #[test]
+    fn quotes_and_code_blocks_are_preserved() {
+        assert_eq!(
+            text("> quoted\n> text\n\n```\nlet x = 1;\nlet y = 2;\n```"),
+            "> quoted text\n\n    let x = 1;\n    let y = 2;"
+        );
+    }
+
+    #[test]
+    fn raw_html_is_dropped_from_the_text_body() {
+        assert_eq!(text("<div>ignored</div>\n\nKept"), "Kept");
+    }
+}
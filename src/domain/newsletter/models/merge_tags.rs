@@ -0,0 +1,277 @@
+use crate::domain::new_subscriber::models::token::SubscriptionToken;
+
+/// Placeholder filled in with a value specific to each recipient.
+/// `{{tag}}` is HTML-escaped in HTML bodies, `{{{tag}}}` is inserted as is.
+/// A backslash keeps braces literal: `\{{name}}` is sent as `{{name}}`
+/// (write `\\{{` in Markdown, where a single backslash is consumed).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeTag {
+    Name,
+    Email,
+    UnsubscribeUrl,
+    ArchiveUrl,
+}
+
+impl MergeTag {
+    pub fn parse(tag: &str) -> Result<MergeTag, String> {
+        match tag {
+            "name" => Ok(MergeTag::Name),
+            "email" => Ok(MergeTag::Email),
+            "unsubscribe_url" => Ok(MergeTag::UnsubscribeUrl),
+            "archive_url" => Ok(MergeTag::ArchiveUrl),
+            other => Err(format!("Unknown merge tag: {{{{{}}}}}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Tag { tag: MergeTag, escaped: bool },
+}
+
+/// A newsletter body split into literal text and merge tags
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeTemplate(Vec<Segment>);
+
+impl MergeTemplate {
+    /// Splits a body written by an editor, rejecting unknown or unclosed tags
+    pub fn parse(body: &str) -> Result<MergeTemplate, String> {
+        Self::split(body, true)
+    }
+
+    /// Splits a stored body, keeping anything that is not a known tag as
+    /// literal text: what was accepted when the issue was written must still
+    /// be sendable after the set of tags changes
+    pub fn parse_lenient(body: &str) -> MergeTemplate {
+        Self::split(body, false).expect("Lenient parsing never fails")
+    }
+
+    /// Template of a body without any tag left to fill in
+    pub fn literal(body: &str) -> MergeTemplate {
+        Self(vec![Segment::Literal(body.to_string())])
+    }
+
+    fn split(body: &str, strict: bool) -> Result<MergeTemplate, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = body;
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                // Escaped braces are kept, without the backslash
+                let braces = rest[start..].len() - rest[start..].trim_start_matches('{').len();
+                literal.push_str(&rest[..start - 1]);
+                literal.push_str(&rest[start..start + braces]);
+                rest = &rest[start + braces..];
+                continue;
+            }
+            literal.push_str(&rest[..start]);
+            let (escaped, open, close) = if rest[start..].starts_with("{{{") {
+                (false, "{{{", "}}}")
+            } else {
+                (true, "{{", "}}")
+            };
+            let after_open = &rest[start + open.len()..];
+            let tag = match after_open.find(close) {
+                Some(end) => MergeTag::parse(after_open[..end].trim()).map(|tag| (tag, end)),
+                None => {
+                    let excerpt: String = rest[start..].chars().take(30).collect();
+                    Err(format!("Unclosed merge tag: {}", excerpt))
+                }
+            };
+            match tag {
+                Ok((tag, end)) => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Tag { tag, escaped });
+                    rest = &after_open[end + close.len()..];
+                }
+                Err(e) if strict => return Err(e),
+                Err(_) => {
+                    literal.push_str(open);
+                    rest = after_open;
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self(segments))
+    }
+
+    /// Fills in the tags of an HTML body, escaping the values of `{{tag}}`
+    pub fn render_html(&self, values: &MergeValues) -> String {
+        self.render(values, true)
+    }
+
+    /// Fills in the tags of a plain text body: values are never escaped
+    pub fn render_text(&self, values: &MergeValues) -> String {
+        self.render(values, false)
+    }
+
+    fn render(&self, values: &MergeValues, escape: bool) -> String {
+        let mut rendered = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Literal(literal) => rendered.push_str(literal),
+                Segment::Tag { tag, escaped } => {
+                    let value = values.get(*tag);
+                    if escape && *escaped {
+                        rendered.push_str(&htmlescape::encode_minimal(value));
+                    } else {
+                        rendered.push_str(value);
+                    }
+                }
+            }
+        }
+        rendered
+    }
+}
+
+/// Values of the merge tags for one recipient
+#[derive(Debug, Clone)]
+pub struct MergeValues {
+    pub name: String,
+    pub email: String,
+    pub unsubscribe_url: String,
+    pub archive_url: String,
+}
+
+impl MergeValues {
+    /// Name used when an issue is not sent to an actual subscriber
+    pub const SAMPLE_NAME: &'static str = "Test Subscriber";
+
+    pub fn new(name: &str, email: &str, token: &SubscriptionToken, base_url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            email: email.to_string(),
+            unsubscribe_url: format!(
+                "{}/subscriptions/unsubscribe?subscription_token={}",
+                base_url,
+                token.as_str()
+            ),
+            archive_url: format!("{}/newsletters", base_url),
+        }
+    }
+
+    /// Values used on the public web version of an issue, which is not
+    /// addressed to anyone.
+    ///
+    /// Unsubscribe links need a subscriber's token and no page lets an
+    /// anonymous reader manage a subscription, so `{{unsubscribe_url}}` is
+    /// left empty there rather than pointing somewhere misleading.
+    pub fn anonymous(base_url: &str) -> Self {
+        Self {
+            name: "reader".to_string(),
+            email: String::new(),
+            unsubscribe_url: String::new(),
+            archive_url: format!("{}/newsletters", base_url),
+        }
+    }
+
+    fn get(&self, tag: MergeTag) -> &str {
+        match tag {
+            MergeTag::Name => &self.name,
+            MergeTag::Email => &self.email,
+            MergeTag::UnsubscribeUrl => &self.unsubscribe_url,
+            MergeTag::ArchiveUrl => &self.archive_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeTemplate, MergeValues};
+    use claim::{assert_err, assert_ok};
+
+    fn values() -> MergeValues {
+        MergeValues {
+            name: "Tom & Jerry".into(),
+            email: "tom@example.com".into(),
+            unsubscribe_url: "https://example.com/unsubscribe?token=a&b".into(),
+            archive_url: "https://example.com/newsletters".into(),
+        }
+    }
+
+    #[test]
+    fn bodies_without_tags_are_left_untouched() {
+        let template = MergeTemplate::parse("Hello { world }").unwrap();
+
+        assert_eq!(template.render_html(&values()), "Hello { world }");
+    }
+
+    #[test]
+    fn tags_are_escaped_in_html_bodies() {
+        let template = MergeTemplate::parse("<p>Hi {{name}}</p>").unwrap();
+
+        assert_eq!(template.render_html(&values()), "<p>Hi Tom &amp; Jerry</p>");
+    }
+
+    #[test]
+    fn raw_tags_are_not_escaped() {
+        let template = MergeTemplate::parse("<p>Hi {{{name}}}</p>").unwrap();
+
+        assert_eq!(template.render_html(&values()), "<p>Hi Tom & Jerry</p>");
+    }
+
+    #[test]
+    fn tags_are_never_escaped_in_text_bodies() {
+        let template = MergeTemplate::parse("Hi {{ name }}, see {{archive_url}}").unwrap();
+
+        assert_eq!(
+            template.render_text(&values()),
+            "Hi Tom & Jerry, see https://example.com/newsletters"
+        );
+    }
+
+    #[test]
+    fn all_tags_are_supported() {
+        assert_ok!(MergeTemplate::parse(
+            "{{name}} {{email}} {{unsubscribe_url}} {{archive_url}}"
+        ));
+    }
+
+    #[test]
+    fn web_versions_have_no_unsubscribe_url() {
+        let template =
+            MergeTemplate::parse("<a href=\"{{unsubscribe_url}}\">Unsubscribe</a>").unwrap();
+
+        assert_eq!(
+            template.render_html(&MergeValues::anonymous("https://example.com")),
+            "<a href=\"\">Unsubscribe</a>"
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_rejected() {
+        assert_err!(MergeTemplate::parse("Hi {{nickname}}"));
+    }
+
+    #[test]
+    fn unclosed_tags_are_rejected() {
+        assert_err!(MergeTemplate::parse("Hi {{name"));
+        assert_err!(MergeTemplate::parse("Hi {{{name}}"));
+    }
+
+    #[test]
+    fn escaped_braces_are_kept_literally() {
+        let template = MergeTemplate::parse(r"Use \{{name}} or \{{{name}}} for {{name}}").unwrap();
+
+        assert_eq!(
+            template.render_text(&values()),
+            "Use {{name}} or {{{name}}} for Tom & Jerry"
+        );
+    }
+
+    #[test]
+    fn lenient_parsing_keeps_unknown_and_unclosed_tags_as_text() {
+        let template = MergeTemplate::parse_lenient("Hi {{nickname}} {{name}}, {{name");
+
+        assert_eq!(
+            template.render_text(&values()),
+            "Hi {{nickname}} Tom & Jerry, {{name"
+        );
+    }
+}
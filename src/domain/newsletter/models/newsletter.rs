@@ -1,24 +1,67 @@
+use crate::domain::new_subscriber::models::list::ListId;
 use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::markdown::MarkdownBody;
+use crate::domain::newsletter::models::merge_tags::{MergeTemplate, MergeValues};
+use crate::domain::newsletter::models::tracking::TrackingLinks;
 use serde::Deserialize;
+use serde_aux::field_attributes::deserialize_bool_from_anything;
 
 #[derive(Deserialize, Debug)]
 pub struct NewsletterDto {
     pub title: String,
     #[serde(flatten)]
     pub content: NewsletterContentDto,
+    pub idempotency_key: String,
+    /// Leave empty to publish the issue right away
+    #[serde(default)]
+    pub scheduled_at: Option<String>,
+    /// Leave empty to send the issue to every confirmed subscriber
+    #[serde(default)]
+    pub segment: String,
+    /// Leave empty to send the issue to the default list
+    #[serde(default)]
+    pub list_id: Option<ListId>,
+    /// Tick to record opens and clicks of every recipient
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub tracking: bool,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct NewsletterContentDto {
-    #[serde(rename = "html_content")]
+    #[serde(rename = "html_content", default)]
     pub html: String,
-    #[serde(rename = "text_content")]
+    #[serde(rename = "text_content", default)]
     pub text: String,
+    /// When filled, the HTML and plain text bodies are generated from it
+    #[serde(rename = "markdown_content", default)]
+    pub markdown: Option<String>,
 }
 
+impl NewsletterContentDto {
+    /// Replaces the HTML and plain text bodies with the ones generated from
+    /// the Markdown source, if there is one
+    pub fn render_markdown(self) -> Self {
+        match self.markdown.map(MarkdownBody::parse) {
+            Some(Ok(markdown)) => Self {
+                html: markdown.to_html(),
+                text: markdown.to_text(),
+                markdown: Some(markdown.as_str().to_string()),
+            },
+            Some(Err(_)) | None => Self {
+                markdown: None,
+                ..self
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct Newsletter {
     pub title: NewsletterTitle,
     pub content: NewsletterContent,
+    /// Templates of the HTML and plain text bodies, split once so that
+    /// personalizing an issue for each recipient only fills in the tags
+    templates: (MergeTemplate, MergeTemplate),
 }
 
 #[derive(Debug, PartialEq)]
@@ -76,18 +119,44 @@ impl<B> NewsletterBodyWrapper<B> {
     }
 }
 
-impl TryFrom<NewsletterDto> for Newsletter {
-    type Error = NewsletterError;
+impl Newsletter {
+    /// Validates an issue written by an editor, including its merge tags
+    pub fn parse(title: String, content: NewsletterContentDto) -> Result<Self, NewsletterError> {
+        let content = content.render_markdown();
+        let newsletter = Self::new(title, content.html, content.text, |body| {
+            MergeTemplate::parse(body).map_err(NewsletterError::ValidationError)
+        })?;
+        Ok(newsletter)
+    }
 
-    fn try_from(dto: NewsletterDto) -> Result<Self, Self::Error> {
+    /// Reads an issue stored by an earlier `parse`. Merge tags are not
+    /// checked again: anything that is not a known tag is sent as written
+    pub fn parse_stored(
+        title: String,
+        html: String,
+        text: String,
+    ) -> Result<Self, NewsletterError> {
+        Self::new(title, html, text, |body| {
+            Ok(MergeTemplate::parse_lenient(body))
+        })
+    }
+
+    fn new(
+        title: String,
+        html: String,
+        text: String,
+        template: impl Fn(&str) -> Result<MergeTemplate, NewsletterError>,
+    ) -> Result<Self, NewsletterError> {
         let newsletter_title =
-            NewsletterTitle::parse(dto.title).map_err(NewsletterError::ValidationError)?;
-        let newsletter_html_body =
-            NewsletterBodyWrapper::<NewsletterHtmlBody>::new(dto.content.html)
-                .map_err(NewsletterError::ValidationError)?;
-        let newsletter_text_body =
-            NewsletterBodyWrapper::<NewsletterTextBody>::new(dto.content.text)
-                .map_err(NewsletterError::ValidationError)?;
+            NewsletterTitle::parse(title).map_err(NewsletterError::ValidationError)?;
+        let newsletter_html_body = NewsletterBodyWrapper::<NewsletterHtmlBody>::new(html)
+            .map_err(NewsletterError::ValidationError)?;
+        let newsletter_text_body = NewsletterBodyWrapper::<NewsletterTextBody>::new(text)
+            .map_err(NewsletterError::ValidationError)?;
+        let templates = (
+            template(newsletter_html_body.as_str())?,
+            template(newsletter_text_body.as_str())?,
+        );
 
         let newsletter_content = NewsletterContent {
             html: newsletter_html_body,
@@ -97,8 +166,51 @@ impl TryFrom<NewsletterDto> for Newsletter {
         Ok(Self {
             title: newsletter_title,
             content: newsletter_content,
+            templates,
+        })
+    }
+
+    /// Fills in the merge tags of both bodies for one recipient
+    pub fn personalize(&self, values: &MergeValues) -> Result<Newsletter, NewsletterError> {
+        let html = self.templates.0.render_html(values);
+        let text = self.templates.1.render_text(values);
+        Ok(Self {
+            title: NewsletterTitle(self.title.0.clone()),
+            templates: (MergeTemplate::literal(&html), MergeTemplate::literal(&text)),
+            content: NewsletterContent {
+                html: NewsletterBodyWrapper::new(html).map_err(NewsletterError::ValidationError)?,
+                text: NewsletterBodyWrapper::new(text).map_err(NewsletterError::ValidationError)?,
+            },
         })
     }
+
+    /// Version of a personalized issue whose HTML body records opens and clicks
+    /// through the tracking links of its recipient
+    pub fn with_tracking(&self, links: &TrackingLinks, base_url: &str) -> Newsletter {
+        let html = links.apply(self.content.html.as_str(), base_url);
+        Self {
+            title: NewsletterTitle(self.title.0.clone()),
+            templates: (MergeTemplate::literal(&html), self.templates.1.clone()),
+            content: NewsletterContent {
+                html: NewsletterBodyWrapper {
+                    body: NewsletterBody(html),
+                    _marker: std::marker::PhantomData,
+                },
+                text: NewsletterBodyWrapper {
+                    body: NewsletterBody(self.content.text.as_str().to_string()),
+                    _marker: std::marker::PhantomData,
+                },
+            },
+        }
+    }
+}
+
+impl TryFrom<NewsletterDto> for Newsletter {
+    type Error = NewsletterError;
+
+    fn try_from(dto: NewsletterDto) -> Result<Self, Self::Error> {
+        Newsletter::parse(dto.title, dto.content)
+    }
 }
 
 #[cfg(test)]
@@ -109,7 +221,8 @@ fn test_newsletter_deserialization() {
     let json_data = serde_json::json!({
         "title": "My Newsletter",
         "html_content": "<p>Hello, world!</p>",
-        "text_content":  "Hello, world!"
+        "text_content":  "Hello, world!",
+        "idempotency_key": "b7f4a1de-9d70-4f36-8e2e-3c5ad7c0f1b2"
     });
 
     let newsletter_dto: Result<NewsletterDto, serde_json::Error> =
@@ -120,6 +233,7 @@ fn test_newsletter_deserialization() {
             assert_eq!(dto.title, "My Newsletter");
             assert_eq!(dto.content.html, "<p>Hello, world!</p>");
             assert_eq!(dto.content.text, "Hello, world!");
+            assert_eq!(dto.idempotency_key, "b7f4a1de-9d70-4f36-8e2e-3c5ad7c0f1b2");
         }
         Err(e) => {
             panic!("Failed to deserialize: {:?}", e);
@@ -132,6 +246,58 @@ fn test_newsletter_deserialization() {
     assert_eq!(newsletter.content.text.as_str(), "Hello, world!");
 }
 
+#[test]
+fn markdown_content_replaces_the_html_and_text_bodies() {
+    let content = NewsletterContentDto {
+        html: "<p>Ignored</p>".into(),
+        text: "Ignored".into(),
+        markdown: Some("Hello, [world](https://example.com)!".into()),
+    };
+
+    let newsletter = Newsletter::parse("My Newsletter".into(), content).unwrap();
+    assert_eq!(
+        newsletter.content.html.as_str(),
+        "<p>Hello, <a href=\"https://example.com\">world</a>!</p>\n"
+    );
+    assert_eq!(
+        newsletter.content.text.as_str(),
+        "Hello, world (https://example.com)!"
+    );
+}
+
+#[test]
+fn stored_bodies_are_not_checked_for_merge_tags() {
+    let newsletter = Newsletter::parse_stored(
+        "My Newsletter".into(),
+        "<p>Hi {{nickname}}</p>".into(),
+        "Hi {{nickname}}".into(),
+    )
+    .unwrap();
+
+    assert_eq!(newsletter.content.html.as_str(), "<p>Hi {{nickname}}</p>");
+    assert_err!(Newsletter::parse(
+        "My Newsletter".into(),
+        NewsletterContentDto {
+            html: "<p>Hi {{nickname}}</p>".into(),
+            text: "Hi {{nickname}}".into(),
+            markdown: None,
+        },
+    ));
+}
+
+#[test]
+fn blank_markdown_content_is_ignored() {
+    let content = NewsletterContentDto {
+        html: "<p>Hello</p>".into(),
+        text: "Hello".into(),
+        markdown: Some("  ".into()),
+    };
+
+    let newsletter = Newsletter::parse("My Newsletter".into(), content).unwrap();
+    assert_eq!(newsletter.content.html.as_str(), "<p>Hello</p>");
+    assert_eq!(newsletter.content.text.as_str(), "Hello");
+}
+
 #[test]
 fn empty_newsletter_title_is_rejected() {
     let title = "".to_string();
@@ -0,0 +1,134 @@
+use crate::domain::new_subscriber::models::list::ListId;
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::issue_delivery::NewsletterIssueId;
+use crate::domain::newsletter::models::newsletter::{Newsletter, NewsletterContentDto};
+use crate::domain::newsletter::models::segment::Segment;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+/// Point in time at which a scheduled issue is released for delivery
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledAt(DateTime<Utc>);
+
+impl ScheduledAt {
+    /// Formats accepted from the admin forms, interpreted as UTC
+    const FORM_FORMATS: [&'static str; 2] = ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"];
+
+    pub fn parse(value: &str) -> Result<ScheduledAt, NewsletterError> {
+        let value = value.trim();
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Self(datetime.with_timezone(&Utc)));
+        }
+        Self::FORM_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .map(|datetime| Self(datetime.and_utc()))
+            .ok_or_else(|| {
+                NewsletterError::ValidationError(format!("Invalid schedule time: {}", value))
+            })
+    }
+
+    /// Parses an optional form field, where an empty value means "send now"
+    pub fn parse_optional(value: Option<&str>) -> Result<Option<ScheduledAt>, NewsletterError> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(value) => Self::parse(value).map(Some),
+        }
+    }
+
+    pub fn as_datetime(&self) -> DateTime<Utc> {
+        self.0
+    }
+
+    /// Formats the schedule time as expected by a `datetime-local` input
+    pub fn to_form_value(&self) -> String {
+        self.0.format(Self::FORM_FORMATS[0]).to_string()
+    }
+}
+
+impl From<DateTime<Utc>> for ScheduledAt {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self(value)
+    }
+}
+
+/// A stored issue waiting for its schedule time
+pub struct ScheduledIssue {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub newsletter: Newsletter,
+    /// List picked when the issue was scheduled
+    pub list_id: ListId,
+    pub segment: Segment,
+    pub scheduled_at: ScheduledAt,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScheduledIssueDto {
+    pub title: String,
+    #[serde(flatten)]
+    pub content: NewsletterContentDto,
+    /// Leave empty to send the issue to every confirmed subscriber
+    #[serde(default)]
+    pub segment: String,
+    pub scheduled_at: String,
+}
+
+impl TryFrom<ScheduledIssueDto> for (Newsletter, Segment, ScheduledAt) {
+    type Error = NewsletterError;
+
+    fn try_from(dto: ScheduledIssueDto) -> Result<Self, Self::Error> {
+        let scheduled_at = ScheduledAt::parse(&dto.scheduled_at)?;
+        let segment = Segment::parse(&dto.segment)?;
+        let newsletter = Newsletter::parse(dto.title, dto.content)?;
+        Ok((newsletter, segment, scheduled_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScheduledAt;
+    use chrono::{TimeZone, Utc};
+    use claim::{assert_err, assert_none};
+
+    #[test]
+    fn form_values_are_parsed_as_utc() {
+        let expected = Utc.with_ymd_and_hms(2024, 11, 6, 8, 30, 0).unwrap();
+
+        assert_eq!(
+            ScheduledAt::parse("2024-11-06T08:30")
+                .unwrap()
+                .as_datetime(),
+            expected
+        );
+        assert_eq!(
+            ScheduledAt::parse("2024-11-06T08:30:00")
+                .unwrap()
+                .as_datetime(),
+            expected
+        );
+        assert_eq!(
+            ScheduledAt::parse("2024-11-06T09:30:00+01:00")
+                .unwrap()
+                .as_datetime(),
+            expected
+        );
+    }
+
+    #[test]
+    fn form_value_round_trips() {
+        let scheduled_at = ScheduledAt::parse("2024-11-06T08:30").unwrap();
+
+        assert_eq!(scheduled_at.to_form_value(), "2024-11-06T08:30");
+    }
+
+    #[test]
+    fn invalid_schedule_time_is_rejected() {
+        assert_err!(ScheduledAt::parse("tomorrow"));
+    }
+
+    #[test]
+    fn empty_schedule_time_means_send_now() {
+        assert_none!(ScheduledAt::parse_optional(Some("  ")).unwrap());
+        assert_none!(ScheduledAt::parse_optional(None).unwrap());
+    }
+}
@@ -0,0 +1,359 @@
+use crate::domain::new_subscriber::models::list::ListId;
+use crate::domain::new_subscriber::models::tag::{SubscriberTag, TagError};
+use crate::domain::newsletter::errors::NewsletterError;
+use serde::Deserialize;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SegmentError {
+    #[error("Segments can have at most {} characters", Segment::MAX_LENGTH)]
+    TooLong,
+    #[error("Unexpected character '{0}' in segment")]
+    UnexpectedCharacter(char),
+    #[error("Unexpected '{0}' in segment")]
+    UnexpectedToken(String),
+    #[error("The segment ends unexpectedly")]
+    UnexpectedEnd,
+    #[error(transparent)]
+    InvalidTag(#[from] TagError),
+}
+
+impl From<SegmentError> for NewsletterError {
+    fn from(value: SegmentError) -> Self {
+        Self::ValidationError(value.to_string())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SegmentDto {
+    /// Leave empty to target every confirmed subscriber
+    #[serde(default)]
+    pub segment: String,
+    /// Leave empty to target the default list
+    #[serde(default)]
+    pub list_id: Option<ListId>,
+}
+
+/// Part of the confirmed subscribers an issue is sent to, written as a boolean
+/// expression over their tags, e.g. `rust AND NOT unpaid`.
+/// `NOT` binds tighter than `AND`, which binds tighter than `OR`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment(Option<SegmentExpr>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentExpr {
+    Tag(SubscriberTag),
+    Not(Box<SegmentExpr>),
+    And(Box<SegmentExpr>, Box<SegmentExpr>),
+    Or(Box<SegmentExpr>, Box<SegmentExpr>),
+}
+
+impl Segment {
+    const MAX_LENGTH: usize = 500;
+
+    /// Every confirmed subscriber
+    pub fn everyone() -> Self {
+        Self(None)
+    }
+
+    /// Parses a segment expression. A blank expression targets everyone.
+    pub fn parse(expression: &str) -> Result<Segment, SegmentError> {
+        if expression.len() > Self::MAX_LENGTH {
+            return Err(SegmentError::TooLong);
+        }
+        let tokens = tokenize(expression)?;
+        if tokens.is_empty() {
+            return Ok(Self::everyone());
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.parse_or()?;
+        match parser.next() {
+            None => Ok(Self(Some(expr))),
+            Some(token) => Err(SegmentError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    /// Reads a segment stored with an issue, `None` meaning everyone
+    pub fn parse_stored(expression: Option<&str>) -> Result<Segment, SegmentError> {
+        expression.map_or(Ok(Self::everyone()), Segment::parse)
+    }
+
+    pub fn is_everyone(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Expression over the tags, `None` meaning everyone, for stores that
+    /// filter subscribers themselves
+    pub fn expression(&self) -> Option<&SegmentExpr> {
+        self.0.as_ref()
+    }
+
+    /// Canonical form of the expression, `None` when it targets everyone
+    pub fn to_stored(&self) -> Option<String> {
+        self.0.as_ref().map(ToString::to_string)
+    }
+
+    pub fn matches(&self, tags: &[SubscriberTag]) -> bool {
+        self.0.as_ref().is_none_or(|expr| expr.matches(tags))
+    }
+}
+
+impl TryFrom<SegmentDto> for Segment {
+    type Error = SegmentError;
+
+    fn try_from(dto: SegmentDto) -> Result<Self, Self::Error> {
+        Segment::parse(&dto.segment)
+    }
+}
+
+impl std::fmt::Display for Segment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(expr) => expr.fmt(f),
+            None => Ok(()),
+        }
+    }
+}
+
+impl SegmentExpr {
+    fn matches(&self, tags: &[SubscriberTag]) -> bool {
+        match self {
+            Self::Tag(tag) => tags.contains(tag),
+            Self::Not(expr) => !expr.matches(tags),
+            Self::And(left, right) => left.matches(tags) && right.matches(tags),
+            Self::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+
+    /// Binding strength, used to only print the parentheses that are needed
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 0,
+            Self::And(..) => 1,
+            Self::Not(_) | Self::Tag(_) => 2,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl std::fmt::Display for SegmentExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tag(tag) => tag.fmt(f),
+            Self::Not(expr) => {
+                f.write_str("NOT ")?;
+                expr.fmt_operand(f, 2)
+            }
+            Self::And(left, right) => {
+                left.fmt_operand(f, 1)?;
+                f.write_str(" AND ")?;
+                right.fmt_operand(f, 1)
+            }
+            Self::Or(left, right) => {
+                left.fmt_operand(f, 0)?;
+                f.write_str(" OR ")?;
+                right.fmt_operand(f, 0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Open => f.write_str("("),
+            Token::Close => f.write_str(")"),
+            Token::Word(word) => f.write_str(word),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token<'_>>, SegmentError> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    let mut tokens = Vec::new();
+    let mut rest = expression;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c == '(' || c == ')' {
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            rest = &rest[1..];
+        } else if is_word_char(c) {
+            let end = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            rest = &rest[end..];
+        } else {
+            return Err(SegmentError::UnexpectedCharacter(c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, one method per precedence level
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<SegmentExpr, SegmentError> {
+        let mut expr = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.position += 1;
+            expr = SegmentExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<SegmentExpr, SegmentError> {
+        let mut expr = self.parse_not()?;
+        while self.next_is_keyword("and") {
+            self.position += 1;
+            expr = SegmentExpr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<SegmentExpr, SegmentError> {
+        if self.next_is_keyword("not") {
+            self.position += 1;
+            return Ok(SegmentExpr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_operand()
+    }
+
+    fn parse_operand(&mut self) -> Result<SegmentExpr, SegmentError> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    Some(token) => Err(SegmentError::UnexpectedToken(token.to_string())),
+                    None => Err(SegmentError::UnexpectedEnd),
+                }
+            }
+            Some(Token::Word(word)) => Ok(SegmentExpr::Tag(SubscriberTag::parse(word)?)),
+            Some(token) => Err(SegmentError::UnexpectedToken(token.to_string())),
+            None => Err(SegmentError::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Segment;
+    use crate::domain::new_subscriber::models::tag::SubscriberTag;
+    use claim::assert_err;
+
+    fn tags(tags: &str) -> Vec<SubscriberTag> {
+        SubscriberTag::parse_list(tags).unwrap()
+    }
+
+    #[test]
+    fn blank_segments_target_everyone() {
+        let segment = Segment::parse("  ").unwrap();
+
+        assert!(segment.is_everyone());
+        assert!(segment.matches(&[]));
+        assert_eq!(segment.to_stored(), None);
+    }
+
+    #[test]
+    fn segments_match_the_tags_of_a_subscriber() {
+        let segment = Segment::parse("rust AND NOT unpaid").unwrap();
+
+        assert!(segment.matches(&tags("rust")));
+        assert!(segment.matches(&tags("rust, beta")));
+        assert!(!segment.matches(&tags("rust, unpaid")));
+        assert!(!segment.matches(&tags("beta")));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let segment = Segment::parse("beta OR rust AND go").unwrap();
+
+        assert!(segment.matches(&tags("beta")));
+        assert!(!segment.matches(&tags("rust")));
+        assert!(segment.matches(&tags("rust, go")));
+    }
+
+    #[test]
+    fn parentheses_group_expressions() {
+        let segment = Segment::parse("(beta OR rust) AND go").unwrap();
+
+        assert!(!segment.matches(&tags("beta")));
+        assert!(segment.matches(&tags("beta, go")));
+    }
+
+    #[test]
+    fn segments_are_stored_in_canonical_form() {
+        let test_cases = [
+            ("rust and not Unpaid", "rust AND NOT unpaid"),
+            ("((beta)) OR (rust AND go)", "beta OR rust AND go"),
+            ("(beta OR rust) AND go", "(beta OR rust) AND go"),
+            ("not (beta or rust)", "NOT (beta OR rust)"),
+        ];
+
+        for (expression, canonical) in test_cases {
+            let segment = Segment::parse(expression).unwrap();
+
+            assert_eq!(segment.to_stored().as_deref(), Some(canonical));
+            assert_eq!(Segment::parse(canonical).unwrap(), segment);
+        }
+    }
+
+    #[test]
+    fn invalid_segments_are_rejected() {
+        let test_cases = [
+            "rust AND",
+            "AND rust",
+            "rust beta",
+            "(rust",
+            "rust)",
+            "rust & beta",
+            "NOT",
+            "()",
+        ];
+
+        for expression in test_cases {
+            assert_err!(Segment::parse(expression), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn overly_long_segments_are_rejected() {
+        let expression = vec!["rust"; 200].join(" OR ");
+
+        assert_err!(Segment::parse(&expression));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::domain::new_subscriber::models::email::SubscriberEmail;
+use crate::domain::new_subscriber::models::list::ListId;
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::newsletter::NewsletterContentDto;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct TestIssueDto {
+    pub title: String,
+    #[serde(flatten)]
+    pub content: NewsletterContentDto,
+    /// Addresses separated by commas, semicolons or whitespace
+    pub test_recipients: String,
+    /// Segment picked for the real issue, kept when the form is rendered again
+    #[serde(default)]
+    pub segment: String,
+    /// List picked for the real issue, kept when the form is rendered again
+    #[serde(default)]
+    pub list_id: Option<ListId>,
+}
+
+/// Addresses a test issue is sent to instead of the subscriber list
+#[derive(Debug)]
+pub struct TestRecipients(Vec<SubscriberEmail>);
+
+impl TestRecipients {
+    const MAX_RECIPIENTS: usize = 5;
+
+    pub fn parse(value: &str) -> Result<TestRecipients, NewsletterError> {
+        let mut recipients: Vec<SubscriberEmail> = Vec::new();
+        for address in value
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|address| !address.is_empty())
+        {
+            let recipient = SubscriberEmail::parse(address.to_string())?;
+            if !recipients.contains(&recipient) {
+                recipients.push(recipient);
+            }
+        }
+
+        if recipients.is_empty() {
+            return Err(NewsletterError::ValidationError(
+                "At least one test recipient is required".into(),
+            ));
+        }
+        if recipients.len() > Self::MAX_RECIPIENTS {
+            return Err(NewsletterError::ValidationError(format!(
+                "A test issue can be sent to at most {} recipients",
+                Self::MAX_RECIPIENTS
+            )));
+        }
+        Ok(Self(recipients))
+    }
+
+    pub fn as_slice(&self) -> &[SubscriberEmail] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestRecipients;
+    use claim::assert_err;
+
+    #[test]
+    fn recipients_can_be_separated_by_commas_semicolons_and_whitespace() {
+        let recipients =
+            TestRecipients::parse("a@example.com, b@example.com;c@example.com\nd@example.com")
+                .unwrap();
+
+        assert_eq!(recipients.as_slice().len(), 4);
+    }
+
+    #[test]
+    fn duplicated_recipients_are_sent_a_single_test_issue() {
+        let recipients = TestRecipients::parse("a@example.com, a@example.com").unwrap();
+
+        assert_eq!(recipients.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn empty_recipient_list_is_rejected() {
+        assert_err!(TestRecipients::parse(" , "));
+    }
+
+    #[test]
+    fn invalid_recipient_is_rejected() {
+        assert_err!(TestRecipients::parse("a@example.com, not-an-email"));
+    }
+
+    #[test]
+    fn too_many_recipients_are_rejected() {
+        let recipients = (0..=TestRecipients::MAX_RECIPIENTS)
+            .map(|i| format!("user{}@example.com", i))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        assert_err!(TestRecipients::parse(&recipients));
+    }
+}
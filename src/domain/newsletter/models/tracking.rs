@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+pub type TrackingId = uuid::Uuid;
+
+/// Tracking links issued for one recipient of an issue
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackingLinks {
+    /// Link of the pixel that records opens
+    pub open: TrackingId,
+    /// Link that records clicks on each URL of the issue
+    pub clicks: HashMap<String, TrackingId>,
+}
+
+impl TrackingLinks {
+    /// Absolute URLs the links of an HTML body point to, in order of appearance
+    /// and without duplicates. Relative links, other schemes (`mailto:`...) and
+    /// links built from merge tags, such as `{{unsubscribe_url}}`, are not tracked:
+    /// the body must be the one written by the editor, before it is personalized.
+    pub fn tracked_urls(html: &str) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for href in hrefs(html) {
+            let url = decode_href(href);
+            if is_trackable(&url) && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+
+    /// Points the links of an HTML body to the click tracker and appends the
+    /// open pixel, before `</body>` when the body has one
+    pub fn apply(&self, html: &str, base_url: &str) -> String {
+        let mut tracked = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some(start) = rest.find(HREF) {
+            let value_start = start + HREF.len();
+            let Some(length) = rest[value_start..].find('"') else {
+                break;
+            };
+            let href = &rest[value_start..value_start + length];
+            tracked.push_str(&rest[..value_start]);
+            match self.clicks.get(&decode_href(href)) {
+                Some(tracking_id) => tracked.push_str(&tracking_url(base_url, *tracking_id)),
+                None => tracked.push_str(href),
+            }
+            rest = &rest[value_start + length..];
+        }
+        tracked.push_str(rest);
+
+        let pixel = format!(
+            r#"<img src="{}" width="1" height="1" alt="" style="display:none">"#,
+            tracking_url(base_url, self.open)
+        );
+        match tracked.rfind("</body>") {
+            Some(end) => tracked.insert_str(end, &pixel),
+            None => tracked.push_str(&pixel),
+        }
+        tracked
+    }
+}
+
+const HREF: &str = "href=\"";
+
+fn hrefs(html: &str) -> impl Iterator<Item = &str> {
+    html.split(HREF)
+        .skip(1)
+        .filter_map(|part| part.split_once('"').map(|(href, _)| href))
+}
+
+/// Links are HTML-escaped in the body, e.g. `&amp;` between query parameters
+fn decode_href(href: &str) -> String {
+    htmlescape::decode_html(href).unwrap_or_else(|_| href.to_string())
+}
+
+fn is_trackable(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://")) && !url.contains("{{")
+}
+
+pub fn tracking_url(base_url: &str, tracking_id: TrackingId) -> String {
+    format!("{}/t/{}", base_url, tracking_id)
+}
+
+/// What following a tracking link records
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackedTarget {
+    Open,
+    /// A click on a link of the issue, forwarded to its URL
+    Click(String),
+}
+
+/// Opens and clicks of an issue. Unique counts are per recipient.
+#[derive(Debug, Clone, Default)]
+pub struct TrackingStats {
+    pub opens: i64,
+    pub unique_opens: i64,
+    pub clicks: i64,
+    pub unique_clicks: i64,
+    /// Clicks on each link, most clicked first
+    pub links: Vec<LinkClicks>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkClicks {
+    pub url: String,
+    pub clicks: i64,
+    pub unique_clicks: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrackingLinks;
+    use std::collections::HashMap;
+
+    fn links(urls: &[&str]) -> TrackingLinks {
+        TrackingLinks {
+            open: uuid::Uuid::nil(),
+            clicks: urls
+                .iter()
+                .map(|url| (url.to_string(), uuid::Uuid::new_v4()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn only_absolute_links_are_tracked_once() {
+        let html = r#"<a href="https://example.com/a">A</a>
+            <a href="/relative">R</a>
+            <a href="mailto:editor@example.com">M</a>
+            <a href="https://example.com/a">A again</a>
+            <a href="http://example.com/b?x=1&amp;y=2">B</a>"#;
+
+        assert_eq!(
+            TrackingLinks::tracked_urls(html),
+            vec!["https://example.com/a", "http://example.com/b?x=1&y=2"]
+        );
+    }
+
+    #[test]
+    fn links_built_from_merge_tags_are_not_tracked() {
+        let html = r#"<a href="{{unsubscribe_url}}">U</a>
+            <a href="https://example.com/?who={{email}}">E</a>
+            <a href="https://example.com/a">A</a>"#;
+
+        assert_eq!(
+            TrackingLinks::tracked_urls(html),
+            vec!["https://example.com/a"]
+        );
+    }
+
+    #[test]
+    fn tracked_links_point_to_the_click_tracker() {
+        let html =
+            r#"<p><a href="https://example.com/a?x=1&amp;y=2">A</a> <a href="/relative">R</a></p>"#;
+        let links = links(&["https://example.com/a?x=1&y=2"]);
+
+        let tracked = links.apply(html, "https://blog.example.com");
+
+        let tracking_id = links.clicks["https://example.com/a?x=1&y=2"];
+        assert!(tracked.contains(&format!(
+            r#"<a href="https://blog.example.com/t/{}">A</a>"#,
+            tracking_id
+        )));
+        assert!(tracked.contains(r#"<a href="/relative">R</a>"#));
+        assert!(!tracked.contains("https://example.com/a"));
+    }
+
+    #[test]
+    fn the_open_pixel_is_appended_to_the_body() {
+        let pixel = format!(
+            r#"<img src="https://blog.example.com/t/{}""#,
+            uuid::Uuid::nil()
+        );
+
+        let tracked = links(&[]).apply("<p>Hello</p>", "https://blog.example.com");
+        assert!(tracked.starts_with("<p>Hello</p>"));
+        assert!(tracked.contains(&pixel));
+
+        let tracked = links(&[]).apply(
+            "<html><body><p>Hello</p></body></html>",
+            "https://blog.example.com",
+        );
+        assert!(tracked.ends_with("</body></html>"));
+        assert!(tracked.contains(&pixel));
+    }
+
+    #[test]
+    fn unterminated_links_are_left_alone() {
+        let html = r#"<a href="https://example.com/a">A</a><a href="oops"#;
+        let links = links(&["https://example.com/a"]);
+
+        let tracked = links.apply(html, "https://blog.example.com");
+
+        assert!(tracked.contains(r#"<a href="oops"#));
+    }
+}
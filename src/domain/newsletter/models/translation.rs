@@ -0,0 +1,131 @@
+use crate::domain::new_subscriber::models::locale::Locale;
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::issue_delivery::NewsletterIssueId;
+use crate::domain::newsletter::models::newsletter::{Newsletter, NewsletterContentDto};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct TranslationDto {
+    pub locale: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(flatten)]
+    pub content: NewsletterContentDto,
+}
+
+impl TranslationDto {
+    /// A form submitted without a title nor a body removes the translation
+    pub fn is_blank(&self) -> bool {
+        self.title.trim().is_empty()
+            && self.content.html.trim().is_empty()
+            && self.content.text.trim().is_empty()
+            && self
+                .content
+                .markdown
+                .as_deref()
+                .is_none_or(|markdown| markdown.trim().is_empty())
+    }
+}
+
+/// Version of an issue sent to the subscribers whose locale is not the default one
+#[derive(Debug)]
+pub struct IssueTranslation {
+    pub locale: Locale,
+    pub newsletter: Newsletter,
+    /// Source the bodies were generated from, kept so that it can be edited again
+    pub markdown: Option<String>,
+}
+
+impl TryFrom<TranslationDto> for IssueTranslation {
+    type Error = NewsletterError;
+
+    fn try_from(dto: TranslationDto) -> Result<Self, Self::Error> {
+        let locale = Locale::parse(&dto.locale)
+            .map_err(|e| NewsletterError::ValidationError(e.to_string()))?;
+        let content = dto.content.render_markdown();
+        let markdown = content.markdown.clone();
+        Ok(Self {
+            locale,
+            newsletter: Newsletter::parse(dto.title, content)?,
+            markdown,
+        })
+    }
+}
+
+/// An issue together with the translations written so far
+#[derive(Debug)]
+pub struct IssueTranslations {
+    pub newsletter_issue_id: NewsletterIssueId,
+    pub title: String,
+    /// Locale the issue itself is written in
+    pub default_locale: Locale,
+    pub translations: Vec<IssueTranslation>,
+}
+
+impl IssueTranslations {
+    /// Locales an editor can translate the issue into
+    pub fn translatable_locales(&self) -> impl Iterator<Item = Locale> + '_ {
+        Locale::ALL
+            .into_iter()
+            .filter(|locale| *locale != self.default_locale)
+    }
+
+    pub fn get(&self, locale: Locale) -> Option<&IssueTranslation> {
+        self.translations.iter().find(|t| t.locale == locale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IssueTranslation, TranslationDto};
+    use crate::domain::new_subscriber::models::locale::Locale;
+    use crate::domain::newsletter::models::newsletter::NewsletterContentDto;
+    use claim::assert_err;
+
+    fn dto(locale: &str, title: &str, markdown: Option<&str>) -> TranslationDto {
+        TranslationDto {
+            locale: locale.into(),
+            title: title.into(),
+            content: NewsletterContentDto {
+                html: String::new(),
+                text: String::new(),
+                markdown: markdown.map(Into::into),
+            },
+        }
+    }
+
+    #[test]
+    fn translations_can_be_written_in_markdown() {
+        let translation =
+            IssueTranslation::try_from(dto("fr", "Bonjour", Some("Salut *tout le monde*")))
+                .unwrap();
+
+        assert_eq!(translation.locale, Locale::French);
+        assert_eq!(translation.newsletter.title.as_str(), "Bonjour");
+        assert_eq!(
+            translation.newsletter.content.text.as_str(),
+            "Salut tout le monde"
+        );
+        assert_eq!(
+            translation.markdown.as_deref(),
+            Some("Salut *tout le monde*")
+        );
+    }
+
+    #[test]
+    fn translations_to_unsupported_locales_are_rejected() {
+        assert_err!(IssueTranslation::try_from(dto("xx", "Title", Some("Body"))));
+    }
+
+    #[test]
+    fn translations_need_a_title_and_a_body() {
+        assert_err!(IssueTranslation::try_from(dto("fr", "", Some("Body"))));
+        assert_err!(IssueTranslation::try_from(dto("fr", "Title", None)));
+    }
+
+    #[test]
+    fn empty_forms_are_blank() {
+        assert!(dto("fr", " ", Some("")).is_blank());
+        assert!(!dto("fr", "", Some("Body")).is_blank());
+    }
+}
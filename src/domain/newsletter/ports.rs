@@ -1,27 +1,428 @@
 use async_trait::async_trait;
 
 use crate::domain::{
-    new_subscriber::models::{email::SubscriberEmail, token::SubscriptionToken},
+    idempotency::models::IdempotentResponse,
+    new_subscriber::{
+        models::{
+            email::SubscriberEmail,
+            list::{ListId, MailingList},
+            locale::Locale,
+            token::LinkTokens,
+        },
+        ports::ListRepository,
+    },
     newsletter::{
         errors::NewsletterError,
-        models::{confirmed_subscribers::ConfirmedSubscriber, newsletter::Newsletter},
+        models::{
+            archive::{ArchiveEntry, ArchivedIssue, IssueSlug, IssueVisibilityDto, PublishedIssue},
+            confirmed_subscribers::ConfirmedSubscriber,
+            digest::DueDigest,
+            draft::{Draft, DraftContent, DraftDto, NewsletterPreview},
+            feed::Feed,
+            issue_delivery::{
+                DeadLetter, DeadLetterReplayDto, DeliveryLogEntry, DeliveryOutcome, DeliveryTask,
+                ExecutionOutcome, IssueDeliveryReport, IssueSummary, NewsletterIssueId,
+            },
+            newsletter::{Newsletter, NewsletterTitle},
+            scheduled_issue::{ScheduledAt, ScheduledIssue},
+            segment::{Segment, SegmentDto},
+            test_issue::TestRecipients,
+            tracking::{TrackedTarget, TrackingId, TrackingLinks, TrackingStats},
+            translation::{IssueTranslation, IssueTranslations, TranslationDto},
+        },
     },
 };
 
 #[async_trait]
-pub trait NewsletterRepository: Clone + Send + Sync + 'static {
+pub trait NewsletterRepository: ListRepository {
+    /// Retrieves the confirmed subscribers of a list whose tags match `segment`. Subscribers
+    /// who receive a weekly digest or paused their subscription are left out.
     async fn get_confirmed_subscribers(
         &self,
-    ) -> Result<Vec<Result<(ConfirmedSubscriber, SubscriptionToken), NewsletterError>>, anyhow::Error>;
+        list_id: ListId,
+        segment: &Segment,
+    ) -> Result<Vec<Result<ConfirmedSubscriber, NewsletterError>>, anyhow::Error>;
+
+    /// Counts the subscribers `get_confirmed_subscribers` would retrieve
+    async fn count_confirmed_subscribers(
+        &self,
+        list_id: ListId,
+        segment: &Segment,
+    ) -> Result<usize, NewsletterError>;
+
+    /// Retrieves a subscriber of a list if the subscriber is still confirmed
+    async fn get_confirmed_subscriber(
+        &self,
+        list_id: ListId,
+        email: &SubscriberEmail,
+    ) -> Result<Option<ConfirmedSubscriber>, NewsletterError>;
+
+    /// Stores new unsubscribe and preferences tokens for the links of an email
+    /// sent to a confirmed subscriber
+    async fn issue_link_tokens(
+        &self,
+        subscriber: &ConfirmedSubscriber,
+    ) -> Result<LinkTokens, NewsletterError>;
+
+    /// Stores a newsletter issue, enqueues one delivery task per recipient,
+    /// records them as queued in the delivery log and saves the response to the
+    /// request within the same transaction. Nothing is stored, and a conflict is
+    /// returned, when a response was already saved for the idempotency key.
+    async fn add_issue_and_enqueue_delivery_tasks(
+        &self,
+        newsletter: &Newsletter,
+        list_id: ListId,
+        segment: &Segment,
+        tracking_enabled: bool,
+        recipients: &[SubscriberEmail],
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError>;
+
+    /// Stores a newsletter issue that is released for delivery at `scheduled_at`
+    /// along with the response to the request, like `add_issue_and_enqueue_delivery_tasks`
+    async fn add_scheduled_issue(
+        &self,
+        newsletter: &Newsletter,
+        list_id: ListId,
+        segment: &Segment,
+        scheduled_at: ScheduledAt,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError>;
+
+    /// Lists the issues that have not been released yet, soonest first
+    async fn get_scheduled_issues(&self) -> Result<Vec<ScheduledIssue>, NewsletterError>;
+
+    async fn get_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<ScheduledIssue, NewsletterError>;
+
+    /// Replaces the content, segment and schedule of an issue that has not been released yet
+    async fn update_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        newsletter: &Newsletter,
+        segment: &Segment,
+        scheduled_at: ScheduledAt,
+    ) -> Result<(), NewsletterError>;
+
+    /// Removes an issue that has not been released yet
+    async fn delete_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError>;
+
+    /// Retrieves the oldest scheduled issue whose schedule time has passed, with the
+    /// list and segment it is sent to
+    async fn get_due_scheduled_issue(
+        &self,
+    ) -> Result<Option<(NewsletterIssueId, ListId, Segment)>, NewsletterError>;
+
+    /// Marks an unpublished (draft or scheduled) issue as published and enqueues its
+    /// delivery tasks within the same transaction. The list and segment are recorded with
+    /// the issue. Returns `false` if the issue was already released or deleted.
+    async fn release_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        list_id: ListId,
+        segment: &Segment,
+        recipients: &[SubscriberEmail],
+    ) -> Result<bool, NewsletterError>;
+
+    async fn add_draft(&self, draft: &DraftContent) -> Result<NewsletterIssueId, NewsletterError>;
+
+    /// Lists drafts, most recently edited first
+    async fn get_drafts(&self) -> Result<Vec<Draft>, NewsletterError>;
+
+    async fn get_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Draft, NewsletterError>;
+
+    async fn update_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        draft: &DraftContent,
+    ) -> Result<(), NewsletterError>;
+
+    async fn delete_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError>;
+
+    async fn get_published_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<PublishedIssue, NewsletterError>;
+
+    /// Claims the weekly digest of a subscriber whose last digest is a week old, unless
+    /// another worker claimed it. The claim expires if the digest is not completed in
+    /// time, so that a worker that stopped midway does not lose it.
+    async fn claim_due_digest(&self) -> Result<Option<DueDigest>, NewsletterError>;
+
+    /// Marks a claimed digest as done: the next one starts where it was claimed. When
+    /// sending failed, each of its issues is moved to dead letters for the subscriber,
+    /// as a failed delivery of that issue would be.
+    async fn complete_digest(
+        &self,
+        digest: &DueDigest,
+        issue_ids: &[NewsletterIssueId],
+        outcome: &DeliveryOutcome,
+    ) -> Result<(), NewsletterError>;
+
+    /// Lists the issues published to a list after `since`, oldest first
+    async fn get_issues_published_since(
+        &self,
+        list_id: ListId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PublishedIssue>, NewsletterError>;
+
+    /// Retrieves the title of an issue, whatever its state
+    async fn get_issue_title(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<NewsletterTitle, NewsletterError>;
+
+    async fn get_translations(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Vec<IssueTranslation>, NewsletterError>;
+
+    /// Adds the translation of an issue, or replaces the one it already has in that locale
+    async fn save_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        translation: &IssueTranslation,
+    ) -> Result<(), NewsletterError>;
+
+    async fn delete_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        locale: Locale,
+    ) -> Result<(), NewsletterError>;
+
+    /// Lists the published issues that are not hidden from the archive, most recent first
+    async fn get_archive_entries(&self) -> Result<Vec<ArchiveEntry>, NewsletterError>;
+
+    /// Retrieves a published issue by slug, unless it is hidden from the archive
+    async fn get_archived_issue(&self, slug: &IssueSlug) -> Result<ArchivedIssue, NewsletterError>;
+
+    async fn set_issue_hidden_from_archive(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        hidden: bool,
+    ) -> Result<(), NewsletterError>;
+
+    /// Retrieves the `max_entries` most recent issues of the archive
+    async fn get_feed(&self, max_entries: i64) -> Result<Feed, NewsletterError>;
+
+    /// Claims the next delivery task that is not being processed by another worker.
+    /// A claimed task becomes available again if it is not deleted before its lease expires.
+    async fn dequeue_delivery_task(&self) -> Result<Option<DeliveryTask>, NewsletterError>;
+
+    /// Removes a delivery task from the queue and records its outcome in the delivery log.
+    /// Failed tasks are moved to the dead-letter table.
+    async fn complete_delivery_task(
+        &self,
+        task: &DeliveryTask,
+        outcome: &DeliveryOutcome,
+    ) -> Result<(), NewsletterError>;
+
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, NewsletterError>;
+
+    /// Moves a dead-lettered delivery back to the queue
+    async fn replay_dead_letter(&self, task: &DeliveryTask) -> Result<(), NewsletterError>;
+
+    /// Lists published issues, most recent first, with their delivery totals
+    async fn get_issue_summaries(&self) -> Result<Vec<IssueSummary>, NewsletterError>;
+
+    async fn get_issue_summary(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueSummary, NewsletterError>;
+
+    /// Retrieves the delivery log of the recipients of an issue that failed or bounced
+    async fn get_undelivered_recipients(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Vec<DeliveryLogEntry>, NewsletterError>;
+
+    /// Stores the open pixel of a recipient of a tracked issue and one click
+    /// tracking link per URL. The links issued to the recipient by an earlier
+    /// attempt to deliver the issue are reused.
+    async fn issue_tracking_links(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        recipient: &ConfirmedSubscriber,
+        urls: &[String],
+    ) -> Result<TrackingLinks, NewsletterError>;
+
+    /// Records that a tracking link was followed and tells what it stands for
+    async fn record_tracking_event(
+        &self,
+        tracking_id: TrackingId,
+    ) -> Result<TrackedTarget, NewsletterError>;
+
+    async fn get_tracking_stats(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<TrackingStats, NewsletterError>;
 }
 
 #[async_trait]
 pub trait NewsletterService: Clone + Send + Sync + 'static {
-    async fn send_newsletter(
+    /// Lists the mailing lists an issue can be sent to, the default one first
+    async fn get_lists(&self) -> Result<Vec<MailingList>, NewsletterError>;
+
+    /// Stores the newsletter issue and schedules its delivery to the confirmed
+    /// subscribers of the list that belong to the segment. Without a list, the
+    /// issue goes to the default one. The response to the request is saved with
+    /// the issue, so that a retry never publishes it twice.
+    async fn publish_newsletter(
+        &self,
+        newsletter: Newsletter,
+        list_id: Option<ListId>,
+        segment: Segment,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError>;
+
+    /// Sends the rendered issue to the test recipients only, with throwaway
+    /// link tokens. Nothing is stored.
+    async fn send_test_issue(
+        &self,
+        newsletter: &Newsletter,
+        recipients: &TestRecipients,
+        base_url: &str,
+    ) -> Result<(), NewsletterError>;
+
+    /// Stores the newsletter issue to be published to a segment of a list at a
+    /// future time, along with the response to the request
+    async fn schedule_newsletter(
+        &self,
+        newsletter: Newsletter,
+        list_id: Option<ListId>,
+        segment: Segment,
+        scheduled_at: ScheduledAt,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError>;
+
+    async fn get_scheduled_issues(&self) -> Result<Vec<ScheduledIssue>, NewsletterError>;
+
+    async fn get_scheduled_issue(
         &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<ScheduledIssue, NewsletterError>;
+
+    /// Edits a scheduled issue and moves it to a new (future) schedule time
+    async fn reschedule_newsletter(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
         newsletter: Newsletter,
+        segment: Segment,
+        scheduled_at: ScheduledAt,
+    ) -> Result<(), NewsletterError>;
+
+    async fn cancel_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError>;
+
+    async fn create_draft(&self, req: DraftDto) -> Result<NewsletterIssueId, NewsletterError>;
+
+    async fn get_drafts(&self) -> Result<Vec<Draft>, NewsletterError>;
+
+    async fn get_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Draft, NewsletterError>;
+
+    async fn update_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: DraftDto,
+    ) -> Result<(), NewsletterError>;
+
+    async fn delete_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError>;
+
+    /// Renders a draft as a subscriber would receive it
+    async fn preview_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        base_url: &str,
+    ) -> Result<NewsletterPreview, NewsletterError>;
+
+    /// Retrieves an issue together with its translations, whatever its state
+    async fn get_translations(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueTranslations, NewsletterError>;
+
+    /// Stores the translation of an issue. A blank form removes it.
+    async fn save_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: TranslationDto,
+    ) -> Result<(), NewsletterError>;
+
+    /// Publishes a draft right away to the confirmed subscribers of a segment of a list
+    async fn publish_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: SegmentDto,
+    ) -> Result<(), NewsletterError>;
+
+    /// Counts the confirmed subscribers an issue sent to a segment of a list would reach
+    async fn count_recipients(&self, req: SegmentDto) -> Result<usize, NewsletterError>;
+
+    /// Publishes at most one scheduled issue whose schedule time has passed
+    async fn try_release_scheduled_issue(&self) -> Result<ExecutionOutcome, NewsletterError>;
+
+    /// Delivers at most one pending newsletter issue to a subscriber
+    async fn try_execute_task(&self, base_url: &str) -> Result<ExecutionOutcome, NewsletterError>;
+
+    /// Sends at most one weekly digest that is due, made of the issues published to the
+    /// list of the subscriber since their last digest
+    async fn try_send_digest(&self, base_url: &str) -> Result<ExecutionOutcome, NewsletterError>;
+
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, NewsletterError>;
+
+    async fn replay_dead_letter(&self, req: DeadLetterReplayDto) -> Result<(), NewsletterError>;
+
+    async fn get_archive(&self) -> Result<Vec<ArchiveEntry>, NewsletterError>;
+
+    /// Retrieves the web version of an issue, with its merge tags filled in with generic values
+    async fn get_archived_issue(
+        &self,
+        slug: String,
         base_url: &str,
+    ) -> Result<ArchivedIssue, NewsletterError>;
+
+    /// Retrieves the most recent issues of the archive, for the RSS and Atom feeds
+    async fn get_feed(&self, base_url: &str) -> Result<Feed, NewsletterError>;
+
+    /// Shows or hides a published issue in the public archive
+    async fn set_issue_visibility(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: IssueVisibilityDto,
     ) -> Result<(), NewsletterError>;
+
+    async fn get_issue_summaries(&self) -> Result<Vec<IssueSummary>, NewsletterError>;
+
+    /// Delivery totals of an issue together with the recipients that did not get it
+    async fn get_issue_delivery_report(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueDeliveryReport, NewsletterError>;
+
+    /// Records the open or click behind a tracking link of a recipient
+    async fn track(&self, tracking_id: TrackingId) -> Result<TrackedTarget, NewsletterError>;
 }
 
 #[async_trait]
@@ -30,7 +431,30 @@ pub trait NewsletterNotifier: Clone + Send + Sync + 'static {
         &self,
         recipient: &SubscriberEmail,
         newsletter: &Newsletter,
-        token: SubscriptionToken,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
         base_url: &str,
+        locale: Locale,
     ) -> Result<(), NewsletterError>;
+
+    /// Sends several issues at once, to a subscriber who receives a weekly digest
+    async fn send_digest(
+        &self,
+        recipient: &SubscriberEmail,
+        issues: &[Newsletter],
+        tokens: &LinkTokens,
+        base_url: &str,
+        locale: Locale,
+    ) -> Result<(), NewsletterError>;
+
+    /// Builds the subject and bodies sent to a subscriber, unsubscribe footer included.
+    /// Issues with a web version also link to it. The footer is written in `locale`.
+    fn render_newsletter(
+        &self,
+        newsletter: &Newsletter,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
+        base_url: &str,
+        locale: Locale,
+    ) -> NewsletterPreview;
 }
@@ -1,8 +1,34 @@
 use async_trait::async_trait;
 
+use crate::domain::idempotency::models::IdempotentResponse;
+use crate::domain::new_subscriber::{
+    errors::SubscriberError,
+    models::{
+        email::SubscriberEmail,
+        list::{ListId, MailingList},
+        locale::Locale,
+        token::LinkTokens,
+    },
+};
 use crate::domain::newsletter::{
     errors::NewsletterError,
-    models::newsletter::Newsletter,
+    models::{
+        archive::{ArchiveEntry, ArchivedIssue, IssueSlug, IssueVisibilityDto},
+        digest::DueDigest,
+        draft::{Draft, DraftContent, DraftDto, NewsletterPreview},
+        feed::Feed,
+        issue_delivery::{
+            DeadLetter, DeadLetterReplayDto, DeliveryOutcome, DeliveryTask, ExecutionOutcome,
+            IssueDeliveryReport, IssueSummary, NewsletterIssueId,
+        },
+        merge_tags::{MergeTemplate, MergeValues},
+        newsletter::Newsletter,
+        scheduled_issue::{ScheduledAt, ScheduledIssue},
+        segment::{Segment, SegmentDto},
+        test_issue::TestRecipients,
+        tracking::{TrackedTarget, TrackingId, TrackingLinks},
+        translation::{IssueTranslation, IssueTranslations, TranslationDto},
+    },
     ports::{NewsletterNotifier, NewsletterRepository, NewsletterService},
 };
 use std::sync::Arc;
@@ -15,6 +41,8 @@ where
 {
     pub repo: Arc<R>,
     pub notifier: Arc<N>,
+    /// Locale issues are written in, and sent in when they were not translated
+    pub default_locale: Locale,
 }
 
 impl<R, N> BlogDelivery<R, N>
@@ -22,9 +50,60 @@ where
     R: NewsletterRepository,
     N: NewsletterNotifier,
 {
-    pub fn new(repo: Arc<R>, notifier: Arc<N>) -> Self {
-        Self { repo, notifier }
+    pub fn new(repo: Arc<R>, notifier: Arc<N>, default_locale: Locale) -> Self {
+        Self {
+            repo,
+            notifier,
+            default_locale,
+        }
+    }
+
+    /// List an issue is sent to: the default one unless another is picked
+    async fn resolve_list(&self, list_id: Option<ListId>) -> Result<MailingList, NewsletterError> {
+        match list_id {
+            Some(list_id) => self.repo.get_list(list_id).await.map_err(|e| match e {
+                SubscriberError::NotFound(message) => NewsletterError::ValidationError(message),
+                e => e.into(),
+            }),
+            None => Ok(self.repo.get_default_list().await?),
+        }
+    }
+
+    /// Emails of the confirmed subscribers of a list an issue sent to `segment` is
+    /// delivered to
+    async fn get_recipients(
+        &self,
+        list_id: ListId,
+        segment: &Segment,
+    ) -> Result<Vec<SubscriberEmail>, NewsletterError> {
+        let confirmed_subscribers = self
+            .repo
+            .get_confirmed_subscribers(list_id, segment)
+            .await?;
+
+        let mut recipients = Vec::with_capacity(confirmed_subscribers.len());
+        for subscriber in confirmed_subscribers {
+            match subscriber {
+                Ok(subscriber) => recipients.push(subscriber.email().clone()),
+                Err(error) => {
+                    tracing::warn!(
+                        error.cause_chain = ?error,
+                        "Skipping a confirmed subscriber. Their stored contact details are invalid",
+                    );
+                }
+            }
+        }
+        Ok(recipients)
+    }
+}
+
+fn ensure_in_the_future(scheduled_at: ScheduledAt) -> Result<(), NewsletterError> {
+    if scheduled_at.as_datetime() <= chrono::Utc::now() {
+        return Err(NewsletterError::ValidationError(
+            "The schedule time must be in the future".into(),
+        ));
     }
+    Ok(())
 }
 
 #[async_trait]
@@ -33,36 +112,500 @@ where
     R: NewsletterRepository,
     N: NewsletterNotifier,
 {
-    async fn send_newsletter(
+    async fn get_lists(&self) -> Result<Vec<MailingList>, NewsletterError> {
+        Ok(self.repo.get_lists().await?)
+    }
+
+    async fn publish_newsletter(
         &self,
         newsletter: Newsletter,
+        list_id: Option<ListId>,
+        segment: Segment,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError> {
+        let list = self.resolve_list(list_id).await?;
+        let recipients = self.get_recipients(list.list_id, &segment).await?;
+        self.repo
+            .add_issue_and_enqueue_delivery_tasks(
+                &newsletter,
+                list.list_id,
+                &segment,
+                tracking_enabled,
+                &recipients,
+                response,
+            )
+            .await
+    }
+
+    #[tracing::instrument(skip(self, newsletter, base_url), err)]
+    async fn send_test_issue(
+        &self,
+        newsletter: &Newsletter,
+        recipients: &TestRecipients,
         base_url: &str,
     ) -> Result<(), NewsletterError> {
-        let confirmed_subscribers_with_tokens = self.repo.get_confirmed_subscribers().await?;
-
-        for subscriber_with_token in confirmed_subscribers_with_tokens {
-            match subscriber_with_token {
-                Ok(subscriber_with_token) => {
-                    let subscriber = subscriber_with_token.0;
-                    let token = subscriber_with_token.1;
-                    tracing::Span::current().record(
-                        "subscriber_email",
-                        tracing::field::display(&subscriber.email().as_str()),
-                    );
-                    self.notifier
-                        .send_newsletter(subscriber.email(), &newsletter, token, base_url)
+        for recipient in recipients.as_slice() {
+            let tokens = LinkTokens::placeholder();
+            let values = MergeValues::new(
+                MergeValues::SAMPLE_NAME,
+                recipient.as_str(),
+                &tokens.unsubscribe,
+                base_url,
+            );
+            self.notifier
+                .send_newsletter(
+                    recipient,
+                    &newsletter.personalize(&values)?,
+                    &tokens,
+                    None,
+                    base_url,
+                    self.default_locale,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn schedule_newsletter(
+        &self,
+        newsletter: Newsletter,
+        list_id: Option<ListId>,
+        segment: Segment,
+        scheduled_at: ScheduledAt,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError> {
+        ensure_in_the_future(scheduled_at)?;
+        let list = self.resolve_list(list_id).await?;
+        self.repo
+            .add_scheduled_issue(
+                &newsletter,
+                list.list_id,
+                &segment,
+                scheduled_at,
+                tracking_enabled,
+                response,
+            )
+            .await
+    }
+
+    async fn get_scheduled_issues(&self) -> Result<Vec<ScheduledIssue>, NewsletterError> {
+        self.repo.get_scheduled_issues().await
+    }
+
+    async fn get_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<ScheduledIssue, NewsletterError> {
+        self.repo.get_scheduled_issue(newsletter_issue_id).await
+    }
+
+    async fn reschedule_newsletter(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        newsletter: Newsletter,
+        segment: Segment,
+        scheduled_at: ScheduledAt,
+    ) -> Result<(), NewsletterError> {
+        ensure_in_the_future(scheduled_at)?;
+        self.repo
+            .update_scheduled_issue(newsletter_issue_id, &newsletter, &segment, scheduled_at)
+            .await
+    }
+
+    async fn cancel_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError> {
+        self.repo.delete_scheduled_issue(newsletter_issue_id).await
+    }
+
+    async fn create_draft(&self, req: DraftDto) -> Result<NewsletterIssueId, NewsletterError> {
+        let draft = DraftContent::try_from(req)?;
+        self.repo.add_draft(&draft).await
+    }
+
+    async fn get_drafts(&self) -> Result<Vec<Draft>, NewsletterError> {
+        self.repo.get_drafts().await
+    }
+
+    async fn get_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Draft, NewsletterError> {
+        self.repo.get_draft(newsletter_issue_id).await
+    }
+
+    async fn update_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: DraftDto,
+    ) -> Result<(), NewsletterError> {
+        let draft = DraftContent::try_from(req)?;
+        self.repo.update_draft(newsletter_issue_id, &draft).await
+    }
+
+    async fn delete_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError> {
+        self.repo.delete_draft(newsletter_issue_id).await
+    }
+
+    async fn preview_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        base_url: &str,
+    ) -> Result<NewsletterPreview, NewsletterError> {
+        let draft = self.repo.get_draft(newsletter_issue_id).await?;
+        let newsletter = Newsletter::try_from(&draft.content)?;
+        // The preview is not addressed to anyone, so its footer links point nowhere
+        let tokens = LinkTokens::placeholder();
+        let values = MergeValues::new(MergeValues::SAMPLE_NAME, "", &tokens.unsubscribe, base_url);
+        Ok(self.notifier.render_newsletter(
+            &newsletter.personalize(&values)?,
+            &tokens,
+            None,
+            base_url,
+            self.default_locale,
+        ))
+    }
+
+    async fn get_translations(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueTranslations, NewsletterError> {
+        let title = self.repo.get_issue_title(newsletter_issue_id).await?;
+        let translations = self.repo.get_translations(newsletter_issue_id).await?;
+        Ok(IssueTranslations {
+            newsletter_issue_id,
+            title: title.as_str().to_string(),
+            default_locale: self.default_locale,
+            translations,
+        })
+    }
+
+    #[tracing::instrument(skip(self, req), err)]
+    async fn save_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: TranslationDto,
+    ) -> Result<(), NewsletterError> {
+        self.repo.get_issue_title(newsletter_issue_id).await?;
+        let locale = Locale::parse(&req.locale)
+            .map_err(|e| NewsletterError::ValidationError(e.to_string()))?;
+        if locale == self.default_locale {
+            return Err(NewsletterError::ValidationError(format!(
+                "Issues are already written in {}",
+                locale.name()
+            )));
+        }
+
+        if req.is_blank() {
+            return self
+                .repo
+                .delete_translation(newsletter_issue_id, locale)
+                .await;
+        }
+        let translation = IssueTranslation::try_from(req)?;
+        self.repo
+            .save_translation(newsletter_issue_id, &translation)
+            .await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn publish_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: SegmentDto,
+    ) -> Result<(), NewsletterError> {
+        let list = self.resolve_list(req.list_id).await?;
+        let segment = Segment::try_from(req)?;
+        let draft = self.repo.get_draft(newsletter_issue_id).await?;
+        Newsletter::try_from(&draft.content)?;
+
+        let recipients = self.get_recipients(list.list_id, &segment).await?;
+        if !self
+            .repo
+            .release_issue(newsletter_issue_id, list.list_id, &segment, &recipients)
+            .await?
+        {
+            return Err(NewsletterError::NotFound(format!(
+                "Draft with id {} not found",
+                newsletter_issue_id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn count_recipients(&self, req: SegmentDto) -> Result<usize, NewsletterError> {
+        let list = self.resolve_list(req.list_id).await?;
+        let segment = Segment::try_from(req)?;
+        self.repo
+            .count_confirmed_subscribers(list.list_id, &segment)
+            .await
+    }
+
+    #[tracing::instrument(skip_all, fields(newsletter_issue_id=tracing::field::Empty), err)]
+    async fn try_release_scheduled_issue(&self) -> Result<ExecutionOutcome, NewsletterError> {
+        let (newsletter_issue_id, list_id, segment) =
+            match self.repo.get_due_scheduled_issue().await? {
+                Some(due_issue) => due_issue,
+                None => return Ok(ExecutionOutcome::EmptyQueue),
+            };
+        tracing::Span::current().record(
+            "newsletter_issue_id",
+            tracing::field::display(&newsletter_issue_id),
+        );
+
+        let recipients = self.get_recipients(list_id, &segment).await?;
+        if !self
+            .repo
+            .release_issue(newsletter_issue_id, list_id, &segment, &recipients)
+            .await?
+        {
+            tracing::info!("Scheduled issue was released or cancelled by someone else");
+        }
+        Ok(ExecutionOutcome::TaskCompleted)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(newsletter_issue_id=tracing::field::Empty, subscriber_email=tracing::field::Empty),
+        err
+    )]
+    async fn try_execute_task(&self, base_url: &str) -> Result<ExecutionOutcome, NewsletterError> {
+        let task = match self.repo.dequeue_delivery_task().await? {
+            Some(task) => task,
+            None => return Ok(ExecutionOutcome::EmptyQueue),
+        };
+        tracing::Span::current()
+            .record(
+                "newsletter_issue_id",
+                tracing::field::display(&task.newsletter_issue_id),
+            )
+            .record(
+                "subscriber_email",
+                tracing::field::display(&task.subscriber_email),
+            );
+
+        let issue = self
+            .repo
+            .get_published_issue(task.newsletter_issue_id)
+            .await?;
+        let outcome = match self
+            .repo
+            .get_confirmed_subscriber(issue.list_id, &task.subscriber_email)
+            .await?
+        {
+            Some(subscriber) => {
+                let tokens = self.repo.issue_link_tokens(&subscriber).await?;
+                let values = MergeValues::new(
+                    subscriber.name().as_str(),
+                    subscriber.email().as_str(),
+                    &tokens.unsubscribe,
+                    base_url,
+                );
+                let written =
+                    issue.newsletter_for(subscriber.locale().unwrap_or(self.default_locale));
+                let mut newsletter = written.personalize(&values)?;
+                if issue.tracking_enabled {
+                    // Links are picked from the body as written: once personalized, they
+                    // carry the tokens and details of the recipient
+                    let links = self
+                        .repo
+                        .issue_tracking_links(
+                            task.newsletter_issue_id,
+                            &subscriber,
+                            &TrackingLinks::tracked_urls(written.content.html.as_str()),
+                        )
                         .await?;
+                    newsletter = newsletter.with_tracking(&links, base_url);
                 }
+                match self
+                    .notifier
+                    .send_newsletter(
+                        subscriber.email(),
+                        &newsletter,
+                        &tokens,
+                        Some(&issue.slug),
+                        base_url,
+                        subscriber.locale().unwrap_or(self.default_locale),
+                    )
+                    .await
+                {
+                    Ok(()) => DeliveryOutcome::Sent,
+                    Err(error @ NewsletterError::BlockedRecipient) => {
+                        DeliveryOutcome::Skipped(error.to_string())
+                    }
+                    Err(error) => {
+                        tracing::error!(
+                            error.cause_chain = ?error,
+                            error.message = %error,
+                            "Failed to deliver issue to a confirmed subscriber. Moving it to dead letters.",
+                        );
+                        DeliveryOutcome::Failed(format!("{:#}", error))
+                    }
+                }
+            }
+            None => {
+                tracing::warn!("Skipping a subscriber that is no longer confirmed");
+                DeliveryOutcome::Skipped("Subscriber is no longer confirmed".into())
+            }
+        };
 
+        self.repo.complete_delivery_task(&task, &outcome).await?;
+        Ok(ExecutionOutcome::TaskCompleted)
+    }
+
+    #[tracing::instrument(skip_all, fields(subscriber_email=tracing::field::Empty), err)]
+    async fn try_send_digest(&self, base_url: &str) -> Result<ExecutionOutcome, NewsletterError> {
+        let digest = match self.repo.claim_due_digest().await? {
+            Some(digest) => digest,
+            None => return Ok(ExecutionOutcome::EmptyQueue),
+        };
+        let DueDigest {
+            subscriber,
+            list_id,
+            since,
+            ..
+        } = &digest;
+        tracing::Span::current().record(
+            "subscriber_email",
+            tracing::field::display(subscriber.email()),
+        );
+
+        let published: Vec<_> = self
+            .repo
+            .get_issues_published_since(*list_id, *since)
+            .await?
+            .into_iter()
+            .filter(|issue| issue.segment.matches(subscriber.tags()))
+            .collect();
+        let issue_ids: Vec<_> = published
+            .iter()
+            .map(|issue| issue.newsletter_issue_id)
+            .collect();
+        let outcome = if published.is_empty() {
+            tracing::info!("Nothing was published since the last digest, skipping it");
+            DeliveryOutcome::Skipped("Nothing was published since the last digest".into())
+        } else {
+            // Links are only issued for a digest that is going out
+            let tokens = self.repo.issue_link_tokens(subscriber).await?;
+            let values = MergeValues::new(
+                subscriber.name().as_str(),
+                subscriber.email().as_str(),
+                &tokens.unsubscribe,
+                base_url,
+            );
+            let locale = subscriber.locale().unwrap_or(self.default_locale);
+            let issues = published
+                .iter()
+                .map(|issue| issue.newsletter_for(locale).personalize(&values))
+                .collect::<Result<Vec<_>, _>>()?;
+            match self
+                .notifier
+                .send_digest(subscriber.email(), &issues, &tokens, base_url, locale)
+                .await
+            {
+                Ok(()) => DeliveryOutcome::Sent,
+                Err(error @ NewsletterError::BlockedRecipient) => {
+                    DeliveryOutcome::Skipped(error.to_string())
+                }
                 Err(error) => {
-                    tracing::warn!(
+                    tracing::error!(
                         error.cause_chain = ?error,
-                        "Skipping a confirmed subscriber. Their stored contact details are invalid",
+                        error.message = %error,
+                        "Failed to send a weekly digest. Moving its issues to dead letters.",
                     );
+                    DeliveryOutcome::Failed(format!("{:#}", error))
                 }
             }
+        };
+
+        self.repo
+            .complete_digest(&digest, &issue_ids, &outcome)
+            .await?;
+        Ok(ExecutionOutcome::TaskCompleted)
+    }
+
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, NewsletterError> {
+        self.repo.get_dead_letters().await
+    }
+
+    async fn replay_dead_letter(&self, req: DeadLetterReplayDto) -> Result<(), NewsletterError> {
+        let task = DeliveryTask::try_from(req)?;
+        self.repo.replay_dead_letter(&task).await
+    }
+
+    async fn get_issue_summaries(&self) -> Result<Vec<IssueSummary>, NewsletterError> {
+        self.repo.get_issue_summaries().await
+    }
+
+    async fn get_issue_delivery_report(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueDeliveryReport, NewsletterError> {
+        let summary = self.repo.get_issue_summary(newsletter_issue_id).await?;
+        let undelivered = self
+            .repo
+            .get_undelivered_recipients(newsletter_issue_id)
+            .await?;
+        let tracking = if summary.tracking_enabled {
+            Some(self.repo.get_tracking_stats(newsletter_issue_id).await?)
+        } else {
+            None
+        };
+        Ok(IssueDeliveryReport {
+            summary,
+            undelivered,
+            tracking,
+        })
+    }
+
+    async fn track(&self, tracking_id: TrackingId) -> Result<TrackedTarget, NewsletterError> {
+        self.repo.record_tracking_event(tracking_id).await
+    }
+
+    async fn get_archive(&self) -> Result<Vec<ArchiveEntry>, NewsletterError> {
+        self.repo.get_archive_entries().await
+    }
+
+    async fn get_archived_issue(
+        &self,
+        slug: String,
+        base_url: &str,
+    ) -> Result<ArchivedIssue, NewsletterError> {
+        // A malformed slug cannot match any issue
+        let slug = IssueSlug::parse(slug).map_err(|e| NewsletterError::NotFound(e.to_string()))?;
+        let mut issue = self.repo.get_archived_issue(&slug).await?;
+        issue.html_content = fill_in_public_merge_tags(issue.html_content, base_url);
+        Ok(issue)
+    }
+
+    async fn get_feed(&self, base_url: &str) -> Result<Feed, NewsletterError> {
+        let mut feed = self.repo.get_feed(Feed::MAX_ENTRIES).await?;
+        for entry in &mut feed.entries {
+            entry.html_content =
+                fill_in_public_merge_tags(std::mem::take(&mut entry.html_content), base_url);
         }
+        Ok(feed)
+    }
 
-        Ok(())
+    async fn set_issue_visibility(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        req: IssueVisibilityDto,
+    ) -> Result<(), NewsletterError> {
+        self.repo
+            .set_issue_hidden_from_archive(newsletter_issue_id, req.hidden)
+            .await
     }
 }
+
+/// The web version of an issue is not addressed to anyone: its merge tags are
+/// filled in with generic values.
+fn fill_in_public_merge_tags(html: String, base_url: &str) -> String {
+    MergeTemplate::parse_lenient(&html).render_html(&MergeValues::anonymous(base_url))
+}
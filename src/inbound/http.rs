@@ -1,14 +1,24 @@
 use crate::configuration::ApplicationSettings;
 use crate::domain::auth::ports::AuthService;
+use crate::domain::idempotency::ports::IdempotencyService;
+use crate::domain::new_subscriber::models::locale::Locale;
 use crate::domain::new_subscriber::ports::SubscriptionService;
 use crate::domain::newsletter::ports::NewsletterService;
 use crate::inbound::http::handlers::{
-    admin::change_password, admin::change_password_form, admin_dashboard, confirm, health_check,
-    home, log_out, login, login_form, publish_newsletter, publish_newsletter_form, subscribe,
-    unsubscribe,
+    add_suppression, admin::change_password, admin::change_password_form, admin_dashboard,
+    apply_retention, archive, archived_issue, atom_feed, block_address, blocklist_page,
+    cancel_scheduled_issue, confirm, create_draft, create_list, dead_letters_page, delete_draft,
+    draft_form, drafts_page, health_check, home, issue_page, issues_page, lists_page, log_out,
+    login, login_form, one_click_unsubscribe, postmark_webhook, preferences_page, preview_draft,
+    publish_draft, publish_newsletter, publish_newsletter_form, recipient_count,
+    recipient_count_script, reinstate_address, replay_dead_letter, reschedule_issue, rss_feed,
+    save_translation, scheduled_issue_form, scheduled_issues_page, send_test_issue,
+    set_issue_visibility, set_subscriber_tags, subscribe, subscribers_page, suppressions_page,
+    track, translations_form, unblock_address, unsubscribe, update_draft, update_preferences,
 };
+use crate::inbound::http::locale::DefaultLocale;
 use crate::inbound::http::state::{
-    SharedAuthState, SharedNewsletterState, SharedSubscriptionState,
+    SharedAuthState, SharedIdempotencyState, SharedNewsletterState, SharedSubscriptionState,
 };
 use actix_session::storage::RedisSessionStore;
 use actix_session::SessionMiddleware;
@@ -27,33 +37,47 @@ use secrecy::{ExposeSecret, Secret};
 mod auth;
 mod errors;
 mod handlers;
+mod idempotency;
+mod locale;
 pub mod state;
 mod utils;
 
-pub struct Application<SS, NS, AS>
+pub struct Application<SS, NS, AS, IS>
 where
     SS: SubscriptionService,
     NS: NewsletterService,
     AS: AuthService,
+    IS: IdempotencyService,
 {
     port: u16,
     server: Server,
     subscription_state: SharedSubscriptionState<SS>,
     newsletter_state: SharedNewsletterState<NS>,
     auth_state: SharedAuthState<AS>,
+    idempotency_state: SharedIdempotencyState<IS>,
 }
 
-async fn run<SS: SubscriptionService, NS: NewsletterService, AS: AuthService>(
+#[allow(clippy::too_many_arguments)]
+async fn run<
+    SS: SubscriptionService,
+    NS: NewsletterService,
+    AS: AuthService,
+    IS: IdempotencyService,
+>(
     listener: TcpListener,
     hmac_secret: Secret<String>,
     redis_uri: Secret<String>,
+    default_locale: Locale,
     subscription_state: SharedSubscriptionState<SS>,
     newsletter_state: SharedNewsletterState<NS>,
     auth_state: SharedAuthState<AS>,
+    idempotency_state: SharedIdempotencyState<IS>,
 ) -> Result<Server, anyhow::Error> {
     let subscription_state = web::Data::new(subscription_state);
     let newsletter_state = web::Data::new(newsletter_state);
     let auth_state = web::Data::new(auth_state);
+    let idempotency_state = web::Data::new(idempotency_state);
+    let default_locale = web::Data::new(DefaultLocale(default_locale));
 
     let secret_key = Key::from(hmac_secret.expose_secret().as_bytes());
     let message_store = CookieMessageStore::builder(secret_key.clone()).build();
@@ -68,10 +92,12 @@ async fn run<SS: SubscriptionService, NS: NewsletterService, AS: AuthService>(
                 redis_store.clone(),
                 secret_key.clone(),
             ))
+            .app_data(default_locale.clone())
             .route("/", web::get().to(home))
             .route("/health_check", web::get().to(health_check))
             .app_data(auth_state.clone())
             .app_data(newsletter_state.clone())
+            .app_data(idempotency_state.clone())
             .route("/login", web::get().to(login_form))
             .route("/login", web::post().to(login::<AS>))
             .app_data(subscription_state.clone())
@@ -81,6 +107,24 @@ async fn run<SS: SubscriptionService, NS: NewsletterService, AS: AuthService>(
                 "/subscriptions/unsubscribe",
                 web::get().to(unsubscribe::<SS>),
             )
+            .route(
+                "/subscriptions/unsubscribe",
+                web::post().to(one_click_unsubscribe::<SS>),
+            )
+            .route(
+                "/subscriptions/preferences",
+                web::get().to(preferences_page::<SS>),
+            )
+            .route(
+                "/subscriptions/preferences",
+                web::post().to(update_preferences::<SS>),
+            )
+            .route("/webhooks/postmark", web::post().to(postmark_webhook::<SS>))
+            .route("/newsletters", web::get().to(archive::<NS>))
+            .route("/newsletters/{slug}", web::get().to(archived_issue::<NS>))
+            .route("/feed.rss", web::get().to(rss_feed::<NS>))
+            .route("/feed.atom", web::get().to(atom_feed::<NS>))
+            .route("/t/{tracking_id}", web::get().to(track::<NS>))
             .service(
                 web::scope("/admin")
                     .wrap(from_fn(reject_anonymous_users))
@@ -88,8 +132,103 @@ async fn run<SS: SubscriptionService, NS: NewsletterService, AS: AuthService>(
                     .route("/dashboard", web::get().to(admin_dashboard::<AS>))
                     .route("/password", web::get().to(change_password_form))
                     .route("/password", web::post().to(change_password::<AS>))
-                    .route("/newsletters", web::post().to(publish_newsletter::<NS>))
-                    .route("/newsletters", web::get().to(publish_newsletter_form))
+                    .route("/newsletters", web::post().to(publish_newsletter::<NS, IS>))
+                    .route("/newsletters", web::get().to(publish_newsletter_form::<NS>))
+                    .route("/newsletters/test", web::post().to(send_test_issue::<NS>))
+                    .route(
+                        "/newsletters/recipients",
+                        web::get().to(recipient_count::<NS>),
+                    )
+                    .route(
+                        "/newsletters/recipients.js",
+                        web::get().to(recipient_count_script),
+                    )
+                    .route(
+                        "/newsletters/dead_letters",
+                        web::get().to(dead_letters_page::<NS>),
+                    )
+                    .route(
+                        "/newsletters/dead_letters/replay",
+                        web::post().to(replay_dead_letter::<NS>),
+                    )
+                    .route("/newsletters/drafts", web::get().to(drafts_page::<NS>))
+                    .route("/newsletters/drafts", web::post().to(create_draft::<NS>))
+                    .route(
+                        "/newsletters/drafts/{newsletter_issue_id}",
+                        web::get().to(draft_form::<NS>),
+                    )
+                    .route(
+                        "/newsletters/drafts/{newsletter_issue_id}",
+                        web::post().to(update_draft::<NS>),
+                    )
+                    .route(
+                        "/newsletters/drafts/{newsletter_issue_id}/delete",
+                        web::post().to(delete_draft::<NS>),
+                    )
+                    .route(
+                        "/newsletters/drafts/{newsletter_issue_id}/preview",
+                        web::get().to(preview_draft::<NS>),
+                    )
+                    .route(
+                        "/newsletters/drafts/{newsletter_issue_id}/publish",
+                        web::post().to(publish_draft::<NS>),
+                    )
+                    .route("/newsletters/issues", web::get().to(issues_page::<NS>))
+                    .route(
+                        "/newsletters/issues/{newsletter_issue_id}",
+                        web::get().to(issue_page::<NS>),
+                    )
+                    .route(
+                        "/newsletters/issues/{newsletter_issue_id}/visibility",
+                        web::post().to(set_issue_visibility::<NS>),
+                    )
+                    .route(
+                        "/newsletters/scheduled",
+                        web::get().to(scheduled_issues_page::<NS>),
+                    )
+                    .route(
+                        "/newsletters/scheduled/{newsletter_issue_id}",
+                        web::get().to(scheduled_issue_form::<NS>),
+                    )
+                    .route(
+                        "/newsletters/scheduled/{newsletter_issue_id}",
+                        web::post().to(reschedule_issue::<NS>),
+                    )
+                    .route(
+                        "/newsletters/scheduled/{newsletter_issue_id}/cancel",
+                        web::post().to(cancel_scheduled_issue::<NS>),
+                    )
+                    .route(
+                        "/newsletters/translations/{newsletter_issue_id}",
+                        web::get().to(translations_form::<NS>),
+                    )
+                    .route(
+                        "/newsletters/translations/{newsletter_issue_id}",
+                        web::post().to(save_translation::<NS>),
+                    )
+                    .route("/lists", web::get().to(lists_page::<SS>))
+                    .route("/lists", web::post().to(create_list::<SS>))
+                    .route("/subscribers", web::get().to(subscribers_page::<SS>))
+                    .route(
+                        "/subscribers/retention",
+                        web::post().to(apply_retention::<SS>),
+                    )
+                    .route(
+                        "/subscribers/{subscriber_id}/tags",
+                        web::post().to(set_subscriber_tags::<SS>),
+                    )
+                    .route("/blocklist", web::get().to(blocklist_page::<SS>))
+                    .route("/blocklist", web::post().to(block_address::<SS>))
+                    .route(
+                        "/blocklist/{blocked_id}/delete",
+                        web::post().to(unblock_address::<SS>),
+                    )
+                    .route("/suppressions", web::get().to(suppressions_page::<SS>))
+                    .route("/suppressions", web::post().to(add_suppression::<SS>))
+                    .route(
+                        "/suppressions/reinstate",
+                        web::post().to(reinstate_address::<SS>),
+                    )
                     .route("/logout", web::post().to(log_out)),
             )
     })
@@ -99,17 +238,20 @@ async fn run<SS: SubscriptionService, NS: NewsletterService, AS: AuthService>(
     Ok(server)
 }
 
-impl<SS, NS, AS> Application<SS, NS, AS>
+impl<SS, NS, AS, IS> Application<SS, NS, AS, IS>
 where
     SS: SubscriptionService,
     NS: NewsletterService,
     AS: AuthService,
+    IS: IdempotencyService,
 {
     pub async fn build(
         subscription_service: SS,
         newsletter_service: NS,
         auth_service: AS,
+        idempotency_service: IS,
         configuration: ApplicationSettings,
+        default_locale: Locale,
     ) -> Result<Self, anyhow::Error> {
         let address = format!("{}:{}", configuration.host, configuration.port);
         let listener = TcpListener::bind(address)?;
@@ -117,16 +259,20 @@ where
 
         let newsletter_state =
             SharedNewsletterState::new(newsletter_service, configuration.base_url);
-        let subscription_state = SharedSubscriptionState::new(subscription_service);
+        let subscription_state =
+            SharedSubscriptionState::new(subscription_service, configuration.webhook_secret);
         let auth_state = SharedAuthState::new(auth_service);
+        let idempotency_state = SharedIdempotencyState::new(idempotency_service);
 
         let server: Server = run(
             listener,
             configuration.hmac_secret,
             configuration.redis_uri,
+            default_locale,
             subscription_state.clone(),
             newsletter_state.clone(),
             auth_state.clone(),
+            idempotency_state.clone(),
         )
         .await?;
 
@@ -136,6 +282,7 @@ where
             subscription_state: subscription_state.clone(),
             newsletter_state: newsletter_state.clone(),
             auth_state: auth_state.clone(),
+            idempotency_state: idempotency_state.clone(),
         })
     }
 
@@ -155,6 +302,10 @@ where
         self.auth_state.clone()
     }
 
+    pub fn idempotency_state(&self) -> SharedIdempotencyState<IS> {
+        self.idempotency_state.clone()
+    }
+
     pub async fn run_until_stopped(self) -> Result<(), std::io::Error> {
         self.server.await
     }
@@ -1,4 +1,5 @@
 use crate::domain::auth::credentials::CredentialsError;
+use crate::domain::idempotency::errors::IdempotencyError;
 use crate::domain::new_subscriber::errors::SubscriberError;
 use crate::domain::newsletter::errors::NewsletterError;
 
@@ -14,6 +15,8 @@ pub enum AppError {
     NotFound(String),
     #[error("Subscriber not authenticated: {0}")]
     AuthError(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -36,6 +39,8 @@ impl From<NewsletterError> for AppError {
             NewsletterError::NotFound(s) => AppError::NotFound(s),
             NewsletterError::Unexpected(s) => AppError::Unexpected(s),
             NewsletterError::AuthError(s) => AppError::AuthError(s),
+            NewsletterError::Conflict(s) => AppError::Conflict(s),
+            e @ NewsletterError::BlockedRecipient => AppError::ValidationError(e.to_string()),
         }
     }
 }
@@ -48,6 +53,16 @@ impl From<CredentialsError> for AppError {
     }
 }
 
+impl From<IdempotencyError> for AppError {
+    fn from(error: IdempotencyError) -> Self {
+        match error {
+            IdempotencyError::ValidationError(s) => AppError::ValidationError(s),
+            IdempotencyError::Conflict(s) => AppError::Conflict(s),
+            IdempotencyError::Unexpected(s) => AppError::Unexpected(s),
+        }
+    }
+}
+
 impl ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match self {
@@ -55,22 +70,32 @@ impl ResponseError for AppError {
             AppError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
         }
     }
 
     fn error_response(&self) -> actix_web::HttpResponse<actix_web::body::BoxBody> {
+        let mut response = HttpResponse::build(self.status_code())
+            .json(serde_json::json!({ "error": self.public_message() }));
+        if let AppError::AuthError(_) = self {
+            let header_value = HeaderValue::from_str(r#"Basic realm="publish""#).unwrap();
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, header_value);
+        }
+        response
+    }
+}
+
+impl AppError {
+    /// Message that can be shown to the client: unexpected errors are not detailed
+    pub fn public_message(&self) -> String {
         match self {
-            AppError::ValidationError(_) => HttpResponse::new(StatusCode::BAD_REQUEST),
-            AppError::Unexpected(_) => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
-            AppError::NotFound(_) => HttpResponse::new(StatusCode::NOT_FOUND),
-            AppError::AuthError(_) => {
-                let mut response = HttpResponse::new(StatusCode::UNAUTHORIZED);
-                let header_value = HeaderValue::from_str(r#"Basic realm="publish""#).unwrap();
-                response
-                    .headers_mut()
-                    .insert(header::WWW_AUTHENTICATE, header_value);
-                response
-            }
+            AppError::ValidationError(message)
+            | AppError::NotFound(message)
+            | AppError::AuthError(message)
+            | AppError::Conflict(message) => message.clone(),
+            AppError::Unexpected(_) => "Something went wrong, please try again later".to_string(),
         }
     }
 }
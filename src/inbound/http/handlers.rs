@@ -1,15 +1,26 @@
 pub mod admin;
+pub mod archive;
 pub mod confirm;
+pub mod feed;
 pub mod health_check;
 pub mod home;
 pub mod login;
+pub mod preferences;
 pub mod subscribe;
+mod subscription_outcome;
+pub mod tracking;
 pub mod unsubscribe;
+pub mod webhooks;
 
 pub use admin::*;
+pub use archive::{archive, archived_issue};
 pub use confirm::confirm;
+pub use feed::{atom_feed, rss_feed};
 pub use health_check::health_check;
 pub use home::*;
 pub use login::*;
+pub use preferences::{preferences_page, update_preferences};
 pub use subscribe::subscribe;
-pub use unsubscribe::unsubscribe;
+pub use tracking::track;
+pub use unsubscribe::{one_click_unsubscribe, unsubscribe};
+pub use webhooks::postmark_webhook;
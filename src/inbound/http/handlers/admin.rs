@@ -1,9 +1,17 @@
+mod blocklist;
 mod dashboard;
+mod lists;
 mod logout;
 mod newsletter;
 mod password;
+mod subscribers;
+mod suppressions;
 
+pub use blocklist::{block_address, blocklist_page, unblock_address};
 pub use dashboard::admin_dashboard;
+pub use lists::{create_list, lists_page};
 pub use logout::log_out;
 pub use newsletter::*;
 pub use password::*;
+pub use subscribers::{apply_retention, set_subscriber_tags, subscribers_page};
+pub use suppressions::{add_suppression, reinstate_address, suppressions_page};
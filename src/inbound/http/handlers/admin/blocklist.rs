@@ -0,0 +1,80 @@
+use crate::domain::new_subscriber::{
+    models::blocklist::{BlockRequest, BlockedEntry},
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "Show the blocklist", skip(flash_message, state))]
+pub async fn blocklist_page<SS: SubscriptionService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let entries = state.subscription_service().get_blocklist().await?;
+
+    let html_content = utils::load_html(HtmlTemplate::Blocklist);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{entries}", &entries_to_html(&entries));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Block an address or a domain", skip(body, state))]
+pub async fn block_address<SS: SubscriptionService>(
+    body: web::Form<BlockRequest>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let pattern = state
+        .subscription_service()
+        .block(body.into_inner())
+        .await?;
+
+    FlashMessage::info(format!("{} is blocked.", pattern)).send();
+    Ok(see_other("/admin/blocklist"))
+}
+
+#[tracing::instrument(name = "Remove a blocklist entry", skip(state))]
+pub async fn unblock_address<SS: SubscriptionService>(
+    blocked_id: web::Path<uuid::Uuid>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .subscription_service()
+        .unblock(blocked_id.into_inner())
+        .await?;
+
+    FlashMessage::info("The entry has been removed from the blocklist.").send();
+    Ok(see_other("/admin/blocklist"))
+}
+
+fn entries_to_html(entries: &[BlockedEntry]) -> String {
+    let mut rows = String::new();
+    for entry in entries {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td>{pattern}</td>
+            <td>{kind}</td>
+            <td>{reason}</td>
+            <td>{created_at}</td>
+            <td>
+                <form action="/admin/blocklist/{blocked_id}/delete" method="post">
+                    <button type="submit">Remove</button>
+                </form>
+            </td>
+        </tr>"#,
+            pattern = htmlescape::encode_minimal(&entry.pattern.to_string()),
+            kind = entry.pattern.kind(),
+            reason = htmlescape::encode_minimal(&entry.reason),
+            created_at = entry.created_at.format("%Y-%m-%d %H:%M UTC"),
+            blocked_id = entry.blocked_id,
+        )
+        .unwrap();
+    }
+    rows
+}
@@ -0,0 +1,59 @@
+use crate::domain::new_subscriber::{
+    models::list::{MailingList, NewListRequest},
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "List mailing lists", skip(flash_message, state))]
+pub async fn lists_page<SS: SubscriptionService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let lists = state.subscription_service().get_lists().await?;
+
+    let html_content = utils::load_html(HtmlTemplate::Lists);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{lists}", &lists_to_html(&lists));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Create a mailing list", skip(body, state))]
+pub async fn create_list<SS: SubscriptionService>(
+    body: web::Form<NewListRequest>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let list = state
+        .subscription_service()
+        .create_list(body.into_inner())
+        .await?;
+
+    FlashMessage::info(format!("The list {} has been created.", list.name)).send();
+    Ok(see_other("/admin/lists"))
+}
+
+fn lists_to_html(lists: &[MailingList]) -> String {
+    let mut rows = String::new();
+    for list in lists {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td><a href="/admin/subscribers?list_id={list_id}">{name}</a></td>
+            <td>{slug}</td>
+            <td>{default}</td>
+        </tr>"#,
+            list_id = list.list_id,
+            name = htmlescape::encode_minimal(list.name.as_str()),
+            slug = list.slug.as_str(),
+            default = if list.is_default { "Yes" } else { "" },
+        )
+        .unwrap();
+    }
+    rows
+}
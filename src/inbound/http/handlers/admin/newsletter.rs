@@ -1,5 +1,23 @@
+pub mod dead_letters;
+pub mod drafts;
 pub mod get;
+pub mod issues;
 pub mod post;
+pub mod recipients;
+pub mod scheduled;
+pub mod test_issue;
+pub mod translations;
 
+pub use dead_letters::{dead_letters_page, replay_dead_letter};
+pub use drafts::{
+    create_draft, delete_draft, draft_form, drafts_page, preview_draft, publish_draft, update_draft,
+};
 pub use get::publish_newsletter_form;
+pub use issues::{issue_page, issues_page, set_issue_visibility};
 pub use post::publish_newsletter;
+pub use recipients::{recipient_count, recipient_count_script};
+pub use scheduled::{
+    cancel_scheduled_issue, reschedule_issue, scheduled_issue_form, scheduled_issues_page,
+};
+pub use test_issue::send_test_issue;
+pub use translations::{save_translation, translations_form};
@@ -0,0 +1,74 @@
+use crate::domain::newsletter::{
+    models::issue_delivery::{DeadLetter, DeadLetterReplayDto},
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, e500, see_other, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "List failed deliveries", skip(flash_message, state))]
+pub async fn dead_letters_page<NS: NewsletterService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let dead_letters = state
+        .newsletter_service()
+        .get_dead_letters()
+        .await
+        .map_err(e500)?;
+
+    let html_content = utils::load_html(HtmlTemplate::DeadLetters);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{dead_letters}", &dead_letters_to_html(&dead_letters));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Replay a failed delivery", skip(state))]
+pub async fn replay_dead_letter<NS: NewsletterService>(
+    req: web::Form<DeadLetterReplayDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .newsletter_service()
+        .replay_dead_letter(req.into_inner())
+        .await?;
+
+    FlashMessage::info("The delivery has been queued again.").send();
+    Ok(see_other("/admin/newsletters/dead_letters"))
+}
+
+fn dead_letters_to_html(dead_letters: &[DeadLetter]) -> String {
+    let mut rows = String::new();
+    for dead_letter in dead_letters {
+        let subscriber_email = dead_letter.subscriber_email.as_str();
+        writeln!(
+            rows,
+            r#"<tr>
+            <td>{title}</td>
+            <td>{recipient}</td>
+            <td>{last_error}</td>
+            <td>{failed_at}</td>
+            <td>
+                <form action="/admin/newsletters/dead_letters/replay" method="post">
+                    <input hidden type="text" name="newsletter_issue_id" value="{newsletter_issue_id}">
+                    <input hidden type="text" name="subscriber_email" value="{email_attribute}">
+                    <button type="submit">Replay</button>
+                </form>
+            </td>
+        </tr>"#,
+            title = htmlescape::encode_minimal(&dead_letter.newsletter_title),
+            recipient = htmlescape::encode_minimal(subscriber_email),
+            email_attribute = htmlescape::encode_attribute(subscriber_email),
+            last_error = htmlescape::encode_minimal(&dead_letter.last_error),
+            failed_at = dead_letter.failed_at.to_rfc3339(),
+            newsletter_issue_id = dead_letter.newsletter_issue_id,
+        )
+        .unwrap();
+    }
+    rows
+}
@@ -0,0 +1,189 @@
+use crate::domain::newsletter::{
+    models::{
+        draft::{Draft, DraftDto},
+        issue_delivery::NewsletterIssueId,
+        segment::SegmentDto,
+    },
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, e500, see_other, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+use super::get::list_options_to_html;
+
+#[tracing::instrument(name = "List drafts", skip(flash_message, state))]
+pub async fn drafts_page<NS: NewsletterService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let drafts = state
+        .newsletter_service()
+        .get_drafts()
+        .await
+        .map_err(e500)?;
+
+    let html_content = utils::load_html(HtmlTemplate::Drafts);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{drafts}", &drafts_to_html(&drafts));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Create a draft", skip(body, state))]
+pub async fn create_draft<NS: NewsletterService>(
+    body: web::Form<DraftDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let newsletter_issue_id = state
+        .newsletter_service()
+        .create_draft(body.into_inner())
+        .await?;
+
+    FlashMessage::info("The draft has been saved.").send();
+    Ok(see_other(&format!(
+        "/admin/newsletters/drafts/{}",
+        newsletter_issue_id
+    )))
+}
+
+#[tracing::instrument(name = "Edit draft form", skip(flash_message, state))]
+pub async fn draft_form<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let draft = state
+        .newsletter_service()
+        .get_draft(newsletter_issue_id.into_inner())
+        .await?;
+    let lists = state.newsletter_service().get_lists().await?;
+
+    // Every value written by the editor is escaped down to its braces, so that
+    // a placeholder it contains is not substituted by a later `replace`
+    let html_content = utils::load_html(HtmlTemplate::Draft);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace(
+            "{newsletter_issue_id}",
+            &draft.newsletter_issue_id.to_string(),
+        )
+        .replace(
+            "{title}",
+            &htmlescape::encode_attribute(draft.content.title.as_str()),
+        )
+        .replace(
+            "{text_content}",
+            &htmlescape::encode_attribute(&draft.content.text),
+        )
+        .replace(
+            "{html_content}",
+            &htmlescape::encode_attribute(&draft.content.html),
+        )
+        .replace(
+            "{markdown_content}",
+            &htmlescape::encode_attribute(draft.content.markdown.as_deref().unwrap_or_default()),
+        )
+        .replace(
+            "{tracking_checked}",
+            if draft.content.tracking_enabled {
+                " checked"
+            } else {
+                ""
+            },
+        )
+        .replace("{list_options}", &list_options_to_html(&lists, None));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Update a draft", skip(body, state))]
+pub async fn update_draft<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    body: web::Form<DraftDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let newsletter_issue_id = newsletter_issue_id.into_inner();
+    state
+        .newsletter_service()
+        .update_draft(newsletter_issue_id, body.into_inner())
+        .await?;
+
+    FlashMessage::info("The draft has been saved.").send();
+    Ok(see_other(&format!(
+        "/admin/newsletters/drafts/{}",
+        newsletter_issue_id
+    )))
+}
+
+#[tracing::instrument(name = "Delete a draft", skip(state))]
+pub async fn delete_draft<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .newsletter_service()
+        .delete_draft(newsletter_issue_id.into_inner())
+        .await?;
+
+    FlashMessage::info("The draft has been deleted.").send();
+    Ok(see_other("/admin/newsletters/drafts"))
+}
+
+#[tracing::instrument(name = "Preview a draft", skip(state))]
+pub async fn preview_draft<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let newsletter_issue_id = newsletter_issue_id.into_inner();
+    let preview = state
+        .newsletter_service()
+        .preview_draft(newsletter_issue_id, state.url())
+        .await?;
+
+    let html_content = utils::load_html(HtmlTemplate::DraftPreview);
+    let page_content = html_content
+        .replace("{newsletter_issue_id}", &newsletter_issue_id.to_string())
+        .replace("{subject}", &htmlescape::encode_minimal(&preview.subject))
+        .replace("{html_body}", &htmlescape::encode_attribute(&preview.html))
+        .replace("{text_body}", &htmlescape::encode_minimal(&preview.text));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Publish a draft", skip(body, state))]
+pub async fn publish_draft<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    body: web::Form<SegmentDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .newsletter_service()
+        .publish_draft(newsletter_issue_id.into_inner(), body.into_inner())
+        .await?;
+
+    FlashMessage::info("The newsletter issue has been accepted - emails will go out shortly.")
+        .send();
+    Ok(see_other("/admin/newsletters/drafts"))
+}
+
+fn drafts_to_html(drafts: &[Draft]) -> String {
+    let mut rows = String::new();
+    for draft in drafts {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td><a href="/admin/newsletters/drafts/{newsletter_issue_id}">{title}</a></td>
+            <td>{updated_at}</td>
+        </tr>"#,
+            newsletter_issue_id = draft.newsletter_issue_id,
+            title = htmlescape::encode_minimal(draft.content.title.as_str()),
+            updated_at = draft.updated_at.format("%Y-%m-%d %H:%M"),
+        )
+        .unwrap();
+    }
+    rows
+}
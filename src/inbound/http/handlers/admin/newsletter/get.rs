@@ -1,13 +1,88 @@
-use crate::inbound::http::utils::{self, build_ok_html_response, HtmlTemplate};
-use actix_web::HttpResponse;
+use crate::domain::new_subscriber::models::list::{ListId, MailingList};
+use crate::domain::newsletter::ports::NewsletterService;
+use crate::inbound::http::utils::{self, build_ok_html_response, e500, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
 use actix_web_flash_messages::IncomingFlashMessages;
+use std::fmt::Write;
 
-pub async fn publish_newsletter_form(
+/// Values the newsletter form is filled with when it is rendered again
+#[derive(Default)]
+pub struct NewsletterFormValues<'a> {
+    pub title: &'a str,
+    pub text_content: &'a str,
+    pub html_content: &'a str,
+    pub markdown_content: &'a str,
+    pub test_recipients: &'a str,
+    pub segment: &'a str,
+    pub lists: &'a [MailingList],
+    pub list_id: Option<ListId>,
+}
+
+pub async fn publish_newsletter_form<NS: NewsletterService>(
     flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let msg_html = utils::flash_message_to_html(flash_message);
+    let lists = state.newsletter_service().get_lists().await.map_err(e500)?;
+    let page_content = render_newsletter_form(
+        &msg_html,
+        &NewsletterFormValues {
+            lists: &lists,
+            ..Default::default()
+        },
+    );
+    Ok(build_ok_html_response(page_content))
+}
 
+/// Options of the list picker, the default list being selected unless another one is
+pub fn list_options_to_html(lists: &[MailingList], selected: Option<ListId>) -> String {
+    let mut html = String::new();
+    for list in lists {
+        let is_selected = match selected {
+            Some(list_id) => list.list_id == list_id,
+            None => list.is_default,
+        };
+        let _ = write!(
+            html,
+            r#"<option value="{}"{}>{}</option>"#,
+            list.list_id,
+            if is_selected { " selected" } else { "" },
+            htmlescape::encode_minimal(list.name.as_str()),
+        );
+    }
+    html
+}
+
+pub fn render_newsletter_form(msg_html: &str, values: &NewsletterFormValues) -> String {
+    let idempotency_key = uuid::Uuid::new_v4().to_string();
+
+    // Every value written by the editor is escaped down to its braces, so that
+    // a placeholder it contains is not substituted by a later `replace`
     let html_content = utils::load_html(HtmlTemplate::Newsletter);
-    let page_content = html_content.replace("{msg_html}", &msg_html);
-    Ok(build_ok_html_response(page_content))
+    html_content
+        .replace("{msg_html}", msg_html)
+        .replace("{title}", &htmlescape::encode_attribute(values.title))
+        .replace(
+            "{text_content}",
+            &htmlescape::encode_attribute(values.text_content),
+        )
+        .replace(
+            "{html_content}",
+            &htmlescape::encode_attribute(values.html_content),
+        )
+        .replace(
+            "{markdown_content}",
+            &htmlescape::encode_attribute(values.markdown_content),
+        )
+        .replace(
+            "{test_recipients}",
+            &htmlescape::encode_attribute(values.test_recipients),
+        )
+        .replace("{segment}", &htmlescape::encode_attribute(values.segment))
+        .replace(
+            "{list_options}",
+            &list_options_to_html(values.lists, values.list_id),
+        )
+        .replace("{idempotency_key}", &idempotency_key)
 }
@@ -0,0 +1,217 @@
+use crate::domain::newsletter::{
+    models::{
+        archive::IssueVisibilityDto,
+        issue_delivery::{DeliveryLogEntry, IssueSummary, NewsletterIssueId},
+        segment::Segment,
+        tracking::TrackingStats,
+    },
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, e500, see_other, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "List published issues", skip(state))]
+pub async fn issues_page<NS: NewsletterService>(
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let issues = state
+        .newsletter_service()
+        .get_issue_summaries()
+        .await
+        .map_err(e500)?;
+
+    let html_content = utils::load_html(HtmlTemplate::Issues);
+    let page_content = html_content.replace("{issues}", &issues_to_html(&issues));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Show issue delivery report", skip(flash_message, state))]
+pub async fn issue_page<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let report = state
+        .newsletter_service()
+        .get_issue_delivery_report(newsletter_issue_id.into_inner())
+        .await?;
+
+    let summary = &report.summary;
+    let (archive_status, visibility_action) = if summary.hidden_from_archive {
+        (
+            "Hidden from the public archive.".to_string(),
+            "Show in archive",
+        )
+    } else {
+        (
+            format!(
+                r#"Visible in the public archive at <a href="/newsletters/{slug}">/newsletters/{slug}</a>."#,
+                slug = summary.slug
+            ),
+            "Hide from archive",
+        )
+    };
+    let html_content = utils::load_html(HtmlTemplate::Issue);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace(
+            "{newsletter_issue_id}",
+            &summary.newsletter_issue_id.to_string(),
+        )
+        .replace("{archive_status}", &archive_status)
+        .replace("{hidden}", &(!summary.hidden_from_archive).to_string())
+        .replace("{visibility_action}", visibility_action)
+        .replace("{title}", &htmlescape::encode_minimal(&summary.title))
+        .replace("{published_at}", &summary.published_at.to_rfc3339())
+        .replace(
+            "{segment}",
+            &segment_to_html(&summary.list_name, &summary.segment),
+        )
+        .replace("{total}", &summary.totals.total().to_string())
+        .replace("{sent}", &summary.totals.sent.to_string())
+        .replace("{queued}", &summary.totals.queued.to_string())
+        .replace("{failed}", &summary.totals.failed.to_string())
+        .replace("{bounced}", &summary.totals.bounced.to_string())
+        .replace("{skipped}", &summary.totals.skipped.to_string())
+        .replace("{undelivered}", &undelivered_to_html(&report.undelivered))
+        .replace("{tracking}", &tracking_to_html(report.tracking.as_ref()));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Change issue visibility in the archive", skip(state))]
+pub async fn set_issue_visibility<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    body: web::Form<IssueVisibilityDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let newsletter_issue_id = newsletter_issue_id.into_inner();
+    let body = body.into_inner();
+    let message = if body.hidden {
+        "The issue is now hidden from the archive."
+    } else {
+        "The issue is now visible in the archive."
+    };
+    state
+        .newsletter_service()
+        .set_issue_visibility(newsletter_issue_id, body)
+        .await?;
+
+    FlashMessage::info(message).send();
+    Ok(see_other(&format!(
+        "/admin/newsletters/issues/{}",
+        newsletter_issue_id
+    )))
+}
+
+fn segment_to_html(list_name: &str, segment: &Segment) -> String {
+    let list_name = htmlescape::encode_minimal(list_name);
+    if segment.is_everyone() {
+        format!("Sent to every confirmed subscriber of {}", list_name)
+    } else {
+        format!(
+            "Sent to the segment <code>{}</code> of {}",
+            htmlescape::encode_minimal(&segment.to_string()),
+            list_name
+        )
+    }
+}
+
+fn issues_to_html(issues: &[IssueSummary]) -> String {
+    let mut rows = String::new();
+    for issue in issues {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td><a href="/admin/newsletters/issues/{newsletter_issue_id}">{title}</a></td>
+            <td>{published_at}</td>
+            <td>{total}</td>
+            <td>{sent}</td>
+            <td>{queued}</td>
+            <td>{failed}</td>
+            <td>{bounced}</td>
+            <td>{skipped}</td>
+        </tr>"#,
+            newsletter_issue_id = issue.newsletter_issue_id,
+            title = htmlescape::encode_minimal(&issue.title),
+            published_at = issue.published_at.to_rfc3339(),
+            total = issue.totals.total(),
+            sent = issue.totals.sent,
+            queued = issue.totals.queued,
+            failed = issue.totals.failed,
+            bounced = issue.totals.bounced,
+            skipped = issue.totals.skipped,
+        )
+        .unwrap();
+    }
+    rows
+}
+
+fn tracking_to_html(stats: Option<&TrackingStats>) -> String {
+    let Some(stats) = stats else {
+        return "<p>Opens and clicks are not tracked for this issue.</p>".to_string();
+    };
+    let mut html = format!(
+        r#"<ul>
+        <li>Opens: {opens} ({unique_opens} unique)</li>
+        <li>Clicks: {clicks} ({unique_clicks} unique)</li>
+    </ul>
+    <table>
+        <tr>
+            <th>Link</th>
+            <th>Clicks</th>
+            <th>Unique clicks</th>
+        </tr>
+"#,
+        opens = stats.opens,
+        unique_opens = stats.unique_opens,
+        clicks = stats.clicks,
+        unique_clicks = stats.unique_clicks,
+    );
+    for link in &stats.links {
+        writeln!(
+            html,
+            r#"<tr>
+            <td>{url}</td>
+            <td>{clicks}</td>
+            <td>{unique_clicks}</td>
+        </tr>"#,
+            url = htmlescape::encode_minimal(&link.url),
+            clicks = link.clicks,
+            unique_clicks = link.unique_clicks,
+        )
+        .unwrap();
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn undelivered_to_html(entries: &[DeliveryLogEntry]) -> String {
+    let mut rows = String::new();
+    for entry in entries {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td>{recipient}</td>
+            <td>{status}</td>
+            <td>{attempts}</td>
+            <td>{last_error}</td>
+            <td>{last_attempted_at}</td>
+        </tr>"#,
+            recipient = htmlescape::encode_minimal(entry.subscriber_email.as_str()),
+            status = entry.status.as_str(),
+            attempts = entry.attempts,
+            last_error = htmlescape::encode_minimal(entry.last_error.as_deref().unwrap_or("")),
+            last_attempted_at = entry
+                .last_attempted_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+        )
+        .unwrap();
+    }
+    rows
+}
@@ -1,3 +1,14 @@
+use crate::domain::idempotency::{
+    models::{IdempotencyKey, IdempotentResponse, NextAction},
+    ports::IdempotencyService,
+};
+use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::newsletter::Newsletter;
+use crate::domain::newsletter::models::scheduled_issue::ScheduledAt;
+use crate::domain::newsletter::models::segment::Segment;
+use crate::inbound::http::auth::UserId;
+use crate::inbound::http::idempotency::{from_saved_response, to_saved_response};
+use crate::inbound::http::state::SharedIdempotencyState;
 use crate::inbound::http::utils::see_other;
 use crate::{
     domain::newsletter::{models::newsletter::NewsletterDto, ports::NewsletterService},
@@ -9,22 +20,94 @@ use actix_web::{web, HttpResponse};
 
 #[tracing::instrument(
     name="Publish a newsletter issue",
-    skip(body, state),
-    fields(username=tracing::field::Empty, user_id=tracing::field::Empty, subscriber_email=tracing::field::Empty)
+    skip(body, state, idempotency_state, user_id),
+    fields(user_id=%*user_id)
 )]
-pub async fn publish_newsletter<NS: NewsletterService>(
+pub async fn publish_newsletter<NS: NewsletterService, IS: IdempotencyService>(
     body: web::Form<NewsletterDto>,
     state: web::Data<SharedNewsletterState<NS>>,
+    idempotency_state: web::Data<SharedIdempotencyState<IS>>,
+    user_id: web::ReqData<UserId>,
 ) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
     let newsletter = body.into_inner();
-    let newsletter = newsletter.try_into()?;
-    let base_url = state.url();
+    let idempotency_key: IdempotencyKey = newsletter.idempotency_key.clone().try_into()?;
+    let scheduled_at = ScheduledAt::parse_optional(newsletter.scheduled_at.as_deref())?;
+    let segment = Segment::parse(&newsletter.segment).map_err(NewsletterError::from)?;
+    let list_id = newsletter.list_id;
+    let tracking_enabled = newsletter.tracking;
+    let newsletter: Newsletter = newsletter.try_into()?;
+    let idempotency_service = idempotency_state.idempotency_service();
+
+    match idempotency_service
+        .try_processing(*user_id, &idempotency_key)
+        .await?
+    {
+        NextAction::StartProcessing => {}
+        NextAction::ReturnSavedResponse(saved_response) => {
+            success_message(scheduled_at).send();
+            return Ok(from_saved_response(saved_response)?);
+        }
+    }
+
+    // The response is saved in the transaction that stores the issue, so a
+    // retry after a crash never publishes it twice
+    let location = match scheduled_at {
+        None => "/admin/newsletters",
+        Some(_) => "/admin/newsletters/scheduled",
+    };
+    let (saved_response, response) = to_saved_response(see_other(location)).await?;
+    let idempotent_response = IdempotentResponse {
+        user_id: *user_id,
+        key: idempotency_key.clone(),
+        response: saved_response,
+    };
 
-    state
-        .newsletter_service()
-        .send_newsletter(newsletter, base_url)
-        .await?;
+    let newsletter_service = state.newsletter_service();
+    let outcome = match scheduled_at {
+        None => {
+            newsletter_service
+                .publish_newsletter(
+                    newsletter,
+                    list_id,
+                    segment,
+                    tracking_enabled,
+                    &idempotent_response,
+                )
+                .await
+        }
+        Some(scheduled_at) => {
+            newsletter_service
+                .schedule_newsletter(
+                    newsletter,
+                    list_id,
+                    segment,
+                    scheduled_at,
+                    tracking_enabled,
+                    &idempotent_response,
+                )
+                .await
+        }
+    };
+    if let Err(e) = outcome {
+        idempotency_service
+            .release(*user_id, &idempotency_key)
+            .await?;
+        return Err(e.into());
+    }
+
+    success_message(scheduled_at).send();
+    Ok(response)
+}
 
-    FlashMessage::info("The newsletter issue has been published!").send();
-    Ok(see_other("/admin/newsletters"))
+fn success_message(scheduled_at: Option<ScheduledAt>) -> FlashMessage {
+    match scheduled_at {
+        None => FlashMessage::info(
+            "The newsletter issue has been accepted - emails will go out shortly.",
+        ),
+        Some(scheduled_at) => FlashMessage::info(format!(
+            "The newsletter issue has been scheduled for {} UTC.",
+            scheduled_at.to_form_value().replace('T', " ")
+        )),
+    }
 }
@@ -0,0 +1,31 @@
+use crate::domain::newsletter::{models::segment::SegmentDto, ports::NewsletterService};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+
+const RECIPIENT_COUNT_SCRIPT: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/templates/recipient_count.js"
+));
+
+/// Number of confirmed subscribers in a segment, shown live on the publish forms.
+/// Invalid segments are rejected with a 400.
+#[tracing::instrument(name = "Count the recipients of a segment", skip(state))]
+pub async fn recipient_count<NS: NewsletterService>(
+    query: web::Query<SegmentDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let recipients = state
+        .newsletter_service()
+        .count_recipients(query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "recipients": recipients })))
+}
+
+/// Script of the publish forms that keeps their recipient count up to date
+pub async fn recipient_count_script() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/javascript; charset=utf-8")
+        .body(RECIPIENT_COUNT_SCRIPT)
+}
@@ -0,0 +1,129 @@
+use crate::domain::newsletter::{
+    models::{
+        issue_delivery::NewsletterIssueId,
+        scheduled_issue::{ScheduledIssue, ScheduledIssueDto},
+    },
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, e500, see_other, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "List scheduled issues", skip(flash_message, state))]
+pub async fn scheduled_issues_page<NS: NewsletterService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let scheduled_issues = state
+        .newsletter_service()
+        .get_scheduled_issues()
+        .await
+        .map_err(e500)?;
+
+    let html_content = utils::load_html(HtmlTemplate::ScheduledIssues);
+    let page_content = html_content.replace("{msg_html}", &msg_html).replace(
+        "{scheduled_issues}",
+        &scheduled_issues_to_html(&scheduled_issues),
+    );
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Edit scheduled issue form", skip(state))]
+pub async fn scheduled_issue_form<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let issue = state
+        .newsletter_service()
+        .get_scheduled_issue(newsletter_issue_id.into_inner())
+        .await?;
+
+    let newsletter = &issue.newsletter;
+    let html_content = utils::load_html(HtmlTemplate::ScheduledIssue);
+    let page_content = html_content
+        .replace(
+            "{newsletter_issue_id}",
+            &issue.newsletter_issue_id.to_string(),
+        )
+        .replace(
+            "{title}",
+            &htmlescape::encode_attribute(newsletter.title.as_str()),
+        )
+        .replace(
+            "{text_content}",
+            &htmlescape::encode_minimal(newsletter.content.text.as_str()),
+        )
+        .replace(
+            "{html_content}",
+            &htmlescape::encode_minimal(newsletter.content.html.as_str()),
+        )
+        .replace(
+            "{segment}",
+            &htmlescape::encode_attribute(&issue.segment.to_string()),
+        )
+        .replace("{list_id}", &issue.list_id.to_string())
+        .replace("{scheduled_at}", &issue.scheduled_at.to_form_value());
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Reschedule an issue", skip(body, state))]
+pub async fn reschedule_issue<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    body: web::Form<ScheduledIssueDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let (newsletter, segment, scheduled_at) = body.into_inner().try_into()?;
+    state
+        .newsletter_service()
+        .reschedule_newsletter(
+            newsletter_issue_id.into_inner(),
+            newsletter,
+            segment,
+            scheduled_at,
+        )
+        .await?;
+
+    FlashMessage::info("The scheduled issue has been updated.").send();
+    Ok(see_other("/admin/newsletters/scheduled"))
+}
+
+#[tracing::instrument(name = "Cancel a scheduled issue", skip(state))]
+pub async fn cancel_scheduled_issue<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .newsletter_service()
+        .cancel_scheduled_issue(newsletter_issue_id.into_inner())
+        .await?;
+
+    FlashMessage::info("The scheduled issue has been cancelled.").send();
+    Ok(see_other("/admin/newsletters/scheduled"))
+}
+
+fn scheduled_issues_to_html(issues: &[ScheduledIssue]) -> String {
+    let mut rows = String::new();
+    for issue in issues {
+        writeln!(
+            rows,
+            r#"<tr>
+            <td><a href="/admin/newsletters/scheduled/{newsletter_issue_id}">{title}</a></td>
+            <td>{scheduled_at}</td>
+            <td>
+                <form action="/admin/newsletters/scheduled/{newsletter_issue_id}/cancel" method="post">
+                    <button type="submit">Cancel</button>
+                </form>
+            </td>
+        </tr>"#,
+            newsletter_issue_id = issue.newsletter_issue_id,
+            title = htmlescape::encode_minimal(issue.newsletter.title.as_str()),
+            scheduled_at = issue.scheduled_at.to_form_value().replace('T', " "),
+        )
+        .unwrap();
+    }
+    rows
+}
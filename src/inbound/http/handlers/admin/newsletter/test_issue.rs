@@ -0,0 +1,63 @@
+use crate::domain::newsletter::{
+    models::{
+        newsletter::{Newsletter, NewsletterContentDto},
+        test_issue::{TestIssueDto, TestRecipients},
+    },
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::build_ok_html_response;
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+
+use super::get::{render_newsletter_form, NewsletterFormValues};
+
+/// Sends the issue being written to a few test addresses and renders the form
+/// again with its content, so that the editor can keep working on it.
+#[tracing::instrument(name = "Send a test newsletter issue", skip(body, state))]
+pub async fn send_test_issue<NS: NewsletterService>(
+    body: web::Form<TestIssueDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let body = body.into_inner();
+    let recipients = TestRecipients::parse(&body.test_recipients)?;
+    let newsletter = Newsletter::parse(
+        body.title.clone(),
+        NewsletterContentDto {
+            html: body.content.html.clone(),
+            text: body.content.text.clone(),
+            markdown: body.content.markdown.clone(),
+        },
+    )?;
+
+    state
+        .newsletter_service()
+        .send_test_issue(&newsletter, &recipients, state.url())
+        .await?;
+
+    let lists = state.newsletter_service().get_lists().await?;
+    let sent_to = recipients
+        .as_slice()
+        .iter()
+        .map(|recipient| recipient.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let msg_html = format!(
+        "<p><i>A test issue has been sent to {}.</i></p>",
+        htmlescape::encode_minimal(&sent_to)
+    );
+    let page_content = render_newsletter_form(
+        &msg_html,
+        &NewsletterFormValues {
+            title: &body.title,
+            text_content: &body.content.text,
+            html_content: &body.content.html,
+            markdown_content: body.content.markdown.as_deref().unwrap_or_default(),
+            test_recipients: &body.test_recipients,
+            segment: &body.segment,
+            lists: &lists,
+            list_id: body.list_id,
+        },
+    );
+    Ok(build_ok_html_response(page_content))
+}
@@ -0,0 +1,103 @@
+use crate::domain::newsletter::{
+    models::{
+        issue_delivery::NewsletterIssueId,
+        translation::{IssueTranslations, TranslationDto},
+    },
+    ports::NewsletterService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "Issue translations form", skip(flash_message, state))]
+pub async fn translations_form<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let translations = state
+        .newsletter_service()
+        .get_translations(newsletter_issue_id.into_inner())
+        .await?;
+
+    let html_content = utils::load_html(HtmlTemplate::Translations);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{title}", &htmlescape::encode_minimal(&translations.title))
+        .replace("{default_locale}", translations.default_locale.name())
+        .replace(
+            "{translation_forms}",
+            &translation_forms_to_html(&translations),
+        );
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Save an issue translation", skip(body, state))]
+pub async fn save_translation<NS: NewsletterService>(
+    newsletter_issue_id: web::Path<NewsletterIssueId>,
+    body: web::Form<TranslationDto>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let newsletter_issue_id = newsletter_issue_id.into_inner();
+    state
+        .newsletter_service()
+        .save_translation(newsletter_issue_id, body.into_inner())
+        .await?;
+
+    FlashMessage::info("The translation has been saved.").send();
+    Ok(see_other(&format!(
+        "/admin/newsletters/translations/{}",
+        newsletter_issue_id
+    )))
+}
+
+fn translation_forms_to_html(translations: &IssueTranslations) -> String {
+    let mut forms = String::new();
+    for locale in translations.translatable_locales() {
+        let translation = translations.get(locale);
+        let newsletter = translation.map(|t| &t.newsletter);
+        writeln!(
+            forms,
+            r#"<h2>{name}</h2>
+    <form action="/admin/newsletters/translations/{newsletter_issue_id}" method="post">
+        <input type="hidden" name="locale" value="{locale}">
+        <label>Title:<br>
+            <input type="text" name="title" value="{title}">
+        </label>
+        <br>
+        <label>Markdown content (when filled, the plain text and HTML contents are generated from it):<br>
+            <textarea name="markdown_content" rows="20" cols="50">{markdown_content}</textarea>
+        </label>
+        <br>
+        <label>Plain text content:<br>
+            <textarea name="text_content" rows="20" cols="50">{text_content}</textarea>
+        </label>
+        <br>
+        <label>HTML content:<br>
+            <textarea name="html_content" rows="20" cols="50">{html_content}</textarea>
+        </label>
+        <br>
+        <button type="submit">Save {name} translation</button>
+    </form>"#,
+            name = locale.name(),
+            newsletter_issue_id = translations.newsletter_issue_id,
+            locale = locale.as_str(),
+            title = htmlescape::encode_attribute(newsletter.map_or("", |n| n.title.as_str())),
+            markdown_content = htmlescape::encode_minimal(
+                translation
+                    .and_then(|t| t.markdown.as_deref())
+                    .unwrap_or_default()
+            ),
+            text_content =
+                htmlescape::encode_minimal(newsletter.map_or("", |n| n.content.text.as_str())),
+            html_content =
+                htmlescape::encode_minimal(newsletter.map_or("", |n| n.content.html.as_str())),
+        )
+        .unwrap();
+    }
+    forms
+}
@@ -0,0 +1,101 @@
+use crate::domain::new_subscriber::{
+    models::{list::ListId, subscriber::NewSubscriber, tag::SubscriberTagsRequest},
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SubscribersQuery {
+    /// Defaults to the default list
+    list_id: Option<ListId>,
+}
+
+#[tracing::instrument(name = "List subscribers", skip(flash_message, state))]
+pub async fn subscribers_page<SS: SubscriptionService>(
+    query: web::Query<SubscribersQuery>,
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let (list, subscribers) = state
+        .subscription_service()
+        .get_subscribers(query.into_inner().list_id)
+        .await?;
+
+    let html_content = utils::load_html(HtmlTemplate::Subscribers);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace(
+            "{list_name}",
+            &htmlescape::encode_minimal(list.name.as_str()),
+        )
+        .replace("{subscribers}", &subscribers_to_html(&subscribers));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Tag a subscriber", skip(body, state))]
+pub async fn set_subscriber_tags<SS: SubscriptionService>(
+    subscriber_id: web::Path<uuid::Uuid>,
+    body: web::Form<SubscriberTagsRequest>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .subscription_service()
+        .set_tags(subscriber_id.into_inner(), body.into_inner())
+        .await?;
+
+    FlashMessage::info("The tags have been saved.").send();
+    Ok(see_other("/admin/subscribers"))
+}
+
+#[tracing::instrument(name = "Apply retention policy on demand", skip(state))]
+pub async fn apply_retention<SS: SubscriptionService>(
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let report = state.subscription_service().apply_retention().await?;
+
+    FlashMessage::info(format!(
+        "Removed {} unconfirmed subscriptions and {} pending cancellations.",
+        report.unconfirmed_subscriptions, report.pending_cancellations
+    ))
+    .send();
+    Ok(see_other("/admin/subscribers"))
+}
+
+fn subscribers_to_html(subscribers: &[NewSubscriber]) -> String {
+    let mut rows = String::new();
+    for subscriber in subscribers {
+        let tags: Vec<_> = subscriber.tags.iter().map(|tag| tag.as_str()).collect();
+        writeln!(
+            rows,
+            r#"<tr>
+            <td>{email}</td>
+            <td>{name}</td>
+            <td>{status}</td>
+            <td>{locale}</td>
+            <td>
+                <form action="/admin/subscribers/{subscriber_id}/tags" method="post">
+                    <input type="text" name="tags" value="{tags}">
+                    <button type="submit">Save tags</button>
+                </form>
+            </td>
+        </tr>"#,
+            email = htmlescape::encode_minimal(subscriber.email.as_str()),
+            name = htmlescape::encode_minimal(subscriber.name.as_str()),
+            status = String::from(subscriber.status.clone()),
+            locale = subscriber
+                .locale
+                .map(|locale| locale.as_str())
+                .unwrap_or_default(),
+            subscriber_id = subscriber.id.unwrap_or_default(),
+            tags = htmlescape::encode_attribute(&tags.join(", ")),
+        )
+        .unwrap();
+    }
+    rows
+}
@@ -0,0 +1,88 @@
+use crate::domain::new_subscriber::{
+    errors::SubscriberError,
+    models::suppression::{ReinstateRequest, SuppressedAddress, Suppression, SuppressionRequest},
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "List suppressed addresses", skip(flash_message, state))]
+pub async fn suppressions_page<SS: SubscriptionService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let suppressed = state.subscription_service().get_suppressed().await?;
+
+    let html_content = utils::load_html(HtmlTemplate::Suppressions);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace("{suppressions}", &suppressions_to_html(&suppressed));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Suppress an address by hand", skip(body, state))]
+pub async fn add_suppression<SS: SubscriptionService>(
+    body: web::Form<SuppressionRequest>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    let suppression = Suppression::try_from(body.into_inner()).map_err(SubscriberError::from)?;
+    let email = suppression.email.clone();
+    let suppressed = state.subscription_service().suppress(suppression).await?;
+
+    FlashMessage::info(format!(
+        "Suppressed {} subscriptions of {}.",
+        suppressed, email
+    ))
+    .send();
+    Ok(see_other("/admin/suppressions"))
+}
+
+#[tracing::instrument(name = "Reinstate a suppressed address", skip(body, state))]
+pub async fn reinstate_address<SS: SubscriptionService>(
+    body: web::Form<ReinstateRequest>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .subscription_service()
+        .reinstate(body.into_inner())
+        .await?;
+
+    FlashMessage::info("The address has been reinstated.").send();
+    Ok(see_other("/admin/suppressions"))
+}
+
+fn suppressions_to_html(suppressed: &[SuppressedAddress]) -> String {
+    let mut rows = String::new();
+    for address in suppressed {
+        let lists: Vec<&str> = address.lists.iter().map(|list| list.as_str()).collect();
+        writeln!(
+            rows,
+            r#"<tr>
+            <td>{email}</td>
+            <td>{lists}</td>
+            <td>{reason}</td>
+            <td>{description}</td>
+            <td>{suppressed_at}</td>
+            <td>
+                <form action="/admin/suppressions/reinstate" method="post">
+                    <input type="hidden" name="email" value="{email_attribute}">
+                    <button type="submit">Reinstate</button>
+                </form>
+            </td>
+        </tr>"#,
+            email = htmlescape::encode_minimal(address.email.as_str()),
+            lists = htmlescape::encode_minimal(&lists.join(", ")),
+            reason = address.reason.as_str(),
+            description = htmlescape::encode_minimal(&address.description),
+            suppressed_at = address.suppressed_at.format("%Y-%m-%d %H:%M UTC"),
+            email_attribute = htmlescape::encode_attribute(address.email.as_str()),
+        )
+        .unwrap();
+    }
+    rows
+}
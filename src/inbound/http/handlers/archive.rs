@@ -0,0 +1,58 @@
+use crate::domain::newsletter::{models::archive::ArchiveEntry, ports::NewsletterService};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::locale::PageLocale;
+use crate::inbound::http::utils::{self, build_ok_html_response, HtmlTemplate};
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::{web, HttpResponse};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "Newsletter archive", skip(state))]
+pub async fn archive<NS: NewsletterService>(
+    state: web::Data<SharedNewsletterState<NS>>,
+    locale: PageLocale,
+) -> Result<HttpResponse, AppError> {
+    let entries = state.newsletter_service().get_archive().await?;
+
+    let html_content = utils::load_localized_html(HtmlTemplate::Archive, locale.0);
+    let page_content = html_content.replace("{issues}", &archive_entries_to_html(&entries));
+    Ok(build_ok_html_response(page_content))
+}
+
+#[tracing::instrument(name = "Archived newsletter issue", skip(state))]
+pub async fn archived_issue<NS: NewsletterService>(
+    slug: web::Path<String>,
+    state: web::Data<SharedNewsletterState<NS>>,
+    locale: PageLocale,
+) -> Result<HttpResponse, AppError> {
+    let issue = state
+        .newsletter_service()
+        .get_archived_issue(slug.into_inner(), state.url())
+        .await?;
+
+    let html_content = utils::load_localized_html(HtmlTemplate::ArchivedIssue, locale.0);
+    let page_content = html_content
+        .replace("{title}", &htmlescape::encode_minimal(&issue.title))
+        .replace(
+            "{published_at}",
+            &issue.published_at.format("%Y-%m-%d").to_string(),
+        )
+        // The issue body is written by the editors: strip scripts, event handlers
+        // and anything else that is unsafe to render on our domain
+        .replace("{content}", &ammonia::clean(&issue.html_content));
+    Ok(build_ok_html_response(page_content))
+}
+
+fn archive_entries_to_html(entries: &[ArchiveEntry]) -> String {
+    let mut items = String::new();
+    for entry in entries {
+        writeln!(
+            items,
+            r#"<li><a href="/newsletters/{slug}">{title}</a> - {published_at}</li>"#,
+            slug = entry.slug,
+            title = htmlescape::encode_minimal(&entry.title),
+            published_at = entry.published_at.format("%Y-%m-%d"),
+        )
+        .unwrap();
+    }
+    items
+}
@@ -1,15 +1,23 @@
 use crate::{
     domain::new_subscriber::{models::token::SubscriptionTokenRequest, ports::SubscriptionService},
-    inbound::http::{errors::AppError, SharedSubscriptionState},
+    inbound::http::SharedSubscriptionState,
 };
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-#[tracing::instrument(name = "Confirm a pending subscriber", skip(req, state))]
+use super::subscription_outcome::{invalid_link, outcome_response, OutcomeError, ResponseFormat};
+
+#[tracing::instrument(name = "Confirm a pending subscriber", skip(req, request, state))]
 pub async fn confirm<SS: SubscriptionService>(
-    req: web::Query<SubscriptionTokenRequest>,
+    req: Result<web::Query<SubscriptionTokenRequest>, actix_web::Error>,
+    request: HttpRequest,
     state: web::Data<SharedSubscriptionState<SS>>,
-) -> Result<HttpResponse, AppError> {
-    let req = req.into_inner();
-    state.subscription_service().confirm(req).await?;
-    Ok(HttpResponse::Ok().finish())
+) -> Result<HttpResponse, OutcomeError> {
+    let format = ResponseFormat::negotiate(&request);
+    let req = req.map_err(|_| OutcomeError::new(invalid_link(), format))?;
+    let outcome = state
+        .subscription_service()
+        .confirm(req.into_inner())
+        .await
+        .map_err(|e| OutcomeError::new(e, format))?;
+    Ok(outcome_response(format, outcome, ""))
 }
@@ -0,0 +1,199 @@
+use crate::domain::newsletter::models::feed::{Feed, FeedEntry};
+use crate::domain::newsletter::ports::NewsletterService;
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::http::header::{
+    self, EntityTag, Header, HttpDate, IfModifiedSince, IfNoneMatch, LastModified,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Timelike;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::time::SystemTime;
+
+const FEED_TITLE: &str = "Newsletter archive";
+const FEED_DESCRIPTION: &str = "Past issues of our newsletter";
+
+#[tracing::instrument(name = "RSS feed", skip(request, state))]
+pub async fn rss_feed<NS: NewsletterService>(
+    request: HttpRequest,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let feed = state.newsletter_service().get_feed(state.url()).await?;
+    let body = render_rss(&feed, state.url());
+    Ok(build_feed_response(
+        &request,
+        body,
+        "application/rss+xml; charset=utf-8",
+        feed.last_modified,
+    ))
+}
+
+#[tracing::instrument(name = "Atom feed", skip(request, state))]
+pub async fn atom_feed<NS: NewsletterService>(
+    request: HttpRequest,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let feed = state.newsletter_service().get_feed(state.url()).await?;
+    let body = render_atom(&feed, state.url());
+    Ok(build_feed_response(
+        &request,
+        body,
+        "application/atom+xml; charset=utf-8",
+        feed.last_modified,
+    ))
+}
+
+/// Answers with `304 Not Modified` when the feed reader already holds the
+/// current version of the feed
+fn build_feed_response(
+    request: &HttpRequest,
+    body: String,
+    content_type: &'static str,
+    last_modified: Option<chrono::DateTime<chrono::Utc>>,
+) -> HttpResponse {
+    let etag = EntityTag::new_strong(hex::encode(Sha256::digest(body.as_bytes())));
+    // HTTP dates have a one second resolution
+    let last_modified = last_modified
+        .and_then(|t| t.with_nanosecond(0))
+        .map(|t| HttpDate::from(SystemTime::from(t)));
+
+    // If-Modified-Since must be ignored when If-None-Match is present
+    let not_modified = if request.headers().contains_key(header::IF_NONE_MATCH) {
+        match IfNoneMatch::parse(request) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+            Err(_) => false,
+        }
+    } else {
+        match (IfModifiedSince::parse(request), last_modified) {
+            (Ok(IfModifiedSince(since)), Some(last_modified)) => last_modified <= since,
+            _ => false,
+        }
+    };
+
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response.insert_header(header::ETag(etag));
+    if let Some(last_modified) = last_modified {
+        response.insert_header(LastModified(last_modified));
+    }
+
+    if not_modified {
+        response.finish()
+    } else {
+        response.content_type(content_type).body(body)
+    }
+}
+
+fn render_rss(feed: &Feed, base_url: &str) -> String {
+    let mut rss = String::new();
+    writeln!(rss, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        rss,
+        r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">"#
+    )
+    .unwrap();
+    writeln!(rss, "<channel>").unwrap();
+    writeln!(rss, "<title>{}</title>", FEED_TITLE).unwrap();
+    writeln!(rss, "<link>{}/newsletters</link>", base_url).unwrap();
+    writeln!(rss, "<description>{}</description>", FEED_DESCRIPTION).unwrap();
+    writeln!(
+        rss,
+        r#"<atom:link href="{}/feed.rss" rel="self" type="application/rss+xml"/>"#,
+        base_url
+    )
+    .unwrap();
+    if let Some(last_modified) = feed.last_modified {
+        writeln!(
+            rss,
+            "<lastBuildDate>{}</lastBuildDate>",
+            last_modified.to_rfc2822()
+        )
+        .unwrap();
+    }
+    for entry in &feed.entries {
+        let link = build_issue_link(base_url, entry);
+        writeln!(rss, "<item>").unwrap();
+        writeln!(
+            rss,
+            "<title>{}</title>",
+            htmlescape::encode_minimal(entry.title.as_str())
+        )
+        .unwrap();
+        writeln!(rss, "<link>{}</link>", link).unwrap();
+        writeln!(rss, r#"<guid isPermaLink="true">{}</guid>"#, link).unwrap();
+        writeln!(
+            rss,
+            "<pubDate>{}</pubDate>",
+            entry.published_at.to_rfc2822()
+        )
+        .unwrap();
+        writeln!(rss, "<description>{}</description>", render_content(entry)).unwrap();
+        writeln!(rss, "</item>").unwrap();
+    }
+    writeln!(rss, "</channel>").unwrap();
+    write!(rss, "</rss>").unwrap();
+    rss
+}
+
+fn render_atom(feed: &Feed, base_url: &str) -> String {
+    // Atom requires an update time: an empty feed reports the Unix epoch
+    let updated = feed.last_modified.unwrap_or_default();
+
+    let mut atom = String::new();
+    writeln!(atom, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(atom, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#).unwrap();
+    writeln!(atom, "<id>{}/feed.atom</id>", base_url).unwrap();
+    writeln!(atom, "<title>{}</title>", FEED_TITLE).unwrap();
+    writeln!(atom, "<subtitle>{}</subtitle>", FEED_DESCRIPTION).unwrap();
+    writeln!(atom, r#"<link href="{}/newsletters"/>"#, base_url).unwrap();
+    writeln!(atom, r#"<link href="{}/feed.atom" rel="self"/>"#, base_url).unwrap();
+    writeln!(atom, "<updated>{}</updated>", updated.to_rfc3339()).unwrap();
+    for entry in &feed.entries {
+        let link = build_issue_link(base_url, entry);
+        writeln!(atom, "<entry>").unwrap();
+        writeln!(atom, "<id>{}</id>", link).unwrap();
+        writeln!(
+            atom,
+            "<title>{}</title>",
+            htmlescape::encode_minimal(entry.title.as_str())
+        )
+        .unwrap();
+        writeln!(atom, r#"<link href="{}"/>"#, link).unwrap();
+        writeln!(
+            atom,
+            "<published>{}</published>",
+            entry.published_at.to_rfc3339()
+        )
+        .unwrap();
+        writeln!(
+            atom,
+            "<updated>{}</updated>",
+            entry.published_at.to_rfc3339()
+        )
+        .unwrap();
+        writeln!(
+            atom,
+            r#"<content type="html">{}</content>"#,
+            render_content(entry)
+        )
+        .unwrap();
+        writeln!(atom, "</entry>").unwrap();
+    }
+    write!(atom, "</feed>").unwrap();
+    atom
+}
+
+fn build_issue_link(base_url: &str, entry: &FeedEntry) -> String {
+    format!("{}/newsletters/{}", base_url, entry.slug)
+}
+
+/// The issue body is sanitized like on the archive page, then escaped to be
+/// embedded as text in the XML document
+fn render_content(entry: &FeedEntry) -> String {
+    htmlescape::encode_minimal(&ammonia::clean(&entry.html_content))
+}
@@ -1,10 +1,11 @@
 use actix_web::http::header::ContentType;
 use actix_web::HttpResponse;
 
-use crate::inbound::http::utils::{load_html, HtmlTemplate};
+use crate::inbound::http::locale::PageLocale;
+use crate::inbound::http::utils::{load_localized_html, HtmlTemplate};
 
-pub async fn home() -> HttpResponse {
-    let body = load_html(HtmlTemplate::Home);
+pub async fn home(locale: PageLocale) -> HttpResponse {
+    let body = load_localized_html(HtmlTemplate::Home, locale.0);
 
     HttpResponse::Ok()
         .content_type(ContentType::html())
@@ -30,12 +30,14 @@ pub async fn login<AS: AuthService>(
     }
 }
 
+#[allow(clippy::result_large_err)]
 fn handle_login_success() -> Result<HttpResponse, InternalError<CredentialsError>> {
     Ok(HttpResponse::SeeOther()
         .insert_header((LOCATION, "/admin/dashboard"))
         .finish())
 }
 
+#[allow(clippy::result_large_err)]
 fn handle_login_failure(
     error: CredentialsError,
 ) -> Result<HttpResponse, InternalError<CredentialsError>> {
@@ -0,0 +1,156 @@
+use crate::domain::new_subscriber::{
+    models::{
+        preferences::{DeliveryFrequency, PreferencesRequest, SubscriberPreferences},
+        token::SubscriptionTokenRequest,
+    },
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::locale::PageLocale;
+use crate::inbound::http::utils::{self, build_ok_html_response, see_other, HtmlTemplate};
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpResponse};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use std::fmt::Write;
+
+#[tracing::instrument(name = "Show subscriber preferences", skip(flash_message, state, req))]
+pub async fn preferences_page<SS: SubscriptionService>(
+    flash_message: IncomingFlashMessages,
+    state: web::Data<SharedSubscriptionState<SS>>,
+    req: web::Query<SubscriptionTokenRequest>,
+    locale: PageLocale,
+) -> Result<HttpResponse, AppError> {
+    let req = req.into_inner();
+    let subscription_token = req.subscription_token.clone();
+    let preferences = state.subscription_service().get_preferences(req).await?;
+
+    let msg_html = utils::flash_message_to_html(flash_message);
+    let paused = if preferences.is_paused(chrono::Utc::now()) {
+        preferences
+            .paused_until
+            .map(|until| {
+                format!(
+                    "<p>{} {}.</p>",
+                    locale.pick(
+                        "Your subscriptions are paused until",
+                        "Vos abonnements sont suspendus jusqu'au"
+                    ),
+                    until.format("%Y-%m-%d")
+                )
+            })
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let html_content = utils::load_localized_html(HtmlTemplate::Preferences, locale.0);
+    let page_content = html_content
+        .replace("{msg_html}", &msg_html)
+        .replace(
+            "{subscription_token}",
+            &htmlescape::encode_attribute(&subscription_token),
+        )
+        .replace(
+            "{email}",
+            &htmlescape::encode_minimal(preferences.email.as_str()),
+        )
+        .replace(
+            "{name}",
+            &htmlescape::encode_attribute(preferences.name.as_str()),
+        )
+        .replace("{lists}", &lists_to_html(&preferences))
+        .replace("{frequencies}", &frequencies_to_html(&preferences, locale))
+        .replace("{paused}", &paused);
+    Ok(build_ok_html_response(page_content))
+}
+
+/// The form is read as pairs since every checked list repeats the `lists` key
+#[tracing::instrument(name = "Update subscriber preferences", skip(body, state))]
+pub async fn update_preferences<SS: SubscriptionService>(
+    body: web::Form<Vec<(String, String)>>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+    locale: PageLocale,
+) -> Result<HttpResponse, AppError> {
+    let req = parse_preferences_form(body.into_inner())?;
+    let location = format!(
+        "/subscriptions/preferences?subscription_token={}",
+        urlencoding::encode(&req.subscription_token)
+    );
+    state.subscription_service().update_preferences(req).await?;
+
+    FlashMessage::info(locale.pick(
+        "Your preferences have been saved.",
+        "Vos préférences ont été enregistrées.",
+    ))
+    .send();
+    Ok(see_other(&location))
+}
+
+fn parse_preferences_form(fields: Vec<(String, String)>) -> Result<PreferencesRequest, AppError> {
+    let mut req = PreferencesRequest {
+        subscription_token: String::new(),
+        name: String::new(),
+        lists: Vec::new(),
+        frequency: String::new(),
+        pause_weeks: String::new(),
+    };
+    for (key, value) in fields {
+        match key.as_str() {
+            "subscription_token" => req.subscription_token = value,
+            "name" => req.name = value,
+            "lists" => req.lists.push(
+                value
+                    .parse()
+                    .map_err(|_| AppError::ValidationError(format!("Unknown list: {}", value)))?,
+            ),
+            "frequency" => req.frequency = value,
+            "pause_weeks" => req.pause_weeks = value,
+            _ => {}
+        }
+    }
+    Ok(req)
+}
+
+fn lists_to_html(preferences: &SubscriberPreferences) -> String {
+    let mut checkboxes = String::new();
+    for (list, subscribed) in &preferences.lists {
+        // The list the link was sent for is left through the unsubscribe link
+        let own_list = list.list_id == preferences.list_id;
+        writeln!(
+            checkboxes,
+            r#"<label><input type="checkbox" name="lists" value="{list_id}"{checked}{disabled}> {name}</label><br>"#,
+            list_id = list.list_id,
+            name = htmlescape::encode_minimal(list.name.as_str()),
+            checked = if *subscribed || own_list { " checked" } else { "" },
+            disabled = if own_list { " disabled" } else { "" },
+        )
+        .unwrap();
+    }
+    checkboxes
+}
+
+fn frequencies_to_html(preferences: &SubscriberPreferences, locale: PageLocale) -> String {
+    let mut radios = String::new();
+    for frequency in DeliveryFrequency::ALL {
+        writeln!(
+            radios,
+            r#"<label><input type="radio" name="frequency" value="{value}"{checked}> {label}</label><br>"#,
+            value = frequency.as_str(),
+            label = frequency_label(frequency, locale),
+            checked = if frequency == preferences.frequency {
+                " checked"
+            } else {
+                ""
+            },
+        )
+        .unwrap();
+    }
+    radios
+}
+
+fn frequency_label(frequency: DeliveryFrequency, locale: PageLocale) -> &'static str {
+    let french = match frequency {
+        DeliveryFrequency::EveryIssue => "Chaque numéro, dès sa publication",
+        DeliveryFrequency::WeeklyDigest => "Un récapitulatif hebdomadaire",
+    };
+    locale.pick(frequency.label(), french)
+}
@@ -1,14 +1,17 @@
 use crate::{
     domain::new_subscriber::{
-        models::subscriber::NewSubscriberRequest, ports::SubscriptionService,
+        models::{outcome::SubscriptionOutcome, subscriber::NewSubscriberRequest},
+        ports::SubscriptionService,
     },
-    inbound::http::{errors::AppError, SharedSubscriptionState},
+    inbound::http::{locale::negotiate_locale, SharedSubscriptionState},
 };
-use actix_web::{web, HttpResponse};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+
+use super::subscription_outcome::{outcome_response, OutcomeError, ResponseFormat};
 
 #[tracing::instrument(
     name = "Adding a new subscriber",
-    skip(subscriber_request, state),
+    skip(subscriber_request, request, state),
     fields(
         subscriber_email = %subscriber_request.email,
         subscriber_name = %subscriber_request.name,
@@ -16,13 +19,28 @@ use actix_web::{web, HttpResponse};
 )]
 pub async fn subscribe<SS: SubscriptionService>(
     subscriber_request: web::Form<NewSubscriberRequest>,
+    request: HttpRequest,
     state: web::Data<SharedSubscriptionState<SS>>,
-) -> Result<HttpResponse, AppError> {
-    let subscriber_request = subscriber_request.0;
+) -> Result<HttpResponse, OutcomeError> {
+    let mut subscriber_request = subscriber_request.0;
+    // A locale picked on the form wins over the language of the browser
+    subscriber_request.locale = subscriber_request
+        .locale
+        .filter(|locale| !locale.trim().is_empty())
+        .or_else(|| {
+            request
+                .headers()
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(negotiate_locale)
+                .map(|locale| locale.as_str().to_string())
+        });
+    let format = ResponseFormat::negotiate(&request);
     state
         .subscription_service()
         .new_subscriber(subscriber_request)
-        .await?;
+        .await
+        .map_err(|e| OutcomeError::new(e, format))?;
 
-    Ok(HttpResponse::Ok().finish())
+    Ok(outcome_response(format, SubscriptionOutcome::SignedUp, ""))
 }
@@ -0,0 +1,205 @@
+use crate::domain::new_subscriber::models::{
+    locale::Locale,
+    outcome::SubscriptionOutcome,
+    token::{TokenPurpose, TokenRejection},
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::locale::PageLocale;
+use crate::inbound::http::utils::{self, HtmlTemplate};
+use actix_web::http::header::{Accept, ContentType, Header};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+
+/// How the outcome of a subscriber's request is sent back: a page for the
+/// browsers following the links of our emails, in their language, JSON for
+/// API clients
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseFormat {
+    Html(PageLocale),
+    Json,
+}
+
+impl ResponseFormat {
+    /// JSON when the client prefers it over any other media type, HTML otherwise
+    pub fn negotiate(request: &HttpRequest) -> Self {
+        match Accept::parse(request) {
+            Ok(accept) if accept.preference() == ContentType::json().0 => Self::Json,
+            _ => Self::Html(PageLocale::negotiate(request)),
+        }
+    }
+}
+
+/// Renders `outcome`. `action` is HTML shown below the message, such as the
+/// link that completes a cancellation.
+pub fn outcome_response(
+    format: ResponseFormat,
+    outcome: SubscriptionOutcome,
+    action: &str,
+) -> HttpResponse {
+    match format {
+        ResponseFormat::Json => HttpResponse::Ok().json(serde_json::json!({
+            "outcome": outcome.as_str(),
+            "message": outcome_text(outcome, PageLocale(Locale::English)).1,
+        })),
+        ResponseFormat::Html(locale) => {
+            let (title, message) = outcome_text(outcome, locale);
+            utils::build_ok_html_response(render_page(locale, title, message, action))
+        }
+    }
+}
+
+/// Title and message of the page shown for `outcome`
+fn outcome_text(outcome: SubscriptionOutcome, locale: PageLocale) -> (&'static str, &'static str) {
+    match outcome {
+        SubscriptionOutcome::SignedUp => (
+            locale.pick("Thanks for subscribing!", "Merci de votre inscription !"),
+            locale.pick(
+                "Check your inbox: we sent you an email to confirm your subscription.",
+                "Consultez votre boîte de réception : nous vous avons envoyé un email pour confirmer votre inscription.",
+            ),
+        ),
+        SubscriptionOutcome::Confirmed => (
+            locale.pick("Subscription confirmed", "Inscription confirmée"),
+            locale.pick(
+                "You will receive our next issues.",
+                "Vous recevrez nos prochains numéros.",
+            ),
+        ),
+        SubscriptionOutcome::AlreadyConfirmed => (
+            locale.pick("Already confirmed", "Déjà confirmée"),
+            locale.pick(
+                "Your subscription was already confirmed, there is nothing else to do.",
+                "Votre inscription était déjà confirmée, il n'y a rien d'autre à faire.",
+            ),
+        ),
+        SubscriptionOutcome::CancellationRequested => (
+            locale.pick("Confirm your cancellation", "Confirmez votre désinscription"),
+            locale.pick(
+                "Follow the link below to stop receiving our emails.",
+                "Suivez le lien ci-dessous pour ne plus recevoir nos emails.",
+            ),
+        ),
+        SubscriptionOutcome::Unsubscribed => (
+            locale.pick("Unsubscribed", "Désinscription effectuée"),
+            locale.pick(
+                "You will not receive our emails anymore.",
+                "Vous ne recevrez plus nos emails.",
+            ),
+        ),
+    }
+}
+
+/// An error of a subscriber's request, rendered the way its outcome would have been
+#[derive(thiserror::Error, Debug)]
+#[error("{error}")]
+pub struct OutcomeError {
+    #[source]
+    error: AppError,
+    format: ResponseFormat,
+}
+
+impl OutcomeError {
+    pub fn new(error: impl Into<AppError>, format: ResponseFormat) -> Self {
+        Self {
+            error: error.into(),
+            format,
+        }
+    }
+}
+
+/// The links of our emails always carry a token
+pub(super) fn invalid_link() -> AppError {
+    AppError::ValidationError(INVALID_LINK.to_string())
+}
+
+const INVALID_LINK: &str = "This link is not valid";
+
+/// Message of the page shown for `error`. Messages are written in English: the
+/// ones subscribers run into when following our links are translated, the others
+/// are replaced by a message about the kind of error.
+fn error_message(error: &AppError, locale: PageLocale) -> String {
+    let message = error.public_message();
+    let rejections = [
+        (TokenRejection::Expired, "Ce lien a expiré"),
+        (TokenRejection::AlreadyUsed, "Ce lien a déjà été utilisé"),
+        (
+            TokenRejection::WrongPurpose {
+                expected: TokenPurpose::Confirm,
+            },
+            "Ce lien ne permet pas de confirmer une inscription",
+        ),
+        (
+            TokenRejection::WrongPurpose {
+                expected: TokenPurpose::Unsubscribe,
+            },
+            "Ce lien ne permet pas de se désinscrire",
+        ),
+        (
+            TokenRejection::WrongPurpose {
+                expected: TokenPurpose::Preferences,
+            },
+            "Ce lien ne permet pas de gérer des préférences",
+        ),
+    ];
+    let french = match rejections
+        .iter()
+        .find(|(rejection, _)| rejection.to_string() == message)
+    {
+        Some((_, french)) => french,
+        None if message == INVALID_LINK => "Ce lien n'est pas valide",
+        None if message == "This address no longer receives our emails" => {
+            "Cette adresse ne reçoit plus nos emails"
+        }
+        None => match error {
+            AppError::ValidationError(_) => "Les informations envoyées ne sont pas valides",
+            AppError::AuthError(_) => "Ce lien n'est pas valide",
+            AppError::NotFound(_) => "Ce que vous cherchez n'existe pas",
+            AppError::Conflict(_) => "Cette demande est déjà en cours",
+            AppError::Unexpected(_) => "Une erreur est survenue, veuillez réessayer plus tard",
+        },
+    };
+    locale.pick(&message, french).to_string()
+}
+
+impl ResponseError for OutcomeError {
+    fn status_code(&self) -> StatusCode {
+        self.error.status_code()
+    }
+
+    /// Unlike `AppError`, no authentication is asked for: browsers would prompt
+    /// for credentials on a rejected link
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        match self.format {
+            ResponseFormat::Json => {
+                response.json(serde_json::json!({ "error": self.error.public_message() }))
+            }
+            ResponseFormat::Html(locale) => {
+                let title = match self.error {
+                    AppError::ValidationError(_) => {
+                        locale.pick("Invalid request", "Requête invalide")
+                    }
+                    AppError::AuthError(_) => {
+                        locale.pick("Invalid or expired link", "Lien invalide ou expiré")
+                    }
+                    AppError::NotFound(_) => locale.pick("Not found", "Introuvable"),
+                    AppError::Conflict(_) => locale.pick("Conflict", "Conflit"),
+                    AppError::Unexpected(_) => {
+                        locale.pick("Something went wrong", "Une erreur est survenue")
+                    }
+                };
+                let message = error_message(&self.error, locale);
+                response
+                    .content_type(ContentType::html())
+                    .body(render_page(locale, title, &message, ""))
+            }
+        }
+    }
+}
+
+fn render_page(locale: PageLocale, title: &str, message: &str, action: &str) -> String {
+    utils::load_localized_html(HtmlTemplate::SubscriptionOutcome, locale.0)
+        .replace("{title}", &htmlescape::encode_minimal(title))
+        .replace("{message}", &htmlescape::encode_minimal(message))
+        .replace("{action}", action)
+}
@@ -0,0 +1,37 @@
+use crate::domain::newsletter::models::tracking::{TrackedTarget, TrackingId};
+use crate::domain::newsletter::ports::NewsletterService;
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::SharedNewsletterState;
+use actix_web::http::header::{self, CacheControl, CacheDirective};
+use actix_web::{web, HttpResponse};
+
+/// Transparent 1x1 GIF served as the open pixel
+const PIXEL: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x01, 0x44, 0x00, 0x3b,
+];
+
+/// Records an open or a click of a recipient, then serves the pixel or
+/// forwards to the link of the issue
+#[tracing::instrument(name = "Follow a tracking link", skip(state))]
+pub async fn track<NS: NewsletterService>(
+    tracking_id: web::Path<TrackingId>,
+    state: web::Data<SharedNewsletterState<NS>>,
+) -> Result<HttpResponse, AppError> {
+    let target = state
+        .newsletter_service()
+        .track(tracking_id.into_inner())
+        .await?;
+
+    Ok(match target {
+        TrackedTarget::Open => HttpResponse::Ok()
+            .content_type("image/gif")
+            // Every open must reach us rather than a cache
+            .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+            .body(PIXEL),
+        TrackedTarget::Click(url) => HttpResponse::Found()
+            .insert_header((header::LOCATION, url))
+            .finish(),
+    })
+}
@@ -1,15 +1,72 @@
 use crate::{
-    domain::new_subscriber::{models::token::SubscriptionTokenRequest, ports::SubscriptionService},
-    inbound::http::{errors::AppError, SharedSubscriptionState},
+    domain::new_subscriber::{
+        models::{outcome::SubscriptionOutcome, token::SubscriptionTokenRequest},
+        ports::SubscriptionService,
+    },
+    inbound::http::{errors::AppError, locale::PageLocale, SharedSubscriptionState},
 };
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-#[tracing::instrument(name = "Deleting a subscriber", skip(state, req))]
+use super::subscription_outcome::{invalid_link, outcome_response, OutcomeError, ResponseFormat};
+
+/// Body mail clients send to unsubscribe in one click (RFC 8058)
+const ONE_CLICK_BODY: &str = "List-Unsubscribe=One-Click";
+
+#[tracing::instrument(name = "Deleting a subscriber", skip(state, req, request))]
 pub async fn unsubscribe<SS: SubscriptionService>(
     state: web::Data<SharedSubscriptionState<SS>>,
-    req: web::Query<SubscriptionTokenRequest>,
-) -> Result<HttpResponse, AppError> {
-    let req = req.into_inner();
-    state.subscription_service().delete(req).await?;
-    Ok(HttpResponse::Ok().finish())
+    req: Result<web::Query<SubscriptionTokenRequest>, actix_web::Error>,
+    request: HttpRequest,
+) -> Result<HttpResponse, OutcomeError> {
+    let format = ResponseFormat::negotiate(&request);
+    let req = req
+        .map_err(|_| OutcomeError::new(invalid_link(), format))?
+        .into_inner();
+    // Following the same link again completes the cancellation
+    let confirm_link = format!(
+        r#"<p><a href="/subscriptions/unsubscribe?subscription_token={}">{}</a></p>"#,
+        urlencoding::encode(&req.subscription_token),
+        PageLocale::negotiate(&request).pick("Unsubscribe", "Se désinscrire"),
+    );
+    let outcome = state
+        .subscription_service()
+        .delete(req)
+        .await
+        .map_err(|e| OutcomeError::new(e, format))?;
+    let action = match outcome {
+        SubscriptionOutcome::CancellationRequested => confirm_link.as_str(),
+        _ => "",
+    };
+    Ok(outcome_response(format, outcome, action))
+}
+
+/// One-click unsubscribe: mail clients POST to the link of the `List-Unsubscribe`
+/// header, as form data or multipart, and the subscription is removed right away
+#[tracing::instrument(
+    name = "Deleting a subscriber in one click",
+    skip(state, req, request, body)
+)]
+pub async fn one_click_unsubscribe<SS: SubscriptionService>(
+    state: web::Data<SharedSubscriptionState<SS>>,
+    req: Result<web::Query<SubscriptionTokenRequest>, actix_web::Error>,
+    request: HttpRequest,
+    body: String,
+) -> Result<HttpResponse, OutcomeError> {
+    let format = ResponseFormat::negotiate(&request);
+    let req = req.map_err(|_| OutcomeError::new(invalid_link(), format))?;
+    if !body.contains(ONE_CLICK_BODY) {
+        return Err(OutcomeError::new(
+            AppError::ValidationError(format!(
+                "One-click unsubscribe requests must contain {}",
+                ONE_CLICK_BODY
+            )),
+            format,
+        ));
+    }
+    let outcome = state
+        .subscription_service()
+        .delete_immediately(req.into_inner())
+        .await
+        .map_err(|e| OutcomeError::new(e, format))?;
+    Ok(outcome_response(format, outcome, ""))
 }
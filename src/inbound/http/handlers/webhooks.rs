@@ -0,0 +1,90 @@
+use crate::domain::new_subscriber::{
+    models::{
+        email::SubscriberEmail,
+        suppression::{Suppression, SuppressionReason},
+    },
+    ports::SubscriptionService,
+};
+use crate::inbound::http::errors::AppError;
+use crate::inbound::http::SharedSubscriptionState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+
+const WEBHOOK_SECRET_HEADER: &str = "X-Webhook-Secret";
+
+/// The fields of a Postmark bounce or spam complaint webhook we rely on
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PostmarkEvent {
+    record_type: String,
+    #[serde(rename = "Type", default)]
+    kind: String,
+    email: String,
+    #[serde(default)]
+    description: String,
+    /// Set when Postmark stopped sending to the address itself
+    #[serde(default)]
+    inactive: bool,
+}
+
+impl PostmarkEvent {
+    /// Bounce types meaning the address will never accept our emails
+    const HARD_BOUNCES: [&'static str; 2] = ["HardBounce", "BadEmailAddress"];
+
+    /// Soft bounces, auto-responders and the other events leave the address alone
+    fn suppression_reason(&self) -> Option<SuppressionReason> {
+        match self.record_type.as_str() {
+            "SpamComplaint" => Some(SuppressionReason::Complained),
+            "Bounce" if self.inactive || Self::HARD_BOUNCES.contains(&self.kind.as_str()) => {
+                Some(SuppressionReason::Bounced)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Receive an email provider webhook",
+    skip(request, event, state),
+    fields(record_type = %event.record_type, kind = %event.kind)
+)]
+pub async fn postmark_webhook<SS: SubscriptionService>(
+    request: HttpRequest,
+    event: web::Json<PostmarkEvent>,
+    state: web::Data<SharedSubscriptionState<SS>>,
+) -> Result<HttpResponse, AppError> {
+    verify_secret(&request, state.webhook_secret())?;
+
+    let event = event.into_inner();
+    let Some(reason) = event.suppression_reason() else {
+        return Ok(HttpResponse::Ok().json(serde_json::json!({ "suppressed_subscriptions": 0 })));
+    };
+    // Answering with an error would only make the provider send the event again
+    let Ok(email) = SubscriberEmail::parse(event.email) else {
+        tracing::warn!("The webhook event names an invalid email address");
+        return Ok(HttpResponse::Ok().json(serde_json::json!({ "suppressed_subscriptions": 0 })));
+    };
+    let suppressed = state
+        .subscription_service()
+        .suppress(Suppression {
+            email,
+            reason,
+            description: event.description,
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "suppressed_subscriptions": suppressed })))
+}
+
+fn verify_secret(request: &HttpRequest, secret: &Secret<String>) -> Result<(), AppError> {
+    let provided = request
+        .headers()
+        .get(WEBHOOK_SECRET_HEADER)
+        .ok_or_else(|| AppError::AuthError("The webhook secret is missing".to_string()))?;
+    // Comparing digests keeps the time taken from revealing how much of the secret matched
+    if Sha256::digest(provided.as_bytes()) != Sha256::digest(secret.expose_secret().as_bytes()) {
+        return Err(AppError::AuthError("Invalid webhook secret".to_string()));
+    }
+    Ok(())
+}
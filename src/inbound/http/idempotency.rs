@@ -0,0 +1,41 @@
+use crate::domain::idempotency::models::{HeaderPair, SavedResponse};
+use actix_web::body::to_bytes;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+
+/// Splits a response into a copy that can be stored and an equivalent response to send back
+pub async fn to_saved_response(
+    response: HttpResponse,
+) -> Result<(SavedResponse, HttpResponse), anyhow::Error> {
+    let (response_head, body) = response.into_parts();
+    let body = to_bytes(body).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+    let headers = response_head
+        .headers()
+        .iter()
+        .map(|(name, value)| HeaderPair {
+            name: name.as_str().to_owned(),
+            value: value.as_bytes().to_owned(),
+        })
+        .collect();
+    let saved_response = SavedResponse {
+        status_code: response_head.status().as_u16(),
+        headers,
+        body: body.to_vec(),
+    };
+
+    let response = response_head.set_body(body).map_into_boxed_body();
+    Ok((saved_response, response))
+}
+
+pub fn from_saved_response(saved_response: SavedResponse) -> Result<HttpResponse, anyhow::Error> {
+    let status_code = StatusCode::from_u16(saved_response.status_code)?;
+    let mut response = HttpResponse::build(status_code);
+    for HeaderPair { name, value } in saved_response.headers {
+        response.append_header((
+            HeaderName::try_from(name)?,
+            HeaderValue::from_bytes(&value)?,
+        ));
+    }
+    Ok(response.body(saved_response.body))
+}
@@ -0,0 +1,68 @@
+use crate::domain::new_subscriber::models::locale::Locale;
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest};
+use std::future::{ready, Ready};
+
+/// Locale of the deployment, used for visitors whose browser asks for none of
+/// the supported ones
+#[derive(Debug, Clone, Copy)]
+pub struct DefaultLocale(pub Locale);
+
+/// Locale the public pages are rendered in, negotiated from `Accept-Language`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLocale(pub Locale);
+
+impl PageLocale {
+    pub fn negotiate(request: &HttpRequest) -> Self {
+        let negotiated = request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(negotiate_locale);
+        let default = request
+            .app_data::<web::Data<DefaultLocale>>()
+            .map(|default| default.0)
+            .unwrap_or_default();
+        Self(negotiated.unwrap_or(default))
+    }
+
+    /// Picks the text written in this locale
+    pub fn pick<'a>(&self, english: &'a str, french: &'a str) -> &'a str {
+        match self.0 {
+            Locale::English => english,
+            Locale::French => french,
+        }
+    }
+}
+
+impl FromRequest for PageLocale {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(Self::negotiate(request)))
+    }
+}
+
+/// Picks the supported locale the client prefers, e.g. `fr` for
+/// `de-CH, fr;q=0.8, en;q=0.5`
+pub fn negotiate_locale(accept_language: &str) -> Option<Locale> {
+    let mut languages: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+            Some((tag, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+    // Stable sort: languages of equal quality keep the order of the header
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages
+        .into_iter()
+        .find_map(|(tag, _)| Locale::parse(tag).ok())
+}
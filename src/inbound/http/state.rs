@@ -1,25 +1,34 @@
 use crate::domain::auth::ports::AuthService;
+use crate::domain::idempotency::ports::IdempotencyService;
 use crate::domain::new_subscriber::ports::SubscriptionService;
 use crate::domain::newsletter::ports::NewsletterService;
+use secrecy::Secret;
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct SubscriptionState<SS: SubscriptionService> {
     subscription_service: SS,
+    webhook_secret: Secret<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SharedSubscriptionState<SS: SubscriptionService>(Arc<SubscriptionState<SS>>);
 
 impl<SS: SubscriptionService> SharedSubscriptionState<SS> {
-    pub fn new(subscription_service: SS) -> Self {
+    pub fn new(subscription_service: SS, webhook_secret: Secret<String>) -> Self {
         Self(Arc::new(SubscriptionState {
             subscription_service,
+            webhook_secret,
         }))
     }
     pub fn subscription_service(&self) -> &SS {
         &self.0.subscription_service
     }
+
+    /// Secret the email provider authenticates its webhooks with
+    pub fn webhook_secret(&self) -> &Secret<String> {
+        &self.0.webhook_secret
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -64,3 +73,23 @@ impl<AS: AuthService> SharedAuthState<AS> {
         &self.0.auth_service
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyState<IS: IdempotencyService> {
+    idempotency_service: IS,
+}
+
+#[derive(Debug, Clone)]
+pub struct SharedIdempotencyState<IS: IdempotencyService>(Arc<IdempotencyState<IS>>);
+
+impl<IS: IdempotencyService> SharedIdempotencyState<IS> {
+    pub fn new(idempotency_service: IS) -> Self {
+        Self(Arc::new(IdempotencyState {
+            idempotency_service,
+        }))
+    }
+
+    pub fn idempotency_service(&self) -> &IS {
+        &self.0.idempotency_service
+    }
+}
@@ -1,3 +1,4 @@
+use crate::domain::new_subscriber::models::locale::Locale;
 use actix_web::http::header::{ContentType, LOCATION};
 use actix_web::HttpResponse;
 use actix_web_flash_messages::IncomingFlashMessages;
@@ -19,28 +20,79 @@ pub fn see_other(location: &str) -> HttpResponse {
 }
 
 pub enum HtmlTemplate {
+    Archive,
+    ArchivedIssue,
+    Blocklist,
     ChangePassword,
     Dashboard,
+    DeadLetters,
+    Draft,
+    DraftPreview,
+    Drafts,
     Home,
+    Issue,
+    Issues,
+    Lists,
     Login,
     Newsletter,
+    Preferences,
+    ScheduledIssue,
+    ScheduledIssues,
+    Subscribers,
+    SubscriptionOutcome,
+    Suppressions,
+    Translations,
 }
 
 const TEMPLATES_DIR: &str = "templates";
 
+const TEMPLATE_ARCHIVE: &str = "archive.html";
+const TEMPLATE_ARCHIVED_ISSUE: &str = "archived_issue.html";
+const TEMPLATE_BLOCKLIST: &str = "blocklist.html";
 const TEMPLATE_CHANGE_PASSWORD: &str = "change_password.html";
 const TEMPLATE_DASHBOARD: &str = "dashboard.html";
+const TEMPLATE_DEAD_LETTERS: &str = "dead_letters.html";
+const TEMPLATE_DRAFT: &str = "draft.html";
+const TEMPLATE_DRAFT_PREVIEW: &str = "draft_preview.html";
+const TEMPLATE_DRAFTS: &str = "drafts.html";
 const TEMPLATE_HOME: &str = "home.html";
+const TEMPLATE_ISSUE: &str = "issue.html";
+const TEMPLATE_ISSUES: &str = "issues.html";
+const TEMPLATE_LISTS: &str = "lists.html";
 const TEMPLATE_LOGIN: &str = "login.html";
 const TEMPLATE_NEWSLETTER: &str = "newsletter.html";
+const TEMPLATE_PREFERENCES: &str = "preferences.html";
+const TEMPLATE_SCHEDULED_ISSUE: &str = "scheduled_issue.html";
+const TEMPLATE_SCHEDULED_ISSUES: &str = "scheduled_issues.html";
+const TEMPLATE_SUBSCRIBERS: &str = "subscribers.html";
+const TEMPLATE_SUBSCRIPTION_OUTCOME: &str = "subscription_outcome.html";
+const TEMPLATE_SUPPRESSIONS: &str = "suppressions.html";
+const TEMPLATE_TRANSLATIONS: &str = "translations.html";
 
 fn get_template_path(template: HtmlTemplate) -> (PathBuf, String) {
     let template_name = match template {
+        HtmlTemplate::Archive => TEMPLATE_ARCHIVE,
+        HtmlTemplate::ArchivedIssue => TEMPLATE_ARCHIVED_ISSUE,
+        HtmlTemplate::Blocklist => TEMPLATE_BLOCKLIST,
         HtmlTemplate::ChangePassword => TEMPLATE_CHANGE_PASSWORD,
         HtmlTemplate::Dashboard => TEMPLATE_DASHBOARD,
+        HtmlTemplate::DeadLetters => TEMPLATE_DEAD_LETTERS,
+        HtmlTemplate::Draft => TEMPLATE_DRAFT,
+        HtmlTemplate::DraftPreview => TEMPLATE_DRAFT_PREVIEW,
+        HtmlTemplate::Drafts => TEMPLATE_DRAFTS,
         HtmlTemplate::Home => TEMPLATE_HOME,
+        HtmlTemplate::Issue => TEMPLATE_ISSUE,
+        HtmlTemplate::Issues => TEMPLATE_ISSUES,
+        HtmlTemplate::Lists => TEMPLATE_LISTS,
         HtmlTemplate::Login => TEMPLATE_LOGIN,
         HtmlTemplate::Newsletter => TEMPLATE_NEWSLETTER,
+        HtmlTemplate::Preferences => TEMPLATE_PREFERENCES,
+        HtmlTemplate::ScheduledIssue => TEMPLATE_SCHEDULED_ISSUE,
+        HtmlTemplate::ScheduledIssues => TEMPLATE_SCHEDULED_ISSUES,
+        HtmlTemplate::Subscribers => TEMPLATE_SUBSCRIBERS,
+        HtmlTemplate::SubscriptionOutcome => TEMPLATE_SUBSCRIPTION_OUTCOME,
+        HtmlTemplate::Suppressions => TEMPLATE_SUPPRESSIONS,
+        HtmlTemplate::Translations => TEMPLATE_TRANSLATIONS,
     };
 
     (
@@ -54,6 +106,15 @@ pub fn load_html(template: HtmlTemplate) -> String {
     fs::read_to_string(path).unwrap_or_else(|_| format!("Failed to load {name} page"))
 }
 
+/// Loads a public page from the folder of `locale`, e.g. `templates/fr/home.html`
+pub fn load_localized_html(template: HtmlTemplate, locale: Locale) -> String {
+    let (_, name) = get_template_path(template);
+    let path = PathBuf::from(TEMPLATES_DIR)
+        .join(locale.as_str())
+        .join(&name);
+    fs::read_to_string(path).unwrap_or_else(|_| format!("Failed to load {name} page"))
+}
+
 pub fn flash_message_to_html(flash_message: IncomingFlashMessages) -> String {
     let mut msg_html = String::new();
     for m in flash_message.iter() {
@@ -0,0 +1,9 @@
+pub mod digest;
+pub mod issue_delivery;
+pub mod retention;
+pub mod scheduler;
+
+pub use digest::run_digest_worker_until_stopped;
+pub use issue_delivery::run_issue_delivery_worker_until_stopped;
+pub use retention::run_retention_worker_until_stopped;
+pub use scheduler::run_newsletter_scheduler_until_stopped;
@@ -0,0 +1,21 @@
+use crate::domain::newsletter::{
+    models::issue_delivery::ExecutionOutcome, ports::NewsletterService,
+};
+use std::time::Duration;
+
+const NOTHING_DUE_BACKOFF: Duration = Duration::from_secs(60);
+const ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Sends the weekly digest of every subscriber who opted for one, once it is due
+pub async fn run_digest_worker_until_stopped<NS: NewsletterService>(
+    newsletter_service: NS,
+    base_url: String,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match newsletter_service.try_send_digest(&base_url).await {
+            Ok(ExecutionOutcome::EmptyQueue) => tokio::time::sleep(NOTHING_DUE_BACKOFF).await,
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+            Err(_) => tokio::time::sleep(ERROR_BACKOFF).await,
+        }
+    }
+}
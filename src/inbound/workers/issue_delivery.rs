@@ -0,0 +1,20 @@
+use crate::domain::newsletter::{
+    models::issue_delivery::ExecutionOutcome, ports::NewsletterService,
+};
+use std::time::Duration;
+
+const EMPTY_QUEUE_BACKOFF: Duration = Duration::from_secs(10);
+const ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+pub async fn run_issue_delivery_worker_until_stopped<NS: NewsletterService>(
+    newsletter_service: NS,
+    base_url: String,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match newsletter_service.try_execute_task(&base_url).await {
+            Ok(ExecutionOutcome::EmptyQueue) => tokio::time::sleep(EMPTY_QUEUE_BACKOFF).await,
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+            Err(_) => tokio::time::sleep(ERROR_BACKOFF).await,
+        }
+    }
+}
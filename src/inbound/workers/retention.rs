@@ -0,0 +1,17 @@
+use crate::domain::new_subscriber::ports::SubscriptionService;
+use std::time::Duration;
+
+const ERROR_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Removes the subscriptions that waited too long for their subscriber, every `interval`
+pub async fn run_retention_worker_until_stopped<SS: SubscriptionService>(
+    subscription_service: SS,
+    interval: Duration,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match subscription_service.apply_retention().await {
+            Ok(_) => tokio::time::sleep(interval).await,
+            Err(_) => tokio::time::sleep(ERROR_BACKOFF).await,
+        }
+    }
+}
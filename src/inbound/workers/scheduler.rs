@@ -0,0 +1,20 @@
+use crate::domain::newsletter::{
+    models::issue_delivery::ExecutionOutcome, ports::NewsletterService,
+};
+use std::time::Duration;
+
+const NOTHING_DUE_BACKOFF: Duration = Duration::from_secs(30);
+const ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Releases scheduled newsletter issues into the delivery queue once they are due
+pub async fn run_newsletter_scheduler_until_stopped<NS: NewsletterService>(
+    newsletter_service: NS,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match newsletter_service.try_release_scheduled_issue().await {
+            Ok(ExecutionOutcome::EmptyQueue) => tokio::time::sleep(NOTHING_DUE_BACKOFF).await,
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+            Err(_) => tokio::time::sleep(ERROR_BACKOFF).await,
+        }
+    }
+}
@@ -1,32 +1,111 @@
 use zero2prod::configuration::get_configuration;
 use zero2prod::domain::auth::service::BlogAuth;
+use zero2prod::domain::idempotency::service::BlogIdempotency;
 use zero2prod::domain::new_subscriber::service::BlogSubscription;
 use zero2prod::domain::newsletter::service::BlogDelivery;
 use zero2prod::inbound::http::Application;
+use zero2prod::inbound::workers::{
+    run_digest_worker_until_stopped, run_issue_delivery_worker_until_stopped,
+    run_newsletter_scheduler_until_stopped, run_retention_worker_until_stopped,
+};
 use zero2prod::outbound::db::postgres_db::PostgresDb;
+use zero2prod::outbound::notifier::blocklist::BlocklistNotifier;
 use zero2prod::outbound::notifier::email_client::EmailClient;
 use zero2prod::outbound::telemetry::init_logger;
 
+use std::fmt::{Debug, Display};
 use std::sync::Arc;
+use tokio::task::JoinError;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let configuration = get_configuration().expect("Failed to read configuration");
     init_logger("zero2prod", &configuration.log_level(), std::io::stdout);
 
-    let email_client = Arc::new(EmailClient::new(configuration.email_client));
-    let repo = Arc::new(PostgresDb::new(&configuration.database));
-    let newsletter_service = BlogDelivery::new(Arc::clone(&repo), Arc::clone(&email_client));
-    let subscription_service = BlogSubscription::new(Arc::clone(&repo), Arc::clone(&email_client));
+    let default_locale = configuration
+        .general
+        .default_locale()
+        .expect("Invalid default locale");
+    let repo = Arc::new(PostgresDb::new(
+        &configuration.database,
+        configuration.application.token_hmac_secret.clone(),
+    ));
+    repo.ensure_no_legacy_tokens().await?;
+    let notifier = Arc::new(BlocklistNotifier::new(
+        Arc::new(EmailClient::new(configuration.email_client)),
+        Arc::clone(&repo),
+    ));
+    let newsletter_service =
+        BlogDelivery::new(Arc::clone(&repo), Arc::clone(&notifier), default_locale);
+    let subscription_service = BlogSubscription::new(
+        Arc::clone(&repo),
+        Arc::clone(&notifier),
+        default_locale,
+        configuration.subscriptions.confirmation_policy(),
+        configuration.subscriptions.retention_policy(),
+    );
+    let retention_interval = configuration.subscriptions.retention_interval();
     let auth_service = BlogAuth::new(Arc::clone(&repo));
+    let idempotency_service = BlogIdempotency::new(Arc::clone(&repo));
+    let base_url = configuration.application.base_url.clone();
     let application = Application::build(
-        subscription_service,
-        newsletter_service,
+        subscription_service.clone(),
+        newsletter_service.clone(),
         auth_service,
+        idempotency_service,
         configuration.application,
+        default_locale,
     )
     .await?;
 
-    application.run_until_stopped().await?;
+    let application_task = tokio::spawn(application.run_until_stopped());
+    let scheduler_task = tokio::spawn(run_newsletter_scheduler_until_stopped(
+        newsletter_service.clone(),
+    ));
+    let digest_task = tokio::spawn(run_digest_worker_until_stopped(
+        newsletter_service.clone(),
+        base_url.clone(),
+    ));
+    let retention_task = tokio::spawn(run_retention_worker_until_stopped(
+        subscription_service,
+        retention_interval,
+    ));
+    let worker_task = tokio::spawn(run_issue_delivery_worker_until_stopped(
+        newsletter_service,
+        base_url,
+    ));
+
+    tokio::select! {
+        o = application_task => report_exit("API", o),
+        o = worker_task => report_exit("Background worker", o),
+        o = scheduler_task => report_exit("Newsletter scheduler", o),
+        o = digest_task => report_exit("Digest worker", o),
+        o = retention_task => report_exit("Retention worker", o),
+    };
+
     Ok(())
 }
+
+fn report_exit(task_name: &str, outcome: Result<Result<(), impl Debug + Display>, JoinError>) {
+    match outcome {
+        Ok(Ok(())) => {
+            tracing::info!("{} has exited", task_name)
+        }
+        Ok(Err(e)) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} failed",
+                task_name
+            )
+        }
+        Err(e) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} task failed to complete",
+                task_name
+            )
+        }
+    }
+}
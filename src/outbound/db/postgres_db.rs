@@ -2,53 +2,141 @@ use crate::configuration::DatabaseSettings;
 use crate::domain::new_subscriber::errors::SubscriberError;
 use crate::domain::new_subscriber::{
     models::{
-        subscriber::{NewSubscriber, NewSubscriberRequest, SubscriberId, SubscriberStatus},
-        token::SubscriptionToken,
+        list::{ListError, ListId, ListName, ListSlug, MailingList},
+        locale::Locale,
+        subscriber::{NewSubscriber, NewSubscriberRequest, SubscriberStatus},
+        tag::SubscriberTag,
+        token::{IssuedToken, LinkTokens, SubscriptionToken, TokenPurpose},
     },
-    ports::SubscriberRepository,
+    ports::{ListRepository, SubscriberRepository},
 };
 use crate::domain::newsletter::ports::NewsletterRepository;
 use anyhow::Context;
 use chrono::Utc;
+use secrecy::Secret;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Executor, PgPool, Postgres, Transaction};
 
 mod auth_repo;
 mod debug;
+mod idempotency_repo;
+mod list_repo;
 mod newsletter_repo;
 mod subscriber_repo;
 
 #[derive(Clone, Debug)]
 pub struct PostgresDb {
     pool: PgPool,
+    token_hmac_secret: Secret<String>,
 }
 
 impl PostgresDb {
-    pub fn new(configuration: &DatabaseSettings) -> PostgresDb {
+    pub fn new(configuration: &DatabaseSettings, token_hmac_secret: Secret<String>) -> PostgresDb {
         PostgresDb {
             pool: PgPoolOptions::new()
                 .acquire_timeout(std::time::Duration::from_secs(2))
                 .connect_lazy_with(configuration.with_db()),
+            token_hmac_secret,
         }
     }
 
+    /// Replaces the raw tokens stored before tokens were hashed with their hash.
+    /// Run once by the `hash_legacy_tokens` binary after the migration that
+    /// introduced hashed tokens. Returns the number of rows migrated.
+    #[tracing::instrument(name = "Hash legacy subscription tokens", skip(self))]
+    pub async fn hash_legacy_tokens(&self) -> Result<u64, anyhow::Error> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let legacy_tokens = sqlx::query!(
+            r#"SELECT legacy_token AS "legacy_token!" FROM subscription_tokens
+            WHERE legacy_token IS NOT NULL FOR UPDATE"#
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .context("Failed to retrieve legacy subscription tokens")?;
+
+        let mut migrated = 0;
+        for record in legacy_tokens {
+            // Legacy tokens were all issued by `SubscriptionToken::new`, a row
+            // that does not parse could never have been used anyway
+            let token_hash = match SubscriptionToken::parse(record.legacy_token.clone()) {
+                Ok(token) => Some(self.hash_token(&token)),
+                Err(_) => None,
+            };
+            let query = match token_hash {
+                Some(token_hash) => sqlx::query!(
+                    r#"UPDATE subscription_tokens SET token_hash = $1, legacy_token = NULL
+                    WHERE legacy_token = $2"#,
+                    token_hash,
+                    record.legacy_token,
+                ),
+                None => sqlx::query!(
+                    r#"DELETE FROM subscription_tokens WHERE legacy_token = $1"#,
+                    record.legacy_token,
+                ),
+            };
+            migrated += transaction
+                .execute(query)
+                .await
+                .context("Failed to hash a legacy subscription token")?
+                .rows_affected();
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit the hashed subscription tokens")?;
+        Ok(migrated)
+    }
+
+    /// Fails while some tokens are stored raw: links carrying them would be
+    /// rejected until `hash_legacy_tokens` is run
+    #[tracing::instrument(name = "Check for legacy subscription tokens", skip(self))]
+    pub async fn ensure_no_legacy_tokens(&self) -> Result<(), anyhow::Error> {
+        let record = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM subscription_tokens
+            WHERE legacy_token IS NOT NULL"#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count legacy subscription tokens")?;
+        if record.count > 0 {
+            anyhow::bail!(
+                "{} subscription tokens are not hashed yet: run `hash_legacy_tokens` \
+                with the configuration of the application before starting it",
+                record.count
+            );
+        }
+        Ok(())
+    }
+
+    fn hash_token(&self, token: &SubscriptionToken) -> String {
+        token.hash(&self.token_hmac_secret)
+    }
+
     #[tracing::instrument(
         name = "Checking if user is already subscribed",
         skip(self, subscriber)
     )]
     async fn get_subscriber(
         &self,
+        list_id: ListId,
         subscriber: NewSubscriber,
     ) -> Result<NewSubscriber, SubscriberError> {
         let record = sqlx::query!(
-            "SELECT id, email, name, status FROM subscriptions WHERE email = $1",
+            r#"SELECT id, email, name, status, locale,
+                ARRAY(SELECT tag FROM subscription_tags WHERE subscriber_id = subscriptions.id ORDER BY tag) AS "tags!"
+            FROM subscriptions WHERE list_id = $1 AND email = $2"#,
+            list_id,
             subscriber.email.as_str()
         )
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| SubscriberError::Unexpected(anyhow::Error::from(e)))?;
 
-        let (id, status) = match record {
+        let (id, status, locale, tags) = match record {
             Some(existing_subscriber) if existing_subscriber.name == subscriber.name.as_str() => {
                 let parsed_status = SubscriberStatus::parse(&existing_subscriber.status)?;
 
@@ -59,15 +147,33 @@ impl PostgresDb {
                         subscriber.email.as_str()
                     )));
                 }
-                (Some(existing_subscriber.id), parsed_status)
+                // Existing subscribers keep the locale and tags they signed up with
+                let locale = existing_subscriber
+                    .locale
+                    .as_deref()
+                    .map(Locale::parse)
+                    .transpose()?;
+                let tags = existing_subscriber
+                    .tags
+                    .iter()
+                    .map(|tag| SubscriberTag::parse(tag))
+                    .collect::<Result<_, _>>()?;
+                (Some(existing_subscriber.id), parsed_status, locale, tags)
             }
 
-            _ => (None, SubscriberStatus::NotInserted),
+            _ => (
+                None,
+                SubscriberStatus::NotInserted,
+                subscriber.locale,
+                subscriber.tags,
+            ),
         };
 
         Ok(NewSubscriber::build(subscriber.name, subscriber.email)
             .with_id(id)
-            .with_status(status))
+            .with_status(status)
+            .with_locale(locale)
+            .with_tags(tags))
     }
 
     #[tracing::instrument(
@@ -77,24 +183,30 @@ impl PostgresDb {
     async fn insert_new(
         &self,
         transaction: &mut Transaction<'_, Postgres>,
+        list_id: ListId,
         new_subscriber: NewSubscriber,
     ) -> Result<NewSubscriber, SubscriberError> {
         let subscriber_id = uuid::Uuid::new_v4();
         let query = sqlx::query!(
             r#"
-        INSERT INTO subscriptions (id, email, name, subscribed_at, status)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO subscriptions (id, email, name, subscribed_at, status, locale, list_id, last_confirmation_sent_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $4)
                 "#,
             subscriber_id,
             new_subscriber.email.as_str(),
             new_subscriber.name.as_str(),
             Utc::now(),
-            String::from(SubscriberStatus::SubscriptionPendingConfirmation)
+            String::from(SubscriberStatus::SubscriptionPendingConfirmation),
+            new_subscriber.locale.map(|locale| locale.as_str()),
+            list_id,
         );
         transaction
             .execute(query)
             .await
             .map_err(|e| SubscriberError::Unexpected(anyhow::Error::from(e)))?;
+        self.insert_tags(transaction, subscriber_id, &new_subscriber.tags)
+            .await
+            .map_err(|e| SubscriberError::Unexpected(anyhow::Error::from(e)))?;
 
         Ok(new_subscriber
             .with_id(Some(subscriber_id))
@@ -107,7 +219,9 @@ impl PostgresDb {
         id: uuid::Uuid,
     ) -> Result<NewSubscriber, SubscriberError> {
         let result = sqlx::query!(
-            "SELECT  email, name, status FROM subscriptions WHERE id = $1",
+            r#"SELECT email, name, status, locale,
+                ARRAY(SELECT tag FROM subscription_tags WHERE subscriber_id = subscriptions.id ORDER BY tag) AS "tags!"
+            FROM subscriptions WHERE id = $1"#,
             id
         )
         .fetch_one(&self.pool)
@@ -117,6 +231,9 @@ impl PostgresDb {
         let subscriber_request = NewSubscriberRequest {
             email: result.email,
             name: result.name,
+            locale: result.locale,
+            tags: Some(result.tags.join(",")),
+            list: None,
         };
         let subscriber: NewSubscriber = subscriber_request.try_into()?;
 
@@ -124,19 +241,78 @@ impl PostgresDb {
 
         Ok(subscriber.with_id(Some(id)).with_status(status))
     }
-    #[tracing::instrument(name = "Get token from subscriber id", skip(self, subscriber_id))]
-    async fn get_token_from_subscriber_id(
+    #[tracing::instrument(name = "Saving subscriber tags in db", skip(self, transaction, tags))]
+    async fn insert_tags(
         &self,
+        transaction: &mut Transaction<'_, Postgres>,
         subscriber_id: uuid::Uuid,
-    ) -> Result<SubscriptionToken, SubscriberError> {
-        let result = sqlx::query!(
-            r#"SELECT subscription_token FROM subscription_tokens WHERE  subscriber_id= $1"#,
-            subscriber_id
+        tags: &[SubscriberTag],
+    ) -> Result<(), sqlx::Error> {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.as_str().to_string()).collect();
+        let query = sqlx::query!(
+            r#"INSERT INTO subscription_tags (subscriber_id, tag)
+            SELECT $1, tag FROM UNNEST($2::text[]) AS tag"#,
+            subscriber_id,
+            &tags,
+        );
+        transaction.execute(query).await?;
+        Ok(())
+    }
+
+    /// Stores the hash of a token of a subscription, valid for the lifetime of its purpose
+    #[tracing::instrument(
+        name = "Store subscription token",
+        skip(self, executor, subscription_token)
+    )]
+    async fn store_token<'e, E>(
+        &self,
+        executor: E,
+        subscription_token: &SubscriptionToken,
+        subscriber_id: uuid::Uuid,
+        purpose: TokenPurpose,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO subscription_tokens (token_hash, subscriber_id, purpose, expires_at)
+            VALUES ($1, $2, $3, $4)"#,
+            self.hash_token(subscription_token),
+            subscriber_id,
+            purpose.as_str(),
+            Utc::now() + purpose.lifetime(),
         )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SubscriberError::Unexpected(anyhow::Error::from(e)))?;
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
 
-        SubscriptionToken::try_from(result.subscription_token).map_err(SubscriberError::from)
+    /// Issues the tokens of the links at the bottom of an email to a confirmed subscriber
+    #[tracing::instrument(name = "Issue link tokens", skip(self))]
+    async fn issue_link_tokens(
+        &self,
+        subscriber_id: uuid::Uuid,
+    ) -> Result<LinkTokens, sqlx::Error> {
+        let tokens = LinkTokens {
+            unsubscribe: SubscriptionToken::new(),
+            preferences: SubscriptionToken::new(),
+        };
+        let mut transaction = self.pool.begin().await?;
+        self.store_token(
+            &mut *transaction,
+            &tokens.unsubscribe,
+            subscriber_id,
+            TokenPurpose::Unsubscribe,
+        )
+        .await?;
+        self.store_token(
+            &mut *transaction,
+            &tokens.preferences,
+            subscriber_id,
+            TokenPurpose::Preferences,
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(tokens)
     }
 }
@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+
+use super::*;
+use crate::domain::idempotency::errors::IdempotencyError;
+use crate::domain::idempotency::models::{
+    HeaderPair, IdempotencyKey, IdempotentResponse, SavedResponse,
+};
+use crate::domain::idempotency::ports::IdempotencyRepository;
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "header_pair")]
+struct HeaderPairRecord {
+    name: String,
+    value: Vec<u8>,
+}
+
+impl From<&HeaderPair> for HeaderPairRecord {
+    fn from(pair: &HeaderPair) -> Self {
+        Self {
+            name: pair.name.clone(),
+            value: pair.value.clone(),
+        }
+    }
+}
+
+impl From<HeaderPairRecord> for HeaderPair {
+    fn from(record: HeaderPairRecord) -> Self {
+        Self {
+            name: record.name,
+            value: record.value,
+        }
+    }
+}
+
+impl PostgresDb {
+    /// Saves the response to a request within the transaction of its changes.
+    /// Returns `false`, and saves nothing, when a response was already saved
+    /// for the key: another request made with it has committed its changes.
+    #[tracing::instrument(name = "Save idempotent response", skip(self, transaction, response))]
+    pub(super) async fn save_idempotent_response(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        response: &IdempotentResponse,
+    ) -> Result<bool, anyhow::Error> {
+        let status_code =
+            i16::try_from(response.response.status_code).context("Invalid status code")?;
+        let headers: Vec<HeaderPairRecord> = response
+            .response
+            .headers
+            .iter()
+            .map(HeaderPairRecord::from)
+            .collect();
+        // The row lock makes a concurrent save wait for this transaction, then
+        // find the response saved
+        let query = sqlx::query_unchecked!(
+            r#"
+            UPDATE idempotency
+            SET
+                response_status_code = $3,
+                response_headers = $4,
+                response_body = $5
+            WHERE
+                user_id = $1 AND
+                idempotency_key = $2 AND
+                response_status_code IS NULL
+            "#,
+            response.user_id,
+            response.key.as_str(),
+            status_code,
+            headers,
+            response.response.body.as_slice(),
+        );
+        let result = transaction
+            .execute(query)
+            .await
+            .context("Failed to save response")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for PostgresDb {
+    #[tracing::instrument(name = "Reserve idempotency key", skip(self, key))]
+    async fn try_reserve(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        stale_after: std::time::Duration,
+    ) -> Result<bool, IdempotencyError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO idempotency (user_id, idempotency_key, created_at)
+            VALUES ($1, $2, now())
+            ON CONFLICT (user_id, idempotency_key) DO UPDATE
+            SET created_at = now()
+            WHERE idempotency.response_status_code IS NULL
+                AND idempotency.created_at < now() - make_interval(secs => $3)
+            "#,
+            user_id,
+            key.as_str(),
+            stale_after.as_secs_f64(),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to reserve idempotency key")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(name = "Get saved response", skip(self, key))]
+    async fn get_saved_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<Option<SavedResponse>, IdempotencyError> {
+        let saved_response = sqlx::query!(
+            r#"
+            SELECT
+                response_status_code as "response_status_code!",
+                response_headers as "response_headers!: Vec<HeaderPairRecord>",
+                response_body as "response_body!"
+            FROM idempotency
+            WHERE
+                user_id = $1 AND
+                idempotency_key = $2 AND
+                response_status_code IS NOT NULL
+            "#,
+            user_id,
+            key.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve saved response")?;
+
+        let Some(r) = saved_response else {
+            return Ok(None);
+        };
+        let status_code =
+            u16::try_from(r.response_status_code).context("Invalid saved status code")?;
+
+        Ok(Some(SavedResponse {
+            status_code,
+            headers: r
+                .response_headers
+                .into_iter()
+                .map(HeaderPair::from)
+                .collect(),
+            body: r.response_body,
+        }))
+    }
+
+    #[tracing::instrument(name = "Save response", skip(self, key, response))]
+    async fn save_response(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+        response: &SavedResponse,
+    ) -> Result<(), IdempotencyError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        self.save_idempotent_response(
+            &mut transaction,
+            &IdempotentResponse {
+                user_id,
+                key: key.clone(),
+                response: response.clone(),
+            },
+        )
+        .await?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to save a response")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Release idempotency key", skip(self, key))]
+    async fn release(
+        &self,
+        user_id: uuid::Uuid,
+        key: &IdempotencyKey,
+    ) -> Result<(), IdempotencyError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency
+            WHERE
+                user_id = $1 AND
+                idempotency_key = $2 AND
+                response_status_code IS NULL
+            "#,
+            user_id,
+            key.as_str()
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to release idempotency key")?;
+
+        Ok(())
+    }
+}
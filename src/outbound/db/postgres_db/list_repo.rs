@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+
+use super::*;
+
+struct ListRecord {
+    list_id: ListId,
+    slug: String,
+    name: String,
+    is_default: bool,
+}
+
+impl TryFrom<ListRecord> for MailingList {
+    type Error = ListError;
+
+    fn try_from(r: ListRecord) -> Result<Self, Self::Error> {
+        Ok(MailingList {
+            list_id: r.list_id,
+            slug: ListSlug::parse(&r.slug)?,
+            name: ListName::parse(&r.name)?,
+            is_default: r.is_default,
+        })
+    }
+}
+
+/// Lists are shared by the subscriber and newsletter repositories
+#[async_trait]
+impl ListRepository for PostgresDb {
+    #[tracing::instrument(name = "Get mailing lists", skip(self))]
+    async fn get_lists(&self) -> Result<Vec<MailingList>, SubscriberError> {
+        let records = sqlx::query_as!(
+            ListRecord,
+            r#"SELECT list_id, slug, name, is_default FROM lists ORDER BY is_default DESC, name"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve mailing lists")?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| MailingList::try_from(r).context("Invalid mailing list in database"))
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[tracing::instrument(name = "Get mailing list", skip(self))]
+    async fn get_list(&self, list_id: ListId) -> Result<MailingList, SubscriberError> {
+        let record = sqlx::query_as!(
+            ListRecord,
+            r#"SELECT list_id, slug, name, is_default FROM lists WHERE list_id = $1"#,
+            list_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a mailing list")?;
+
+        let record = record.ok_or_else(|| {
+            SubscriberError::NotFound(format!("List with id {} not found", list_id))
+        })?;
+        Ok(MailingList::try_from(record).context("Invalid mailing list in database")?)
+    }
+
+    #[tracing::instrument(name = "Get mailing list from slug", skip(self))]
+    async fn get_list_by_slug(&self, slug: &ListSlug) -> Result<MailingList, SubscriberError> {
+        let record = sqlx::query_as!(
+            ListRecord,
+            r#"SELECT list_id, slug, name, is_default FROM lists WHERE slug = $1"#,
+            slug.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a mailing list")?;
+
+        let record = record.ok_or_else(|| {
+            SubscriberError::NotFound(format!("List {} not found", slug.as_str()))
+        })?;
+        Ok(MailingList::try_from(record).context("Invalid mailing list in database")?)
+    }
+
+    #[tracing::instrument(name = "Get default mailing list", skip(self))]
+    async fn get_default_list(&self) -> Result<MailingList, SubscriberError> {
+        let record = sqlx::query_as!(
+            ListRecord,
+            r#"SELECT list_id, slug, name, is_default FROM lists WHERE is_default"#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to retrieve the default mailing list")?;
+
+        Ok(MailingList::try_from(record).context("Invalid mailing list in database")?)
+    }
+
+    #[tracing::instrument(name = "Add mailing list", skip(self))]
+    async fn add_list(
+        &self,
+        name: &ListName,
+        slug: &ListSlug,
+    ) -> Result<MailingList, SubscriberError> {
+        let list_id = uuid::Uuid::new_v4();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO lists (list_id, slug, name)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (slug) DO NOTHING
+            "#,
+            list_id,
+            slug.as_str(),
+            name.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to store a mailing list")?;
+
+        if result.rows_affected() == 0 {
+            return Err(SubscriberError::ValidationError(format!(
+                "Another list already uses the slug {}",
+                slug.as_str()
+            )));
+        }
+        Ok(MailingList {
+            list_id,
+            slug: slug.clone(),
+            name: name.clone(),
+            is_default: false,
+        })
+    }
+}
@@ -1,57 +1,1774 @@
 use async_trait::async_trait;
+use sqlx::QueryBuilder;
+use std::collections::HashMap;
 
 use super::*;
+use crate::domain::idempotency::models::IdempotentResponse;
+use crate::domain::new_subscriber::models::blocklist::BlockedPattern;
 use crate::domain::new_subscriber::models::email::SubscriberEmail;
+use crate::domain::new_subscriber::models::locale::Locale;
 use crate::domain::new_subscriber::models::name::SubscriberName;
+use crate::domain::new_subscriber::models::preferences::DeliveryFrequency;
 use crate::domain::newsletter::errors::NewsletterError;
+use crate::domain::newsletter::models::archive::{
+    ArchiveEntry, ArchivedIssue, IssueSlug, PublishedIssue,
+};
 use crate::domain::newsletter::models::confirmed_subscribers::ConfirmedSubscriber;
-use futures::stream::{self, StreamExt};
+use crate::domain::newsletter::models::digest::{DueDigest, DIGEST_INTERVAL};
+use crate::domain::newsletter::models::draft::{Draft, DraftContent};
+use crate::domain::newsletter::models::feed::{Feed, FeedEntry};
+use crate::domain::newsletter::models::issue_delivery::{
+    DeadLetter, DeliveryLogEntry, DeliveryOutcome, DeliveryStatus, DeliveryTask, DeliveryTotals,
+    IssueSummary, NewsletterIssueId,
+};
+use crate::domain::newsletter::models::newsletter::{Newsletter, NewsletterTitle};
+use crate::domain::newsletter::models::scheduled_issue::{ScheduledAt, ScheduledIssue};
+use crate::domain::newsletter::models::segment::{Segment, SegmentExpr};
+use crate::domain::newsletter::models::tracking::{
+    LinkClicks, TrackedTarget, TrackingId, TrackingLinks, TrackingStats,
+};
+use crate::domain::newsletter::models::translation::IssueTranslation;
 
-impl PostgresDb {}
+#[derive(sqlx::FromRow)]
+struct ConfirmedSubscriberRecord {
+    id: uuid::Uuid,
+    email: String,
+    name: String,
+    locale: Option<String>,
+    tags: Vec<String>,
+}
+
+/// Query over the confirmed subscribers of a list who get every issue as it is
+/// published, filtered by `segment`, leaving out blocked addresses. `select` is
+/// the part before `FROM`.
+fn confirmed_subscribers_query<'a>(
+    select: &str,
+    list_id: ListId,
+    segment: &'a Segment,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query = QueryBuilder::new(select);
+    query
+        .push(" FROM subscriptions WHERE list_id = ")
+        .push_bind(list_id)
+        .push(" AND status = ")
+        .push_bind(String::from(SubscriberStatus::SubscriptionConfirmed))
+        .push(" AND frequency = ")
+        .push_bind(DeliveryFrequency::EveryIssue.as_str())
+        .push(" AND (paused_until IS NULL OR paused_until <= now())")
+        .push(" AND NOT EXISTS (SELECT 1 FROM blocked_addresses b WHERE (b.kind = ")
+        .push_bind(BlockedPattern::ADDRESS)
+        .push(" AND b.value = lower(email)) OR (b.kind = ")
+        .push_bind(BlockedPattern::DOMAIN)
+        // The blocked domain is the domain of the address or one of its parents
+        .push(
+            " AND right('.' || split_part(lower(email), '@', -1), length(b.value) + 1) \
+            = '.' || b.value))",
+        );
+    if let Some(expression) = segment.expression() {
+        query.push(" AND ");
+        push_segment(&mut query, expression);
+    }
+    query
+}
+
+/// Writes a segment as a condition on the tags of `subscriptions`, every tag bound
+/// as a parameter
+fn push_segment<'a>(query: &mut QueryBuilder<'a, Postgres>, expression: &'a SegmentExpr) {
+    match expression {
+        SegmentExpr::Tag(tag) => {
+            query
+                .push("EXISTS (SELECT 1 FROM subscription_tags WHERE subscriber_id = subscriptions.id AND tag = ")
+                .push_bind(tag.as_str())
+                .push(")");
+        }
+        SegmentExpr::Not(expression) => {
+            query.push("NOT (");
+            push_segment(query, expression);
+            query.push(")");
+        }
+        SegmentExpr::And(left, right) => push_operation(query, left, " AND ", right),
+        SegmentExpr::Or(left, right) => push_operation(query, left, " OR ", right),
+    }
+}
+
+fn push_operation<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    left: &'a SegmentExpr,
+    operator: &str,
+    right: &'a SegmentExpr,
+) {
+    query.push("(");
+    push_segment(query, left);
+    query.push(operator);
+    push_segment(query, right);
+    query.push(")");
+}
+
+impl PostgresDb {
+    /// How long a dequeued delivery task stays hidden from other workers
+    const DELIVERY_TASK_LEASE: std::time::Duration = std::time::Duration::from_secs(300);
+    /// How long a claimed digest stays hidden from other workers
+    const DIGEST_LEASE: std::time::Duration = std::time::Duration::from_secs(300);
+
+    /// Inserts an issue that is either published now or, when `scheduled_at` is set,
+    /// released later by the scheduler
+    #[tracing::instrument(name = "Insert newsletter issue", skip(transaction, newsletter))]
+    async fn insert_newsletter_issue(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        newsletter: &Newsletter,
+        list_id: ListId,
+        segment: &Segment,
+        scheduled_at: Option<ScheduledAt>,
+        tracking_enabled: bool,
+    ) -> Result<NewsletterIssueId, sqlx::Error> {
+        let newsletter_issue_id = uuid::Uuid::new_v4();
+        // Only published issues have a web version
+        let slug = scheduled_at
+            .is_none()
+            .then(|| IssueSlug::new(&newsletter.title, newsletter_issue_id));
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO newsletter_issues (
+                newsletter_issue_id,
+                title,
+                text_content,
+                html_content,
+                published_at,
+                scheduled_at,
+                slug,
+                segment,
+                list_id,
+                tracking_enabled
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            newsletter_issue_id,
+            newsletter.title.as_str(),
+            newsletter.content.text.as_str(),
+            newsletter.content.html.as_str(),
+            scheduled_at.is_none().then(Utc::now),
+            scheduled_at.map(|s| s.as_datetime()),
+            slug.as_ref().map(|s| s.as_str()),
+            segment.to_stored(),
+            list_id,
+            tracking_enabled,
+        );
+        transaction.execute(query).await?;
+        Ok(newsletter_issue_id)
+    }
+
+    #[tracing::instrument(name = "Get newsletter issue translations", skip(self))]
+    async fn fetch_translations(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Vec<IssueTranslation>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT locale, title, text_content, html_content, markdown_content
+            FROM newsletter_issue_translations
+            WHERE newsletter_issue_id = $1
+            ORDER BY locale
+            "#,
+            newsletter_issue_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the translations of a newsletter issue")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(IssueTranslation {
+                    locale: Locale::parse(&r.locale)
+                        .map_err(|e| NewsletterError::ValidationError(e.to_string()))?,
+                    newsletter: Newsletter::parse_stored(r.title, r.html_content, r.text_content)?,
+                    markdown: r.markdown_content,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Enqueue delivery tasks", skip(transaction, recipients))]
+    async fn enqueue_delivery_tasks(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        newsletter_issue_id: NewsletterIssueId,
+        recipients: &[SubscriberEmail],
+    ) -> Result<(), sqlx::Error> {
+        let recipients: Vec<String> = recipients.iter().map(|r| r.to_string()).collect();
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO issue_delivery_queue (newsletter_issue_id, subscriber_email)
+            SELECT $1, subscriber_email FROM UNNEST($2::text[]) AS subscriber_email
+            ON CONFLICT DO NOTHING
+            "#,
+            newsletter_issue_id,
+            &recipients,
+        );
+        transaction.execute(query).await?;
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO issue_delivery_log (newsletter_issue_id, subscriber_email, status, queued_at)
+            SELECT $1, subscriber_email, $3, now() FROM UNNEST($2::text[]) AS subscriber_email
+            ON CONFLICT DO NOTHING
+            "#,
+            newsletter_issue_id,
+            &recipients,
+            DeliveryStatus::Queued.as_str(),
+        );
+        transaction.execute(query).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Record delivery attempt", skip(transaction, last_error))]
+    async fn record_delivery_attempt(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        task: &DeliveryTask,
+        status: DeliveryStatus,
+        last_error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let query = sqlx::query!(
+            r#"
+            UPDATE issue_delivery_log
+            SET status = $3,
+                attempts = attempts + 1,
+                last_error = COALESCE($4, last_error),
+                last_attempted_at = now(),
+                sent_at = CASE WHEN $3 = $5 THEN now() ELSE sent_at END
+            WHERE newsletter_issue_id = $1 AND subscriber_email = $2
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+            status.as_str(),
+            last_error,
+            DeliveryStatus::Sent.as_str(),
+        );
+        transaction.execute(query).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Store dead letter", skip(transaction, error))]
+    async fn insert_dead_letter(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        task: &DeliveryTask,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO issue_delivery_dead_letters (
+                newsletter_issue_id,
+                subscriber_email,
+                last_error,
+                failed_at
+            )
+            VALUES ($1, $2, $3, now())
+            ON CONFLICT (newsletter_issue_id, subscriber_email) DO UPDATE
+            SET last_error = EXCLUDED.last_error, failed_at = EXCLUDED.failed_at
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+            error,
+        );
+        transaction.execute(query).await?;
+        Ok(())
+    }
+}
 
 #[async_trait]
 impl NewsletterRepository for PostgresDb {
     #[tracing::instrument(name = "Get confirmed subscribers", skip(self))]
     async fn get_confirmed_subscribers(
         &self,
-    ) -> Result<Vec<Result<(ConfirmedSubscriber, SubscriptionToken), NewsletterError>>, anyhow::Error>
-    {
-        // Fetch confirmed subscribers from the database
-        let confirmed_subscribers = sqlx::query!(
-            r#"SELECT email, name, status, id FROM subscriptions WHERE status = $1"#,
-            String::from(SubscriberStatus::SubscriptionConfirmed),
+        list_id: ListId,
+        segment: &Segment,
+    ) -> Result<Vec<Result<ConfirmedSubscriber, NewsletterError>>, anyhow::Error> {
+        let confirmed_subscribers: Vec<ConfirmedSubscriberRecord> = confirmed_subscribers_query(
+            r#"SELECT email, name, id, locale,
+                ARRAY(SELECT tag FROM subscription_tags WHERE subscriber_id = subscriptions.id) AS tags"#,
+            list_id,
+            segment,
         )
+        .build_query_as()
         .fetch_all(&self.pool)
         .await?;
 
-        let results: Vec<Result<(ConfirmedSubscriber, SubscriptionToken), NewsletterError>> =
-            stream::iter(confirmed_subscribers)
-                .then(|r| async move {
-                    match SubscriberStatus::parse(&r.status) {
-                        Ok(SubscriberStatus::SubscriptionConfirmed) => {
-                            let name = SubscriberName::parse(r.name)?;
-                            let email = SubscriberEmail::parse(r.email)?;
-                            let confirmed_subscriber = NewSubscriber::build(name, email)
-                                .with_id(Some(r.id))
-                                .with_status(SubscriberStatus::SubscriptionConfirmed);
-                            let subscriber_id = r.id;
-
-                            let token = self.get_token_from_subscriber_id(subscriber_id).await?;
-
-                            Ok((
-                                ConfirmedSubscriber::new(confirmed_subscriber).unwrap(),
-                                token,
-                            ))
-                        }
-                        Err(error) => Err(NewsletterError::ValidationError(error.to_string())),
-                        _ => Err(NewsletterError::NotFound(
-                            "No confirmed subscribers found".to_string(),
-                        )),
-                    }
-                })
-                .collect()
-                .await; // Collect the results
+        let results = confirmed_subscribers
+            .into_iter()
+            .map(|r| {
+                let name = SubscriberName::parse(r.name)?;
+                let email = SubscriberEmail::parse(r.email)?;
+                let locale = r
+                    .locale
+                    .as_deref()
+                    .map(Locale::parse)
+                    .transpose()
+                    .map_err(|e| NewsletterError::ValidationError(e.to_string()))?;
+                // Tags are validated when they are stored: one that no longer parses
+                // is left out
+                let tags = r
+                    .tags
+                    .iter()
+                    .filter_map(|tag| SubscriberTag::parse(tag).ok())
+                    .collect();
+                let confirmed_subscriber = NewSubscriber::build(name, email)
+                    .with_id(Some(r.id))
+                    .with_status(SubscriberStatus::SubscriptionConfirmed)
+                    .with_locale(locale)
+                    .with_tags(tags);
+
+                ConfirmedSubscriber::new(confirmed_subscriber)
+                    .map_err(NewsletterError::ValidationError)
+            })
+            .collect();
 
         Ok(results)
     }
+
+    #[tracing::instrument(name = "Count confirmed subscribers", skip(self))]
+    async fn count_confirmed_subscribers(
+        &self,
+        list_id: ListId,
+        segment: &Segment,
+    ) -> Result<usize, NewsletterError> {
+        let (count,): (i64,) = confirmed_subscribers_query("SELECT COUNT(*)", list_id, segment)
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count confirmed subscribers")?;
+        Ok(count as usize)
+    }
+
+    #[tracing::instrument(name = "Get confirmed subscriber", skip(self))]
+    async fn get_confirmed_subscriber(
+        &self,
+        list_id: ListId,
+        email: &SubscriberEmail,
+    ) -> Result<Option<ConfirmedSubscriber>, NewsletterError> {
+        let record = sqlx::query!(
+            r#"
+            SELECT id, name, email, locale
+            FROM subscriptions
+            WHERE list_id = $1 AND email = $2 AND status = $3
+            "#,
+            list_id,
+            email.as_str(),
+            String::from(SubscriberStatus::SubscriptionConfirmed),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a confirmed subscriber")?;
+
+        let Some(r) = record else {
+            return Ok(None);
+        };
+        let name = SubscriberName::parse(r.name)?;
+        let email = SubscriberEmail::parse(r.email)?;
+        let locale = r
+            .locale
+            .as_deref()
+            .map(Locale::parse)
+            .transpose()
+            .map_err(|e| NewsletterError::ValidationError(e.to_string()))?;
+        let subscriber = NewSubscriber::build(name, email)
+            .with_id(Some(r.id))
+            .with_status(SubscriberStatus::SubscriptionConfirmed)
+            .with_locale(locale);
+
+        Ok(Some(
+            ConfirmedSubscriber::new(subscriber).map_err(NewsletterError::ValidationError)?,
+        ))
+    }
+
+    async fn issue_link_tokens(
+        &self,
+        subscriber: &ConfirmedSubscriber,
+    ) -> Result<LinkTokens, NewsletterError> {
+        let tokens = PostgresDb::issue_link_tokens(self, subscriber.id())
+            .await
+            .context("Failed to store the link tokens of a subscriber")?;
+        Ok(tokens)
+    }
+
+    #[tracing::instrument(
+        name = "Add newsletter issue and enqueue delivery tasks",
+        skip(self, newsletter, recipients, response)
+    )]
+    async fn add_issue_and_enqueue_delivery_tasks(
+        &self,
+        newsletter: &Newsletter,
+        list_id: ListId,
+        segment: &Segment,
+        tracking_enabled: bool,
+        recipients: &[SubscriberEmail],
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let newsletter_issue_id = self
+            .insert_newsletter_issue(
+                &mut transaction,
+                newsletter,
+                list_id,
+                segment,
+                None,
+                tracking_enabled,
+            )
+            .await
+            .context("Failed to store newsletter issue details")?;
+        self.enqueue_delivery_tasks(&mut transaction, newsletter_issue_id, recipients)
+            .await
+            .context("Failed to enqueue delivery tasks")?;
+        if !self
+            .save_idempotent_response(&mut transaction, response)
+            .await?
+        {
+            return Err(already_published(response));
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to store a newsletter issue")?;
+
+        Ok(newsletter_issue_id)
+    }
+
+    #[tracing::instrument(
+        name = "Add scheduled newsletter issue",
+        skip(self, newsletter, response)
+    )]
+    async fn add_scheduled_issue(
+        &self,
+        newsletter: &Newsletter,
+        list_id: ListId,
+        segment: &Segment,
+        scheduled_at: ScheduledAt,
+        tracking_enabled: bool,
+        response: &IdempotentResponse,
+    ) -> Result<NewsletterIssueId, NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let newsletter_issue_id = self
+            .insert_newsletter_issue(
+                &mut transaction,
+                newsletter,
+                list_id,
+                segment,
+                Some(scheduled_at),
+                tracking_enabled,
+            )
+            .await
+            .context("Failed to store newsletter issue details")?;
+        if !self
+            .save_idempotent_response(&mut transaction, response)
+            .await?
+        {
+            return Err(already_published(response));
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to store a newsletter issue")?;
+
+        Ok(newsletter_issue_id)
+    }
+
+    #[tracing::instrument(name = "Get scheduled newsletter issues", skip(self))]
+    async fn get_scheduled_issues(&self) -> Result<Vec<ScheduledIssue>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT newsletter_issue_id, title, text_content, html_content, segment,
+                list_id AS "list_id!", scheduled_at AS "scheduled_at!"
+            FROM newsletter_issues
+            WHERE published_at IS NULL AND scheduled_at IS NOT NULL
+            ORDER BY scheduled_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve scheduled newsletter issues")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(ScheduledIssue {
+                    newsletter_issue_id: r.newsletter_issue_id,
+                    newsletter: Newsletter::parse_stored(r.title, r.html_content, r.text_content)?,
+                    list_id: r.list_id,
+                    segment: Segment::parse_stored(r.segment.as_deref())?,
+                    scheduled_at: r.scheduled_at.into(),
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Get scheduled newsletter issue", skip(self))]
+    async fn get_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<ScheduledIssue, NewsletterError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT title, text_content, html_content, segment, list_id AS "list_id!",
+                scheduled_at AS "scheduled_at!"
+            FROM newsletter_issues
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NOT NULL
+            "#,
+            newsletter_issue_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a scheduled newsletter issue")?
+        .ok_or_else(|| scheduled_issue_not_found(newsletter_issue_id))?;
+
+        Ok(ScheduledIssue {
+            newsletter_issue_id,
+            newsletter: Newsletter::parse_stored(r.title, r.html_content, r.text_content)?,
+            list_id: r.list_id,
+            segment: Segment::parse_stored(r.segment.as_deref())?,
+            scheduled_at: r.scheduled_at.into(),
+        })
+    }
+
+    #[tracing::instrument(name = "Update scheduled newsletter issue", skip(self, newsletter))]
+    async fn update_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        newsletter: &Newsletter,
+        segment: &Segment,
+        scheduled_at: ScheduledAt,
+    ) -> Result<(), NewsletterError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE newsletter_issues
+            SET title = $2, text_content = $3, html_content = $4, scheduled_at = $5, segment = $6
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NOT NULL
+            "#,
+            newsletter_issue_id,
+            newsletter.title.as_str(),
+            newsletter.content.text.as_str(),
+            newsletter.content.html.as_str(),
+            scheduled_at.as_datetime(),
+            segment.to_stored(),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to update a scheduled newsletter issue")?;
+
+        if result.rows_affected() == 0 {
+            return Err(scheduled_issue_not_found(newsletter_issue_id));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Delete scheduled newsletter issue", skip(self))]
+    async fn delete_scheduled_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM newsletter_issues
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NOT NULL
+            "#,
+            newsletter_issue_id,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to delete a scheduled newsletter issue")?;
+
+        if result.rows_affected() == 0 {
+            return Err(scheduled_issue_not_found(newsletter_issue_id));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get due scheduled newsletter issue", skip(self))]
+    async fn get_due_scheduled_issue(
+        &self,
+    ) -> Result<Option<(NewsletterIssueId, ListId, Segment)>, NewsletterError> {
+        let record = sqlx::query!(
+            r#"
+            SELECT newsletter_issue_id, list_id AS "list_id!", segment
+            FROM newsletter_issues
+            WHERE published_at IS NULL AND scheduled_at <= now()
+            ORDER BY scheduled_at
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a due newsletter issue")?;
+
+        record
+            .map(|r| {
+                Ok((
+                    r.newsletter_issue_id,
+                    r.list_id,
+                    Segment::parse_stored(r.segment.as_deref())?,
+                ))
+            })
+            .transpose()
+    }
+
+    #[tracing::instrument(name = "Release newsletter issue", skip(self, recipients))]
+    async fn release_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        list_id: ListId,
+        segment: &Segment,
+        recipients: &[SubscriberEmail],
+    ) -> Result<bool, NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let record = sqlx::query!(
+            r#"
+            SELECT title
+            FROM newsletter_issues
+            WHERE newsletter_issue_id = $1 AND published_at IS NULL
+            FOR UPDATE
+            "#,
+            newsletter_issue_id,
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to retrieve an unpublished newsletter issue")?;
+        let Some(record) = record else {
+            return Ok(false);
+        };
+        let title =
+            NewsletterTitle::parse(record.title).map_err(NewsletterError::ValidationError)?;
+        let slug = IssueSlug::new(&title, newsletter_issue_id);
+        let query = sqlx::query!(
+            r#"
+            UPDATE newsletter_issues
+            SET published_at = now(), slug = $2, segment = $3, list_id = $4
+            WHERE newsletter_issue_id = $1
+            "#,
+            newsletter_issue_id,
+            slug.as_str(),
+            segment.to_stored(),
+            list_id,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to publish a newsletter issue")?;
+        self.enqueue_delivery_tasks(&mut transaction, newsletter_issue_id, recipients)
+            .await
+            .context("Failed to enqueue delivery tasks")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to release a newsletter issue")?;
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(name = "Add newsletter draft", skip(self, draft))]
+    async fn add_draft(&self, draft: &DraftContent) -> Result<NewsletterIssueId, NewsletterError> {
+        let newsletter_issue_id = uuid::Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO newsletter_issues (
+                newsletter_issue_id,
+                title,
+                text_content,
+                html_content,
+                markdown_content,
+                tracking_enabled,
+                updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, now())
+            "#,
+            newsletter_issue_id,
+            draft.title.as_str(),
+            draft.text,
+            draft.html,
+            draft.markdown,
+            draft.tracking_enabled,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to store a newsletter draft")?;
+
+        Ok(newsletter_issue_id)
+    }
+
+    #[tracing::instrument(name = "Get newsletter drafts", skip(self))]
+    async fn get_drafts(&self) -> Result<Vec<Draft>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                newsletter_issue_id,
+                title,
+                text_content,
+                html_content,
+                markdown_content,
+                tracking_enabled,
+                updated_at AS "updated_at!"
+            FROM newsletter_issues
+            WHERE published_at IS NULL AND scheduled_at IS NULL
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve newsletter drafts")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(Draft {
+                    newsletter_issue_id: r.newsletter_issue_id,
+                    content: DraftContent {
+                        title: NewsletterTitle::parse(r.title)
+                            .map_err(NewsletterError::ValidationError)?,
+                        text: r.text_content,
+                        html: r.html_content,
+                        markdown: r.markdown_content,
+                        tracking_enabled: r.tracking_enabled,
+                    },
+                    updated_at: r.updated_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Get newsletter draft", skip(self))]
+    async fn get_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Draft, NewsletterError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT title, text_content, html_content, markdown_content, tracking_enabled,
+                updated_at AS "updated_at!"
+            FROM newsletter_issues
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NULL
+            "#,
+            newsletter_issue_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a newsletter draft")?
+        .ok_or_else(|| draft_not_found(newsletter_issue_id))?;
+
+        Ok(Draft {
+            newsletter_issue_id,
+            content: DraftContent {
+                title: NewsletterTitle::parse(r.title).map_err(NewsletterError::ValidationError)?,
+                text: r.text_content,
+                html: r.html_content,
+                markdown: r.markdown_content,
+                tracking_enabled: r.tracking_enabled,
+            },
+            updated_at: r.updated_at,
+        })
+    }
+
+    #[tracing::instrument(name = "Update newsletter draft", skip(self, draft))]
+    async fn update_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        draft: &DraftContent,
+    ) -> Result<(), NewsletterError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE newsletter_issues
+            SET
+                title = $2,
+                text_content = $3,
+                html_content = $4,
+                markdown_content = $5,
+                tracking_enabled = $6,
+                updated_at = now()
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NULL
+            "#,
+            newsletter_issue_id,
+            draft.title.as_str(),
+            draft.text,
+            draft.html,
+            draft.markdown,
+            draft.tracking_enabled,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to update a newsletter draft")?;
+
+        if result.rows_affected() == 0 {
+            return Err(draft_not_found(newsletter_issue_id));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Delete newsletter draft", skip(self))]
+    async fn delete_draft(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<(), NewsletterError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM newsletter_issues
+            WHERE newsletter_issue_id = $1
+                AND published_at IS NULL
+                AND scheduled_at IS NULL
+            "#,
+            newsletter_issue_id,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to delete a newsletter draft")?;
+
+        if result.rows_affected() == 0 {
+            return Err(draft_not_found(newsletter_issue_id));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get published newsletter issue", skip(self))]
+    async fn get_published_issue(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<PublishedIssue, NewsletterError> {
+        let issue = sqlx::query!(
+            r#"
+            SELECT title, text_content, html_content, slug AS "slug!", list_id AS "list_id!",
+                segment, tracking_enabled
+            FROM newsletter_issues
+            WHERE newsletter_issue_id = $1 AND published_at IS NOT NULL
+            "#,
+            newsletter_issue_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a newsletter issue")?
+        .ok_or_else(|| {
+            NewsletterError::NotFound(format!(
+                "Newsletter issue with id {} not found",
+                newsletter_issue_id
+            ))
+        })?;
+
+        Ok(PublishedIssue {
+            newsletter_issue_id,
+            newsletter: Newsletter::parse_stored(
+                issue.title,
+                issue.html_content,
+                issue.text_content,
+            )?,
+            list_id: issue.list_id,
+            segment: Segment::parse_stored(issue.segment.as_deref())?,
+            slug: IssueSlug::parse(issue.slug)?,
+            translations: self.fetch_translations(newsletter_issue_id).await?,
+            tracking_enabled: issue.tracking_enabled,
+        })
+    }
+
+    #[tracing::instrument(name = "Claim due weekly digest", skip(self))]
+    async fn claim_due_digest(&self) -> Result<Option<DueDigest>, NewsletterError> {
+        // Issues published while the subscription was paused are not part of the digest
+        let record = sqlx::query!(
+            r#"
+            WITH due AS (
+                SELECT id,
+                    GREATEST(COALESCE(last_digest_at, subscribed_at), paused_until) AS since
+                FROM subscriptions
+                WHERE status = $1
+                    AND frequency = $2
+                    AND (paused_until IS NULL OR paused_until <= now())
+                    AND (digest_locked_until IS NULL OR digest_locked_until < now())
+                    AND COALESCE(last_digest_at, subscribed_at) <= now() - $3::interval
+                ORDER BY COALESCE(last_digest_at, subscribed_at)
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE subscriptions s
+            SET digest_locked_until = now() + make_interval(secs => $4)
+            FROM due
+            WHERE s.id = due.id
+            RETURNING s.id, s.email, s.name, s.locale, s.list_id, due.since AS "since!",
+                now() AS "claimed_at!",
+                ARRAY(SELECT tag FROM subscription_tags WHERE subscriber_id = s.id) AS "tags!"
+            "#,
+            String::from(SubscriberStatus::SubscriptionConfirmed),
+            DeliveryFrequency::WeeklyDigest.as_str(),
+            sqlx::postgres::types::PgInterval::try_from(DIGEST_INTERVAL)
+                .map_err(|e| anyhow::anyhow!(e))?,
+            Self::DIGEST_LEASE.as_secs_f64(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to claim a due weekly digest")?;
+
+        let Some(r) = record else {
+            return Ok(None);
+        };
+        let locale = r
+            .locale
+            .as_deref()
+            .map(Locale::parse)
+            .transpose()
+            .map_err(|e| NewsletterError::ValidationError(e.to_string()))?;
+        // Tags are validated when they are stored: one that no longer parses cannot
+        // match a segment
+        let tags = r
+            .tags
+            .iter()
+            .filter_map(|tag| SubscriberTag::parse(tag).ok())
+            .collect();
+        let subscriber = NewSubscriber::build(
+            SubscriberName::parse(r.name)?,
+            SubscriberEmail::parse(r.email)?,
+        )
+        .with_id(Some(r.id))
+        .with_status(SubscriberStatus::SubscriptionConfirmed)
+        .with_locale(locale)
+        .with_tags(tags);
+
+        let subscriber =
+            ConfirmedSubscriber::new(subscriber).map_err(NewsletterError::ValidationError)?;
+        Ok(Some(DueDigest {
+            subscriber,
+            list_id: r.list_id,
+            since: r.since,
+            claimed_at: r.claimed_at,
+        }))
+    }
+
+    #[tracing::instrument(name = "Complete weekly digest", skip(self, digest, issue_ids))]
+    async fn complete_digest(
+        &self,
+        digest: &DueDigest,
+        issue_ids: &[NewsletterIssueId],
+        outcome: &DeliveryOutcome,
+    ) -> Result<(), NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        if let DeliveryOutcome::Failed(error) = outcome {
+            for newsletter_issue_id in issue_ids {
+                let task = DeliveryTask {
+                    newsletter_issue_id: *newsletter_issue_id,
+                    subscriber_email: digest.subscriber.email().clone(),
+                };
+                self.insert_dead_letter(&mut transaction, &task, error)
+                    .await
+                    .context("Failed to store a dead letter")?;
+            }
+        }
+        let query = sqlx::query!(
+            r#"UPDATE subscriptions SET last_digest_at = $2, digest_locked_until = NULL
+            WHERE id = $1"#,
+            digest.subscriber.id(),
+            digest.claimed_at,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to complete a weekly digest")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to complete a weekly digest")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get issues published since", skip(self))]
+    async fn get_issues_published_since(
+        &self,
+        list_id: ListId,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<PublishedIssue>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT newsletter_issue_id, title, text_content, html_content, slug AS "slug!",
+                segment, tracking_enabled
+            FROM newsletter_issues
+            WHERE list_id = $1 AND published_at > $2
+            ORDER BY published_at
+            "#,
+            list_id,
+            since,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the issues published to a list")?;
+
+        let mut issues = Vec::with_capacity(records.len());
+        for r in records {
+            issues.push(PublishedIssue {
+                newsletter_issue_id: r.newsletter_issue_id,
+                newsletter: Newsletter::parse_stored(r.title, r.html_content, r.text_content)?,
+                list_id,
+                segment: Segment::parse_stored(r.segment.as_deref())?,
+                slug: IssueSlug::parse(r.slug)?,
+                translations: self.fetch_translations(r.newsletter_issue_id).await?,
+                tracking_enabled: r.tracking_enabled,
+            });
+        }
+        Ok(issues)
+    }
+
+    #[tracing::instrument(name = "Get newsletter issue title", skip(self))]
+    async fn get_issue_title(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<NewsletterTitle, NewsletterError> {
+        let record = sqlx::query!(
+            r#"SELECT title FROM newsletter_issues WHERE newsletter_issue_id = $1"#,
+            newsletter_issue_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a newsletter issue")?
+        .ok_or_else(|| {
+            NewsletterError::NotFound(format!(
+                "Newsletter issue with id {} not found",
+                newsletter_issue_id
+            ))
+        })?;
+
+        NewsletterTitle::parse(record.title).map_err(NewsletterError::ValidationError)
+    }
+
+    async fn get_translations(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Vec<IssueTranslation>, NewsletterError> {
+        self.fetch_translations(newsletter_issue_id).await
+    }
+
+    #[tracing::instrument(name = "Save newsletter issue translation", skip(self, translation))]
+    async fn save_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        translation: &IssueTranslation,
+    ) -> Result<(), NewsletterError> {
+        let newsletter = &translation.newsletter;
+        sqlx::query!(
+            r#"
+            INSERT INTO newsletter_issue_translations (
+                newsletter_issue_id,
+                locale,
+                title,
+                text_content,
+                html_content,
+                markdown_content,
+                updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, now())
+            ON CONFLICT (newsletter_issue_id, locale) DO UPDATE
+            SET title = EXCLUDED.title,
+                text_content = EXCLUDED.text_content,
+                html_content = EXCLUDED.html_content,
+                markdown_content = EXCLUDED.markdown_content,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            newsletter_issue_id,
+            translation.locale.as_str(),
+            newsletter.title.as_str(),
+            newsletter.content.text.as_str(),
+            newsletter.content.html.as_str(),
+            translation.markdown,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to store a newsletter issue translation")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Delete newsletter issue translation", skip(self))]
+    async fn delete_translation(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        locale: Locale,
+    ) -> Result<(), NewsletterError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM newsletter_issue_translations
+            WHERE newsletter_issue_id = $1 AND locale = $2
+            "#,
+            newsletter_issue_id,
+            locale.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to delete a newsletter issue translation")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get archive entries", skip(self))]
+    async fn get_archive_entries(&self) -> Result<Vec<ArchiveEntry>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT slug AS "slug!", title, published_at AS "published_at!"
+            FROM newsletter_issues
+            WHERE published_at IS NOT NULL AND NOT hidden_from_archive
+            ORDER BY published_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the newsletter archive")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(ArchiveEntry {
+                    slug: IssueSlug::parse(r.slug)?,
+                    title: r.title,
+                    published_at: r.published_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Get archived issue", skip(self))]
+    async fn get_archived_issue(&self, slug: &IssueSlug) -> Result<ArchivedIssue, NewsletterError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT title, html_content, published_at AS "published_at!"
+            FROM newsletter_issues
+            WHERE slug = $1 AND published_at IS NOT NULL AND NOT hidden_from_archive
+            "#,
+            slug.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve an archived issue")?
+        .ok_or_else(|| NewsletterError::NotFound(format!("Archived issue {} not found", slug)))?;
+
+        Ok(ArchivedIssue {
+            slug: slug.clone(),
+            title: r.title,
+            html_content: r.html_content,
+            published_at: r.published_at,
+        })
+    }
+
+    #[tracing::instrument(name = "Set issue visibility in the archive", skip(self))]
+    async fn set_issue_hidden_from_archive(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        hidden: bool,
+    ) -> Result<(), NewsletterError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE newsletter_issues
+            SET hidden_from_archive = $2, updated_at = now()
+            WHERE newsletter_issue_id = $1 AND published_at IS NOT NULL
+            "#,
+            newsletter_issue_id,
+            hidden,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to update the visibility of a newsletter issue")?;
+
+        if result.rows_affected() == 0 {
+            return Err(NewsletterError::NotFound(format!(
+                "Newsletter issue with id {} not found",
+                newsletter_issue_id
+            )));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get feed", skip(self))]
+    async fn get_feed(&self, max_entries: i64) -> Result<Feed, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT slug AS "slug!", title, html_content, published_at AS "published_at!"
+            FROM newsletter_issues
+            WHERE published_at IS NOT NULL AND NOT hidden_from_archive
+            ORDER BY published_at DESC
+            LIMIT $1
+            "#,
+            max_entries,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the newsletter feed")?;
+
+        // Hidden issues are taken into account: hiding an issue changes the feed
+        let last_modified = sqlx::query_scalar!(
+            r#"
+            SELECT MAX(GREATEST(published_at, updated_at))
+            FROM newsletter_issues
+            WHERE published_at IS NOT NULL
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to retrieve the last modification time of the newsletter feed")?;
+
+        let entries = records
+            .into_iter()
+            .map(|r| {
+                Ok(FeedEntry {
+                    slug: IssueSlug::parse(r.slug)?,
+                    title: NewsletterTitle::parse(r.title)
+                        .map_err(NewsletterError::ValidationError)?,
+                    html_content: r.html_content,
+                    published_at: r.published_at,
+                })
+            })
+            .collect::<Result<Vec<_>, NewsletterError>>()?;
+
+        Ok(Feed {
+            entries,
+            last_modified,
+        })
+    }
+
+    #[tracing::instrument(name = "Dequeue delivery task", skip(self))]
+    async fn dequeue_delivery_task(&self) -> Result<Option<DeliveryTask>, NewsletterError> {
+        let record = sqlx::query!(
+            r#"
+            UPDATE issue_delivery_queue
+            SET locked_until = now() + make_interval(secs => $1)
+            WHERE (newsletter_issue_id, subscriber_email) = (
+                SELECT newsletter_issue_id, subscriber_email
+                FROM issue_delivery_queue
+                WHERE locked_until IS NULL OR locked_until < now()
+                FOR UPDATE
+                SKIP LOCKED
+                LIMIT 1
+            )
+            RETURNING newsletter_issue_id, subscriber_email
+            "#,
+            Self::DELIVERY_TASK_LEASE.as_secs_f64(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to dequeue a delivery task")?;
+
+        match record {
+            Some(r) => Ok(Some(DeliveryTask {
+                newsletter_issue_id: r.newsletter_issue_id,
+                subscriber_email: SubscriberEmail::parse(r.subscriber_email)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(name = "Complete delivery task", skip(self))]
+    async fn complete_delivery_task(
+        &self,
+        task: &DeliveryTask,
+        outcome: &DeliveryOutcome,
+    ) -> Result<(), NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        match outcome {
+            DeliveryOutcome::Sent => {
+                self.record_delivery_attempt(&mut transaction, task, DeliveryStatus::Sent, None)
+                    .await
+            }
+            DeliveryOutcome::Skipped(reason) => {
+                let query = sqlx::query!(
+                    r#"
+                    UPDATE issue_delivery_log
+                    SET status = $3, last_error = $4
+                    WHERE newsletter_issue_id = $1 AND subscriber_email = $2
+                    "#,
+                    task.newsletter_issue_id,
+                    task.subscriber_email.as_str(),
+                    DeliveryStatus::Skipped.as_str(),
+                    reason,
+                );
+                transaction
+                    .execute(query)
+                    .await
+                    .context("Failed to update the delivery log")?;
+                // Nothing was sent, so the links issued for the recipient lead nowhere
+                let query = sqlx::query!(
+                    r#"
+                    DELETE FROM newsletter_tracking_links
+                    WHERE newsletter_issue_id = $1 AND subscriber_id IN (
+                        SELECT id FROM subscriptions WHERE email = $2
+                    )
+                    "#,
+                    task.newsletter_issue_id,
+                    task.subscriber_email.as_str(),
+                );
+                transaction.execute(query).await.map(|_| ())
+            }
+            DeliveryOutcome::Failed(error) => {
+                self.insert_dead_letter(&mut transaction, task, error)
+                    .await
+                    .context("Failed to store a dead letter")?;
+                self.record_delivery_attempt(
+                    &mut transaction,
+                    task,
+                    DeliveryStatus::Failed,
+                    Some(error),
+                )
+                .await
+            }
+        }
+        .context("Failed to update the delivery log")?;
+        let query = sqlx::query!(
+            r#"
+            DELETE FROM issue_delivery_queue
+            WHERE newsletter_issue_id = $1 AND subscriber_email = $2
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to delete a delivery task")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to complete a delivery task")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get dead letters", skip(self))]
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT d.newsletter_issue_id, i.title, d.subscriber_email, d.last_error, d.failed_at
+            FROM issue_delivery_dead_letters d
+            JOIN newsletter_issues i ON i.newsletter_issue_id = d.newsletter_issue_id
+            ORDER BY d.failed_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve dead letters")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(DeadLetter {
+                    newsletter_issue_id: r.newsletter_issue_id,
+                    newsletter_title: r.title,
+                    subscriber_email: SubscriberEmail::parse(r.subscriber_email)?,
+                    last_error: r.last_error,
+                    failed_at: r.failed_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Replay dead letter", skip(self))]
+    async fn replay_dead_letter(&self, task: &DeliveryTask) -> Result<(), NewsletterError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let query = sqlx::query!(
+            r#"
+            DELETE FROM issue_delivery_dead_letters
+            WHERE newsletter_issue_id = $1 AND subscriber_email = $2
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+        );
+        let result = transaction
+            .execute(query)
+            .await
+            .context("Failed to delete a dead letter")?;
+        if result.rows_affected() == 0 {
+            return Err(NewsletterError::NotFound(format!(
+                "No dead letter for issue {} and subscriber {}",
+                task.newsletter_issue_id, task.subscriber_email
+            )));
+        }
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO issue_delivery_queue (newsletter_issue_id, subscriber_email)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to enqueue a delivery task")?;
+        let query = sqlx::query!(
+            r#"
+            UPDATE issue_delivery_log
+            SET status = $3
+            WHERE newsletter_issue_id = $1 AND subscriber_email = $2
+            "#,
+            task.newsletter_issue_id,
+            task.subscriber_email.as_str(),
+            DeliveryStatus::Queued.as_str(),
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to update the delivery log")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to replay a dead letter")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get issue summaries", skip(self))]
+    async fn get_issue_summaries(&self) -> Result<Vec<IssueSummary>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                i.newsletter_issue_id,
+                i.title,
+                i.published_at AS "published_at!",
+                i.slug AS "slug!",
+                i.hidden_from_archive,
+                i.segment,
+                i.tracking_enabled,
+                li.name AS list_name,
+                COUNT(*) FILTER (WHERE l.status = $1) AS "queued!",
+                COUNT(*) FILTER (WHERE l.status = $2) AS "sent!",
+                COUNT(*) FILTER (WHERE l.status = $3) AS "failed!",
+                COUNT(*) FILTER (WHERE l.status = $4) AS "bounced!",
+                COUNT(*) FILTER (WHERE l.status = $5) AS "skipped!"
+            FROM newsletter_issues i
+            JOIN lists li ON li.list_id = i.list_id
+            LEFT JOIN issue_delivery_log l ON l.newsletter_issue_id = i.newsletter_issue_id
+            WHERE i.published_at IS NOT NULL
+            GROUP BY i.newsletter_issue_id, li.name
+            ORDER BY i.published_at DESC
+            "#,
+            DeliveryStatus::Queued.as_str(),
+            DeliveryStatus::Sent.as_str(),
+            DeliveryStatus::Failed.as_str(),
+            DeliveryStatus::Bounced.as_str(),
+            DeliveryStatus::Skipped.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve newsletter issues")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(IssueSummary {
+                    newsletter_issue_id: r.newsletter_issue_id,
+                    title: r.title,
+                    published_at: r.published_at,
+                    slug: IssueSlug::parse(r.slug)?,
+                    hidden_from_archive: r.hidden_from_archive,
+                    list_name: r.list_name,
+                    segment: Segment::parse_stored(r.segment.as_deref())?,
+                    tracking_enabled: r.tracking_enabled,
+                    totals: DeliveryTotals {
+                        queued: r.queued,
+                        sent: r.sent,
+                        failed: r.failed,
+                        bounced: r.bounced,
+                        skipped: r.skipped,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Get issue summary", skip(self))]
+    async fn get_issue_summary(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<IssueSummary, NewsletterError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT
+                i.newsletter_issue_id,
+                i.title,
+                i.published_at AS "published_at!",
+                i.slug AS "slug!",
+                i.hidden_from_archive,
+                i.segment,
+                i.tracking_enabled,
+                li.name AS list_name,
+                COUNT(*) FILTER (WHERE l.status = $2) AS "queued!",
+                COUNT(*) FILTER (WHERE l.status = $3) AS "sent!",
+                COUNT(*) FILTER (WHERE l.status = $4) AS "failed!",
+                COUNT(*) FILTER (WHERE l.status = $5) AS "bounced!",
+                COUNT(*) FILTER (WHERE l.status = $6) AS "skipped!"
+            FROM newsletter_issues i
+            JOIN lists li ON li.list_id = i.list_id
+            LEFT JOIN issue_delivery_log l ON l.newsletter_issue_id = i.newsletter_issue_id
+            WHERE i.newsletter_issue_id = $1 AND i.published_at IS NOT NULL
+            GROUP BY i.newsletter_issue_id, li.name
+            "#,
+            newsletter_issue_id,
+            DeliveryStatus::Queued.as_str(),
+            DeliveryStatus::Sent.as_str(),
+            DeliveryStatus::Failed.as_str(),
+            DeliveryStatus::Bounced.as_str(),
+            DeliveryStatus::Skipped.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve a newsletter issue")?
+        .ok_or_else(|| {
+            NewsletterError::NotFound(format!(
+                "Newsletter issue with id {} not found",
+                newsletter_issue_id
+            ))
+        })?;
+
+        Ok(IssueSummary {
+            newsletter_issue_id: r.newsletter_issue_id,
+            title: r.title,
+            published_at: r.published_at,
+            slug: IssueSlug::parse(r.slug)?,
+            hidden_from_archive: r.hidden_from_archive,
+            list_name: r.list_name,
+            segment: Segment::parse_stored(r.segment.as_deref())?,
+            tracking_enabled: r.tracking_enabled,
+            totals: DeliveryTotals {
+                queued: r.queued,
+                sent: r.sent,
+                failed: r.failed,
+                bounced: r.bounced,
+                skipped: r.skipped,
+            },
+        })
+    }
+
+    #[tracing::instrument(name = "Get undelivered recipients", skip(self))]
+    async fn get_undelivered_recipients(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<Vec<DeliveryLogEntry>, NewsletterError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                subscriber_email,
+                status,
+                attempts,
+                last_error,
+                queued_at,
+                last_attempted_at,
+                sent_at
+            FROM issue_delivery_log
+            WHERE newsletter_issue_id = $1 AND status IN ($2, $3)
+            ORDER BY subscriber_email
+            "#,
+            newsletter_issue_id,
+            DeliveryStatus::Failed.as_str(),
+            DeliveryStatus::Bounced.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the delivery log")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(DeliveryLogEntry {
+                    subscriber_email: SubscriberEmail::parse(r.subscriber_email)?,
+                    status: DeliveryStatus::parse(&r.status)?,
+                    attempts: r.attempts,
+                    last_error: r.last_error,
+                    queued_at: r.queued_at,
+                    last_attempted_at: r.last_attempted_at,
+                    sent_at: r.sent_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Issue tracking links", skip(self, recipient, urls))]
+    async fn issue_tracking_links(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+        recipient: &ConfirmedSubscriber,
+        urls: &[String],
+    ) -> Result<TrackingLinks, NewsletterError> {
+        // The open pixel is the link without a URL
+        let urls: Vec<Option<String>> = std::iter::once(None)
+            .chain(urls.iter().cloned().map(Some))
+            .collect();
+        let tracking_ids: Vec<TrackingId> = urls.iter().map(|_| uuid::Uuid::new_v4()).collect();
+        let records = sqlx::query!(
+            r#"
+            WITH inserted AS (
+                INSERT INTO newsletter_tracking_links
+                    (tracking_id, newsletter_issue_id, subscriber_id, url)
+                SELECT tracking_id, $1, $2, url
+                FROM UNNEST($3::uuid[], $4::text[]) AS link (tracking_id, url)
+                ON CONFLICT (newsletter_issue_id, subscriber_id, url) DO NOTHING
+                RETURNING tracking_id, url
+            )
+            SELECT tracking_id AS "tracking_id!", url FROM inserted
+            UNION ALL
+            SELECT tracking_id, url FROM newsletter_tracking_links
+            WHERE newsletter_issue_id = $1 AND subscriber_id = $2
+            "#,
+            newsletter_issue_id,
+            recipient.id(),
+            &tracking_ids,
+            &urls as &[Option<String>],
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to store tracking links")?;
+
+        let mut open = None;
+        let mut clicks = HashMap::new();
+        for record in records {
+            match record.url {
+                Some(url) => {
+                    clicks.insert(url, record.tracking_id);
+                }
+                None => open = Some(record.tracking_id),
+            }
+        }
+        let open = open.ok_or_else(|| anyhow::anyhow!("The open pixel was not stored"))?;
+        Ok(TrackingLinks { open, clicks })
+    }
+
+    #[tracing::instrument(name = "Record tracking event", skip(self))]
+    async fn record_tracking_event(
+        &self,
+        tracking_id: TrackingId,
+    ) -> Result<TrackedTarget, NewsletterError> {
+        let record = sqlx::query!(
+            r#"
+            WITH link AS (
+                SELECT tracking_id, url
+                FROM newsletter_tracking_links
+                WHERE tracking_id = $1
+            ), event AS (
+                INSERT INTO newsletter_tracking_events (tracking_id)
+                SELECT tracking_id FROM link
+            )
+            SELECT url FROM link
+            "#,
+            tracking_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to record a tracking event")?
+        .ok_or_else(|| {
+            NewsletterError::NotFound(format!("Tracking link with id {} not found", tracking_id))
+        })?;
+
+        Ok(match record.url {
+            Some(url) => TrackedTarget::Click(url),
+            None => TrackedTarget::Open,
+        })
+    }
+
+    #[tracing::instrument(name = "Get tracking stats", skip(self))]
+    async fn get_tracking_stats(
+        &self,
+        newsletter_issue_id: NewsletterIssueId,
+    ) -> Result<TrackingStats, NewsletterError> {
+        let totals = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE k.url IS NULL) AS "opens!",
+                COUNT(DISTINCT k.subscriber_id) FILTER (WHERE k.url IS NULL)
+                    AS "unique_opens!",
+                COUNT(*) FILTER (WHERE k.url IS NOT NULL) AS "clicks!",
+                COUNT(DISTINCT k.subscriber_id) FILTER (WHERE k.url IS NOT NULL)
+                    AS "unique_clicks!"
+            FROM newsletter_tracking_events e
+            JOIN newsletter_tracking_links k ON k.tracking_id = e.tracking_id
+            WHERE k.newsletter_issue_id = $1
+            "#,
+            newsletter_issue_id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to retrieve the opens and clicks of a newsletter issue")?;
+
+        let links = sqlx::query!(
+            r#"
+            SELECT
+                k.url AS "url!",
+                COUNT(*) AS "clicks!",
+                COUNT(DISTINCT k.subscriber_id) AS "unique_clicks!"
+            FROM newsletter_tracking_events e
+            JOIN newsletter_tracking_links k ON k.tracking_id = e.tracking_id
+            WHERE k.newsletter_issue_id = $1 AND k.url IS NOT NULL
+            GROUP BY k.url
+            ORDER BY COUNT(*) DESC, k.url
+            "#,
+            newsletter_issue_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the clicks on the links of a newsletter issue")?;
+
+        Ok(TrackingStats {
+            opens: totals.opens,
+            unique_opens: totals.unique_opens,
+            clicks: totals.clicks,
+            unique_clicks: totals.unique_clicks,
+            links: links
+                .into_iter()
+                .map(|r| LinkClicks {
+                    url: r.url,
+                    clicks: r.clicks,
+                    unique_clicks: r.unique_clicks,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Another request made with the same idempotency key stored its issue first
+fn already_published(response: &IdempotentResponse) -> NewsletterError {
+    NewsletterError::Conflict(format!(
+        "Idempotency key {} was already used to publish an issue",
+        response.key.as_str()
+    ))
+}
+
+fn scheduled_issue_not_found(newsletter_issue_id: NewsletterIssueId) -> NewsletterError {
+    NewsletterError::NotFound(format!(
+        "Scheduled newsletter issue with id {} not found",
+        newsletter_issue_id
+    ))
+}
+
+fn draft_not_found(newsletter_issue_id: NewsletterIssueId) -> NewsletterError {
+    NewsletterError::NotFound(format!(
+        "Newsletter draft with id {} not found",
+        newsletter_issue_id
+    ))
 }
@@ -1,5 +1,17 @@
 use crate::domain::new_subscriber::errors::SubscriberError;
+use crate::domain::new_subscriber::models::blocklist::{BlockedEntry, BlockedPattern};
+use crate::domain::new_subscriber::models::email::SubscriberEmail;
+use crate::domain::new_subscriber::models::name::SubscriberName;
+use crate::domain::new_subscriber::models::preferences::{
+    DeliveryFrequency, SubscriberPreferences, UpdatedPreferences,
+};
+use crate::domain::new_subscriber::models::retention::RetentionReport;
+use crate::domain::new_subscriber::models::suppression::{
+    SuppressedAddress, Suppression, SuppressionReason,
+};
+use crate::domain::newsletter::models::issue_delivery::DeliveryStatus;
 use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
 
 use super::*;
 
@@ -17,39 +29,30 @@ impl PostgresDb {
         Ok(())
     }
 
-    #[tracing::instrument(name = "Get subscriber_id from token", skip(self, subscription_token))]
-    pub async fn get_subscriber_id_from_token(
+    #[tracing::instrument(name = "Get issued token", skip(self, subscription_token))]
+    pub async fn get_issued_token(
         &self,
         subscription_token: &SubscriptionToken,
-    ) -> Result<SubscriberId, sqlx::Error> {
-        let result = sqlx::query!(
-            r#"SELECT subscriber_id FROM subscription_tokens WHERE subscription_token = $1"#,
-            subscription_token.as_str(),
+    ) -> Result<Option<IssuedToken>, SubscriberError> {
+        let record = sqlx::query!(
+            r#"SELECT subscriber_id, purpose, expires_at, used_at
+            FROM subscription_tokens WHERE token_hash = $1"#,
+            self.hash_token(subscription_token),
         )
         .fetch_optional(&self.pool)
-        .await?;
-
-        Ok(result.map(|r| r.subscriber_id))
-    }
+        .await
+        .context("Failed to retrieve a subscription token")?;
 
-    #[tracing::instrument(
-        name = "Store subscription token in the database",
-        skip(subscription_token, transaction)
-    )]
-    async fn store_token(
-        &self,
-        transaction: &mut Transaction<'_, Postgres>,
-        subscription_token: &SubscriptionToken,
-        subscriber_id: SubscriberId,
-    ) -> Result<(), sqlx::Error> {
-        let query = sqlx::query!(
-            r#"INSERT INTO subscription_tokens (subscription_token, subscriber_id)
-            VALUES ($1, $2)"#,
-            subscription_token.as_str(),
-            subscriber_id.unwrap(),
-        );
-        transaction.execute(query).await?;
-        Ok(())
+        record
+            .map(|r| {
+                Ok(IssuedToken {
+                    subscriber_id: r.subscriber_id,
+                    purpose: TokenPurpose::parse(&r.purpose)?,
+                    expires_at: r.expires_at,
+                    used_at: r.used_at,
+                })
+            })
+            .transpose()
     }
 
     #[tracing::instrument(
@@ -114,54 +117,92 @@ impl SubscriberRepository for PostgresDb {
     )]
     async fn retrieve_or_insert(
         &self,
+        list_id: ListId,
         subscriber_request: NewSubscriberRequest,
         token: SubscriptionToken,
-    ) -> Result<(NewSubscriber, SubscriptionToken), SubscriberError> {
-        let mut new_token = token;
+        resend_cooldown: TimeDelta,
+    ) -> Result<(NewSubscriber, Option<SubscriptionToken>), SubscriberError> {
         let mut new_subscriber: NewSubscriber = subscriber_request.try_into()?;
 
         new_subscriber = self
-            .get_subscriber(new_subscriber)
+            .get_subscriber(list_id, new_subscriber)
             .await
             .context("Failed to check if subscriber existed in db")?;
 
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgress connection from the pool")?;
+
         if let Some(subscriber_id) = new_subscriber.id {
-            new_token = self
-                .get_token_from_subscriber_id(subscriber_id)
-                .await
-                .context("Failed to read token from database")?;
+            if new_subscriber.status != SubscriberStatus::SubscriptionPendingConfirmation {
+                return Ok((new_subscriber, None));
+            }
+            // Claiming the email in a single statement keeps concurrent requests
+            // from sending more than one within the cooldown
+            let claimed = sqlx::query!(
+                r#"UPDATE subscriptions SET last_confirmation_sent_at = now()
+                WHERE id = $1 AND (
+                    last_confirmation_sent_at IS NULL
+                    OR last_confirmation_sent_at <= $2
+                )
+                RETURNING id"#,
+                subscriber_id,
+                Utc::now() - resend_cooldown,
+            )
+            .fetch_optional(&mut *transaction)
+            .await
+            .context("Failed to record a new confirmation email")?;
+            if claimed.is_none() {
+                return Ok((new_subscriber, None));
+            }
+            // The new email comes with a new token, the earlier ones keep
+            // working until they expire
+            self.store_token(
+                &mut *transaction,
+                &token,
+                subscriber_id,
+                TokenPurpose::Confirm,
+            )
+            .await
+            .context("Failed to store a new confirmation token")?;
         } else {
-            let mut transaction = self
-                .pool
-                .begin()
-                .await
-                .context("Failed to acquire a Postgress connection from the pool")?;
-
             new_subscriber = self
-                .insert_new(&mut transaction, new_subscriber)
+                .insert_new(&mut transaction, list_id, new_subscriber)
                 .await
                 .context("Failed to insert a new subscriber in the database")?;
 
-            self.store_token(&mut transaction, &new_token, new_subscriber.id)
-                .await
-                .context("Failed to store the confirmation token for a new subscriber.")?;
-
-            transaction
-                .commit()
-                .await
-                .context("Failed to commit SQL transaction to store a new subscriber")?;
+            self.store_token(
+                &mut *transaction,
+                &token,
+                new_subscriber.id.unwrap(),
+                TokenPurpose::Confirm,
+            )
+            .await
+            .context("Failed to store the confirmation token for a new subscriber.")?;
         }
-        Ok((new_subscriber, new_token))
+
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to store a new subscriber")?;
+        Ok((new_subscriber, Some(token)))
     }
 
     #[tracing::instrument(name = "Update subscriber", skip(subscriber, self))]
     async fn update(&self, subscriber: NewSubscriber) -> Result<(), SubscriberError> {
         let result = sqlx::query!(
-            r#"UPDATE subscriptions SET email = $1, name = $2, status = $3 WHERE id = $4"#,
+            r#"UPDATE subscriptions SET email = $1, name = $2, status = $3,
+                cancellation_requested_at = CASE
+                    WHEN $3 = $5 THEN COALESCE(cancellation_requested_at, now())
+                END
+            WHERE id = $4"#,
             subscriber.email.as_str(),
             subscriber.name.as_str(),
             String::from(subscriber.status),
             subscriber.id,
+            String::from(SubscriberStatus::CancellationPendingConfirmation),
         )
         .execute(&self.pool)
         .await
@@ -182,19 +223,44 @@ impl SubscriberRepository for PostgresDb {
     async fn retrieve_from_token(
         &self,
         token: &SubscriptionToken,
-    ) -> Result<NewSubscriber, SubscriberError> {
-        let id = self
-            .get_subscriber_id_from_token(token)
-            .await
-            .map_err(|e| SubscriberError::Unexpected(anyhow::Error::from(e)))?
-            .ok_or_else(|| SubscriberError::AuthError("Token not found".to_string()))?;
+    ) -> Result<(NewSubscriber, IssuedToken), SubscriberError> {
+        let issued = self
+            .get_issued_token(token)
+            .await?
+            .ok_or_else(|| SubscriberError::AuthError("This link is not valid".to_string()))?;
 
         let subscriber = self
-            .get_subscriber_from_id(id)
+            .get_subscriber_from_id(issued.subscriber_id)
             .await
             .context("Failed retrieving subscriber from a given subscriber id")?;
 
-        Ok(subscriber)
+        Ok((subscriber, issued))
+    }
+
+    #[tracing::instrument(name = "Issue subscription token", skip(self))]
+    async fn issue_token(
+        &self,
+        subscriber_id: uuid::Uuid,
+        purpose: TokenPurpose,
+    ) -> Result<SubscriptionToken, SubscriberError> {
+        let token = SubscriptionToken::new();
+        self.store_token(&self.pool, &token, subscriber_id, purpose)
+            .await
+            .context("Failed to store a subscription token")?;
+        Ok(token)
+    }
+
+    #[tracing::instrument(name = "Mark token as used", skip(token, self))]
+    async fn mark_token_used(&self, token: &SubscriptionToken) -> Result<(), SubscriberError> {
+        sqlx::query!(
+            r#"UPDATE subscription_tokens SET used_at = now()
+            WHERE token_hash = $1 AND used_at IS NULL"#,
+            self.hash_token(token),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark a subscription token as used")?;
+        Ok(())
     }
 
     #[tracing::instrument(name = "Deleting subscriber", skip(subscriber, self))]
@@ -216,4 +282,558 @@ impl SubscriberRepository for PostgresDb {
 
         Ok(())
     }
+
+    #[tracing::instrument(name = "Deleting stale subscribers", skip(self))]
+    async fn delete_stale(
+        &self,
+        subscribed_before: DateTime<Utc>,
+        cancellation_requested_before: DateTime<Utc>,
+    ) -> Result<RetentionReport, SubscriberError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgress connection from the pool")?;
+        let stale = sqlx::query!(
+            r#"SELECT id, status FROM subscriptions
+            WHERE (status = $1 AND subscribed_at < $2)
+                OR (status = $3 AND cancellation_requested_at < $4)
+            FOR UPDATE"#,
+            String::from(SubscriberStatus::SubscriptionPendingConfirmation),
+            subscribed_before,
+            String::from(SubscriberStatus::CancellationPendingConfirmation),
+            cancellation_requested_before,
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .context("Failed to retrieve stale subscribers")?;
+
+        let mut report = RetentionReport::default();
+        let mut ids = Vec::with_capacity(stale.len());
+        for record in stale {
+            match SubscriberStatus::parse(&record.status)? {
+                SubscriberStatus::SubscriptionPendingConfirmation => {
+                    report.unconfirmed_subscriptions += 1
+                }
+                _ => report.pending_cancellations += 1,
+            }
+            ids.push(record.id);
+        }
+
+        let query = sqlx::query!(
+            r#"DELETE FROM subscription_tokens WHERE subscriber_id = ANY($1)"#,
+            &ids,
+        );
+        report.tokens = transaction
+            .execute(query)
+            .await
+            .context("Failed to delete the tokens of stale subscribers")?
+            .rows_affected();
+        let query = sqlx::query!(r#"DELETE FROM subscriptions WHERE id = ANY($1)"#, &ids);
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to delete stale subscribers")?;
+        // Every newsletter issues new link tokens: expired ones would pile up forever
+        let query = sqlx::query!(r#"DELETE FROM subscription_tokens WHERE expires_at < now()"#);
+        report.expired_tokens = transaction
+            .execute(query)
+            .await
+            .context("Failed to delete expired tokens")?
+            .rows_affected();
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to delete stale subscribers")?;
+
+        Ok(report)
+    }
+
+    #[tracing::instrument(name = "Suppress an address", skip(self, suppression))]
+    async fn suppress(&self, suppression: &Suppression) -> Result<u64, SubscriberError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let query = sqlx::query!(
+            r#"INSERT INTO suppressed_addresses (email, reason, description)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (email) DO UPDATE
+            SET reason = EXCLUDED.reason, description = EXCLUDED.description,
+                suppressed_at = now()"#,
+            suppression.email.as_str(),
+            suppression.reason.as_str(),
+            suppression.description,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to record a suppressed address")?;
+        let query = sqlx::query!(
+            r#"UPDATE subscriptions
+            SET status_before_suppression = status, status = $2,
+                suppressed_at = now(), suppression_description = $3
+            WHERE email = $1 AND status = ANY($4)"#,
+            suppression.email.as_str(),
+            String::from(suppression.reason.status()),
+            suppression.description,
+            &[
+                String::from(SubscriberStatus::SubscriptionPendingConfirmation),
+                String::from(SubscriberStatus::SubscriptionConfirmed),
+                String::from(SubscriberStatus::CancellationPendingConfirmation),
+            ],
+        );
+        let result = transaction
+            .execute(query)
+            .await
+            .context("Failed to suppress subscriptions")?;
+        if suppression.reason == SuppressionReason::Bounced {
+            let query = sqlx::query!(
+                r#"UPDATE issue_delivery_log SET status = $2, last_error = $3
+                WHERE subscriber_email = $1 AND status = $4"#,
+                suppression.email.as_str(),
+                DeliveryStatus::Bounced.as_str(),
+                suppression.description,
+                DeliveryStatus::Sent.as_str(),
+            );
+            transaction
+                .execute(query)
+                .await
+                .context("Failed to record bounced deliveries")?;
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to suppress an address")?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(name = "Check the suppressed addresses", skip(self, email))]
+    async fn is_suppressed(&self, email: &SubscriberEmail) -> Result<bool, SubscriberError> {
+        let record = sqlx::query!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM suppressed_addresses WHERE email = $1
+            ) AS "suppressed!""#,
+            email.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check the suppressed addresses")?;
+
+        Ok(record.suppressed)
+    }
+
+    #[tracing::instrument(name = "Retrieve suppressed addresses", skip(self))]
+    async fn retrieve_suppressed(&self) -> Result<Vec<SuppressedAddress>, SubscriberError> {
+        let records = sqlx::query!(
+            r#"SELECT a.email, a.reason, a.description, a.suppressed_at,
+                ARRAY(
+                    SELECT l.name FROM subscriptions s JOIN lists l ON l.list_id = s.list_id
+                    WHERE s.email = a.email AND s.status = ANY($1)
+                    ORDER BY l.name
+                ) AS "lists!"
+            FROM suppressed_addresses a
+            ORDER BY a.suppressed_at DESC, a.email"#,
+            &[
+                String::from(SubscriberStatus::Bounced),
+                String::from(SubscriberStatus::Complained),
+            ],
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read suppressed addresses from database")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(SuppressedAddress {
+                    email: SubscriberEmail::parse(r.email)?,
+                    lists: r
+                        .lists
+                        .iter()
+                        .map(|name| ListName::parse(name))
+                        .collect::<Result<_, _>>()?,
+                    reason: SuppressionReason::parse(&r.reason)
+                        .context("Invalid reason of a suppressed address")?,
+                    description: r.description,
+                    suppressed_at: r.suppressed_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Reinstate a suppressed address", skip(self, email))]
+    async fn reinstate(&self, email: &SubscriberEmail) -> Result<(), SubscriberError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let query = sqlx::query!(
+            r#"DELETE FROM suppressed_addresses WHERE email = $1"#,
+            email.as_str(),
+        );
+        let lifted = transaction
+            .execute(query)
+            .await
+            .context("Failed to lift the suppression of an address")?;
+        if lifted.rows_affected() == 0 {
+            return Err(SubscriberError::NotFound(format!(
+                "Suppressed address {} not found",
+                email.as_str()
+            )));
+        }
+        let query = sqlx::query!(
+            r#"UPDATE subscriptions
+            SET status = status_before_suppression, status_before_suppression = NULL,
+                suppressed_at = NULL, suppression_description = NULL
+            WHERE email = $1 AND status = ANY($2) AND status_before_suppression IS NOT NULL"#,
+            email.as_str(),
+            &[
+                String::from(SubscriberStatus::Bounced),
+                String::from(SubscriberStatus::Complained),
+            ],
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to reinstate suppressed subscriptions")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to reinstate an address")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Check the blocklist", skip(self, email))]
+    async fn is_blocked(&self, email: &SubscriberEmail) -> Result<bool, SubscriberError> {
+        let record = sqlx::query!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM blocked_addresses
+                WHERE (kind = $1 AND value = lower($2)) OR (kind = $3 AND value = ANY($4))
+            ) AS "blocked!""#,
+            BlockedPattern::ADDRESS,
+            email.as_str(),
+            BlockedPattern::DOMAIN,
+            &BlockedPattern::blocking_domains(email),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check the blocklist")?;
+
+        Ok(record.blocked)
+    }
+
+    #[tracing::instrument(name = "Add to the blocklist", skip(self))]
+    async fn add_blocked(
+        &self,
+        pattern: &BlockedPattern,
+        reason: &str,
+    ) -> Result<(), SubscriberError> {
+        sqlx::query!(
+            r#"INSERT INTO blocked_addresses (blocked_id, kind, value, reason)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, value) DO UPDATE SET reason = EXCLUDED.reason"#,
+            uuid::Uuid::new_v4(),
+            pattern.kind(),
+            pattern.value(),
+            reason,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add to the blocklist")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieve the blocklist", skip(self))]
+    async fn retrieve_blocklist(&self) -> Result<Vec<BlockedEntry>, SubscriberError> {
+        let records = sqlx::query!(
+            r#"SELECT blocked_id, kind, value, reason, created_at FROM blocked_addresses
+            ORDER BY created_at DESC, value"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read the blocklist from database")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(BlockedEntry {
+                    blocked_id: r.blocked_id,
+                    pattern: BlockedPattern::from_parts(&r.kind, r.value)?,
+                    reason: r.reason,
+                    created_at: r.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Remove from the blocklist", skip(self))]
+    async fn remove_blocked(&self, blocked_id: uuid::Uuid) -> Result<(), SubscriberError> {
+        let result = sqlx::query!(
+            r#"DELETE FROM blocked_addresses WHERE blocked_id = $1"#,
+            blocked_id
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to remove from the blocklist")?;
+
+        if result.rows_affected() == 0 {
+            return Err(SubscriberError::NotFound(format!(
+                "Blocklist entry with id {} not found",
+                blocked_id
+            )));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieve all subscribers of a list", skip(self))]
+    async fn retrieve_all(&self, list_id: ListId) -> Result<Vec<NewSubscriber>, SubscriberError> {
+        let records = sqlx::query!(
+            r#"SELECT id, email, name, status, locale,
+                ARRAY(SELECT tag FROM subscription_tags WHERE subscriber_id = subscriptions.id ORDER BY tag) AS "tags!"
+            FROM subscriptions WHERE list_id = $1 ORDER BY email"#,
+            list_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read subscribers from database")?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                let subscriber: NewSubscriber = NewSubscriberRequest {
+                    email: r.email,
+                    name: r.name,
+                    locale: r.locale,
+                    tags: Some(r.tags.join(",")),
+                    list: None,
+                }
+                .try_into()?;
+                Ok(subscriber
+                    .with_id(Some(r.id))
+                    .with_status(SubscriberStatus::parse(&r.status)?))
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Update subscriber tags", skip(self, tags))]
+    async fn update_tags(
+        &self,
+        subscriber_id: uuid::Uuid,
+        tags: &[SubscriberTag],
+    ) -> Result<(), SubscriberError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgress connection from the pool")?;
+        let result = sqlx::query!(
+            r#"SELECT id FROM subscriptions WHERE id = $1 FOR UPDATE"#,
+            subscriber_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to read subscriber from database")?;
+        if result.is_none() {
+            return Err(SubscriberError::NotFound(format!(
+                "Subscriber with id {} not found",
+                subscriber_id
+            )));
+        }
+
+        let query = sqlx::query!(
+            r#"DELETE FROM subscription_tags WHERE subscriber_id = $1"#,
+            subscriber_id
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to delete subscriber tags")?;
+        self.insert_tags(&mut transaction, subscriber_id, tags)
+            .await
+            .context("Failed to store subscriber tags")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to update subscriber tags")?;
+
+        Ok(())
+    }
+
+    async fn retrieve_preferences(
+        &self,
+        subscriber_id: uuid::Uuid,
+    ) -> Result<SubscriberPreferences, SubscriberError> {
+        let record = sqlx::query!(
+            r#"SELECT email, name, list_id, frequency, paused_until
+            FROM subscriptions WHERE id = $1"#,
+            subscriber_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to retrieve subscriber preferences")?
+        .ok_or_else(|| {
+            SubscriberError::NotFound(format!("Subscriber with id {} not found", subscriber_id))
+        })?;
+        let subscribed_lists: Vec<ListId> = sqlx::query!(
+            r#"SELECT list_id FROM subscriptions WHERE email = $1 AND status = $2"#,
+            record.email,
+            String::from(SubscriberStatus::SubscriptionConfirmed),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to retrieve the lists of a subscriber")?
+        .into_iter()
+        .map(|r| r.list_id)
+        .collect();
+        let lists = self
+            .get_lists()
+            .await?
+            .into_iter()
+            .map(|list| {
+                let subscribed = subscribed_lists.contains(&list.list_id);
+                (list, subscribed)
+            })
+            .collect();
+
+        Ok(SubscriberPreferences {
+            email: SubscriberEmail::parse(record.email)?,
+            name: SubscriberName::parse(record.name)?,
+            list_id: record.list_id,
+            lists,
+            frequency: DeliveryFrequency::parse(&record.frequency)?,
+            paused_until: record.paused_until,
+        })
+    }
+
+    #[tracing::instrument(name = "Update subscriber preferences", skip(self, preferences))]
+    async fn update_preferences(
+        &self,
+        subscriber_id: uuid::Uuid,
+        preferences: &UpdatedPreferences,
+    ) -> Result<(), SubscriberError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+        let record = sqlx::query!(
+            r#"SELECT email, list_id, locale, paused_until
+            FROM subscriptions WHERE id = $1 FOR UPDATE"#,
+            subscriber_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to retrieve a subscriber")?
+        .ok_or_else(|| {
+            SubscriberError::NotFound(format!("Subscriber with id {} not found", subscriber_id))
+        })?;
+        let paused_until = preferences
+            .pause
+            .paused_until(Utc::now(), record.paused_until);
+        // The list of the subscription itself is only left through the unsubscribe link
+        let mut lists = preferences.lists.clone();
+        lists.push(record.list_id);
+        let confirmed = String::from(SubscriberStatus::SubscriptionConfirmed);
+        // Suppressed subscriptions are only given back by reinstating their address
+        let suppressed = [
+            String::from(SubscriberStatus::Bounced),
+            String::from(SubscriberStatus::Complained),
+        ];
+
+        // Switching to the weekly digest starts it from now: earlier issues were sent already
+        let query = sqlx::query!(
+            r#"UPDATE subscriptions
+            SET name = $2,
+                frequency = $3,
+                paused_until = $4,
+                last_digest_at = CASE WHEN frequency = $3 THEN last_digest_at ELSE now() END
+            WHERE email = $1"#,
+            record.email,
+            preferences.name.as_str(),
+            preferences.frequency.as_str(),
+            paused_until,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to update subscriber preferences")?;
+
+        let query = sqlx::query!(
+            r#"DELETE FROM subscription_tokens WHERE subscriber_id IN (
+                SELECT id FROM subscriptions
+                WHERE email = $1 AND id <> $2 AND NOT (list_id = ANY($3))
+                    AND NOT (status = ANY($4))
+            )"#,
+            record.email,
+            subscriber_id,
+            &lists,
+            &suppressed,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to delete the tokens of left subscriptions")?;
+        let query = sqlx::query!(
+            r#"DELETE FROM subscriptions
+            WHERE email = $1 AND id <> $2 AND NOT (list_id = ANY($3))
+                AND NOT (status = ANY($4))"#,
+            record.email,
+            subscriber_id,
+            &lists,
+            &suppressed,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to delete left subscriptions")?;
+
+        // The email address is known to work: subscriptions waiting for a
+        // confirmation are confirmed right away
+        let query = sqlx::query!(
+            r#"UPDATE subscriptions SET status = $3, cancellation_requested_at = NULL
+            WHERE email = $1 AND list_id = ANY($2) AND NOT (status = ANY($4))"#,
+            record.email,
+            &lists,
+            confirmed,
+            &suppressed,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to confirm subscriptions")?;
+        let query = sqlx::query!(
+            r#"INSERT INTO subscriptions (
+                id, email, name, subscribed_at, status, locale, list_id,
+                frequency, paused_until, last_digest_at
+            )
+            SELECT gen_random_uuid(), $1, $2, now(), $3, $4, l.list_id, $5, $6, now()
+            FROM lists l
+            WHERE l.list_id = ANY($7) AND NOT EXISTS (
+                SELECT 1 FROM subscriptions s WHERE s.list_id = l.list_id AND s.email = $1
+            ) AND NOT EXISTS (SELECT 1 FROM suppressed_addresses WHERE email = $1)"#,
+            record.email,
+            preferences.name.as_str(),
+            confirmed,
+            record.locale,
+            preferences.frequency.as_str(),
+            paused_until,
+            &lists,
+        );
+        transaction
+            .execute(query)
+            .await
+            .context("Failed to subscribe to new lists")?;
+
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to update subscriber preferences")?;
+        Ok(())
+    }
 }
@@ -1 +1,2 @@
+pub mod blocklist;
 pub mod email_client;
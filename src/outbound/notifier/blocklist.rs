@@ -0,0 +1,113 @@
+use crate::domain::new_subscriber::{
+    errors::SubscriberError,
+    models::{
+        email::SubscriberEmail,
+        locale::Locale,
+        token::{LinkTokens, SubscriptionToken},
+    },
+    ports::{SubscriberRepository, SubscriptionNotifier},
+};
+use crate::domain::newsletter::{
+    errors::NewsletterError,
+    models::{archive::IssueSlug, draft::NewsletterPreview, newsletter::Newsletter},
+    ports::NewsletterNotifier,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Wraps a notifier so that no email reaches a blocked address, whichever
+/// path sends it. Subscription emails to blocked addresses are dropped without
+/// an error; newsletters and digests fail with `NewsletterError::BlockedRecipient`
+/// so that the delivery is not recorded as sent.
+#[derive(Debug, Clone)]
+pub struct BlocklistNotifier<N, R> {
+    notifier: Arc<N>,
+    repo: Arc<R>,
+}
+
+impl<N, R: SubscriberRepository> BlocklistNotifier<N, R> {
+    pub fn new(notifier: Arc<N>, repo: Arc<R>) -> Self {
+        Self { notifier, repo }
+    }
+
+    async fn is_blocked(&self, recipient: &SubscriberEmail) -> Result<bool, SubscriberError> {
+        let blocked = self.repo.is_blocked(recipient).await?;
+        if blocked {
+            tracing::info!("Dropped an email to a blocked address");
+        }
+        Ok(blocked)
+    }
+}
+
+#[async_trait]
+impl<N, R> SubscriptionNotifier for BlocklistNotifier<N, R>
+where
+    N: SubscriptionNotifier,
+    R: SubscriberRepository,
+{
+    async fn send_subscriber_notification(
+        &self,
+        recipient: &SubscriberEmail,
+        token: SubscriptionToken,
+        locale: Locale,
+    ) -> Result<(), SubscriberError> {
+        if self.is_blocked(recipient).await? {
+            return Ok(());
+        }
+        self.notifier
+            .send_subscriber_notification(recipient, token, locale)
+            .await
+    }
+}
+
+#[async_trait]
+impl<N, R> NewsletterNotifier for BlocklistNotifier<N, R>
+where
+    N: NewsletterNotifier,
+    R: SubscriberRepository,
+{
+    async fn send_newsletter(
+        &self,
+        recipient: &SubscriberEmail,
+        newsletter: &Newsletter,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
+        base_url: &str,
+        locale: Locale,
+    ) -> Result<(), NewsletterError> {
+        if self.is_blocked(recipient).await? {
+            return Err(NewsletterError::BlockedRecipient);
+        }
+        self.notifier
+            .send_newsletter(recipient, newsletter, tokens, web_version, base_url, locale)
+            .await
+    }
+
+    async fn send_digest(
+        &self,
+        recipient: &SubscriberEmail,
+        issues: &[Newsletter],
+        tokens: &LinkTokens,
+        base_url: &str,
+        locale: Locale,
+    ) -> Result<(), NewsletterError> {
+        if self.is_blocked(recipient).await? {
+            return Err(NewsletterError::BlockedRecipient);
+        }
+        self.notifier
+            .send_digest(recipient, issues, tokens, base_url, locale)
+            .await
+    }
+
+    fn render_newsletter(
+        &self,
+        newsletter: &Newsletter,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
+        base_url: &str,
+        locale: Locale,
+    ) -> NewsletterPreview {
+        self.notifier
+            .render_newsletter(newsletter, tokens, web_version, base_url, locale)
+    }
+}
@@ -2,6 +2,7 @@ use crate::configuration::EmailClientSettings;
 use crate::domain::new_subscriber::{
     models::{
         email::{EmailHtmlContent, EmailMessage, EmailSubject, EmailTextContent, SubscriberEmail},
+        locale::Locale,
         token::SubscriptionToken,
     },
     ports::SubscriptionNotifier,
@@ -12,7 +13,12 @@ use reqwest::Client;
 use secrecy::{ExposeSecret, Secret};
 
 mod newsletter_notifier;
+mod retry;
 mod subscriber_notifier;
+mod templates;
+
+use retry::{RetryPolicy, SendError};
+use templates::{EmailTemplate, EmailTemplates, TemplateValue};
 
 #[derive(Debug, Clone)]
 pub struct EmailClient {
@@ -20,6 +26,8 @@ pub struct EmailClient {
     base_url: String,
     sender: SubscriberEmail,
     authorization_token: Secret<String>,
+    retry_policy: RetryPolicy,
+    templates: EmailTemplates,
 }
 
 impl EmailClient {
@@ -28,6 +36,9 @@ impl EmailClient {
             .sender()
             .expect("Invalid sender email address");
         let timeout = configuration.timeout();
+        let retry_policy = RetryPolicy::new(&configuration.retry);
+        let templates = EmailTemplates::load(configuration.templates_directory.as_deref())
+            .expect("Failed to load the email templates");
 
         let http_client = Client::builder().timeout(timeout).build().unwrap();
         Self {
@@ -35,27 +46,60 @@ impl EmailClient {
             base_url: configuration.base_url,
             sender,
             authorization_token: configuration.authorization_token,
+            retry_policy,
+            templates,
         }
     }
 
+    /// Sends the email, retrying transient failures (timeouts, connection errors
+    /// and 5xx responses) with exponential backoff.
     async fn send_notification<'a>(
         &'a self,
         email_request_body: SendEmailRequest<'a>,
     ) -> Result<(), anyhow::Error> {
+        let mut attempt = 0;
+        loop {
+            match self.try_send_notification(&email_request_body).await {
+                Ok(()) => return Ok(()),
+                Err(error)
+                    if error.is_transient() && attempt + 1 < self.retry_policy.max_attempts() =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    tracing::warn!(
+                        error.message = %error,
+                        attempt = attempt + 1,
+                        backoff_milliseconds = backoff.as_millis() as u64,
+                        "Failed to send email, retrying",
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    async fn try_send_notification(
+        &self,
+        email_request_body: &SendEmailRequest<'_>,
+    ) -> Result<(), SendError> {
         let url = format!("{}/email", self.base_url);
-        let _builder = self
+        let response = self
             .http_client
             .post(&url)
             .header(
                 "X-Postmark-Server-Token",
                 self.authorization_token.expose_secret(),
             )
-            .json(&email_request_body)
+            .json(email_request_body)
             .send()
             .await
-            .map_err(anyhow::Error::from)?
+            .map_err(SendError::from_request_error)?;
+
+        let status = response.status();
+        response
             .error_for_status()
-            .map_err(anyhow::Error::from)?;
+            .map_err(|e| SendError::from_status(status, e))?;
 
         Ok(())
     }
@@ -69,4 +113,31 @@ struct SendEmailRequest<'a> {
     subject: &'a str,
     html_body: &'a str,
     text_body: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<EmailHeader>,
+}
+
+/// A custom header of an email, such as `List-Unsubscribe`
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct EmailHeader {
+    name: &'static str,
+    value: String,
+}
+
+impl EmailHeader {
+    /// Headers of bulk emails that let mail clients unsubscribe the recipient with
+    /// a single click (RFC 8058): they POST to `unsubscribe_link`
+    fn one_click_unsubscribe(unsubscribe_link: &str) -> Vec<Self> {
+        vec![
+            EmailHeader {
+                name: "List-Unsubscribe",
+                value: format!("<{}>", unsubscribe_link),
+            },
+            EmailHeader {
+                name: "List-Unsubscribe-Post",
+                value: "List-Unsubscribe=One-Click".to_string(),
+            },
+        ]
+    }
 }
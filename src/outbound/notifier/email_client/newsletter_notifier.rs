@@ -1,7 +1,7 @@
+use crate::domain::new_subscriber::models::token::LinkTokens;
 use crate::domain::newsletter::errors::NewsletterError;
-use crate::domain::newsletter::models::newsletter::{
-    NewsletterBodyWrapper, NewsletterHtmlBody, NewsletterTextBody,
-};
+use crate::domain::newsletter::models::archive::IssueSlug;
+use crate::domain::newsletter::models::draft::NewsletterPreview;
 use async_trait::async_trait;
 
 use super::*;
@@ -10,50 +10,132 @@ use super::*;
 impl NewsletterNotifier for EmailClient {
     #[tracing::instrument(
         name = "Send newsletter to confirmed subscriber",
-        skip(self, recipient, token, newsletter)
+        skip(self, recipient, tokens, newsletter)
     )]
     async fn send_newsletter(
         &self,
         recipient: &SubscriberEmail,
         newsletter: &Newsletter,
-        token: SubscriptionToken,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
         base_url: &str,
+        locale: Locale,
     ) -> Result<(), NewsletterError> {
-        let unsubscribe_link = build_unsubscribe_link(base_url, &token);
-        let html_content = embed_link_to_html_content(&newsletter.content.html, &unsubscribe_link);
-        let text_content = embed_link_to_text_content(&newsletter.content.text, &unsubscribe_link);
-        let subject = newsletter.title.as_str();
+        let rendered = self.render_newsletter(newsletter, tokens, web_version, base_url, locale);
+        let unsubscribe_link = build_unsubscribe_link(base_url, &tokens.unsubscribe);
         let request_body = SendEmailRequest {
             from: self.sender.as_str(),
             to: recipient.as_str(),
-            subject,
-            html_body: html_content.as_ref(),
-            text_body: text_content.as_ref(),
+            subject: &rendered.subject,
+            html_body: &rendered.html,
+            text_body: &rendered.text,
+            headers: EmailHeader::one_click_unsubscribe(&unsubscribe_link),
         };
         self.send_notification(request_body)
             .await
             .map_err(NewsletterError::Unexpected)
     }
-}
 
-fn embed_link_to_text_content(
-    body: &NewsletterBodyWrapper<NewsletterTextBody>,
-    link: &str,
-) -> String {
-    let text_with_link = format!(
-        "\nClick <a href=\"{}\">here</a> to unsubscribe from newsletter.",
-        link
-    );
-    let content_with_link = format!("{} {} ", body.as_str(), text_with_link);
-    content_with_link
-}
-fn embed_link_to_html_content(
-    body: &NewsletterBodyWrapper<NewsletterHtmlBody>,
-    link: &str,
-) -> String {
-    let text_with_link = format!("\nClick here {} to unsubscribe from newsletter.", link);
-    let content_with_link = format!("{} {} ", body.as_str(), text_with_link);
-    content_with_link
+    #[tracing::instrument(
+        name = "Send weekly digest to confirmed subscriber",
+        skip(self, recipient, issues, tokens)
+    )]
+    async fn send_digest(
+        &self,
+        recipient: &SubscriberEmail,
+        issues: &[Newsletter],
+        tokens: &LinkTokens,
+        base_url: &str,
+        locale: Locale,
+    ) -> Result<(), NewsletterError> {
+        let html = issues
+            .iter()
+            .map(|issue| {
+                format!(
+                    "<h1>{}</h1>\n{}",
+                    htmlescape::encode_minimal(issue.title.as_str()),
+                    issue.content.html.as_str()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n<hr>\n");
+        let text = issues
+            .iter()
+            .map(|issue| {
+                format!(
+                    "{}\n\n{}",
+                    issue.title.as_str(),
+                    issue.content.text.as_str()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n----------\n\n");
+        let unsubscribe_link = build_unsubscribe_link(base_url, &tokens.unsubscribe);
+        let preferences_link = build_preferences_link(base_url, &tokens.preferences);
+        let rendered = self.templates.render(
+            EmailTemplate::Digest,
+            locale,
+            &[
+                (
+                    "content",
+                    TemplateValue::Body {
+                        html: &html,
+                        text: &text,
+                    },
+                ),
+                ("unsubscribe_link", TemplateValue::Plain(&unsubscribe_link)),
+                ("preferences_link", TemplateValue::Plain(&preferences_link)),
+            ],
+        );
+        let request_body = SendEmailRequest {
+            from: self.sender.as_str(),
+            to: recipient.as_str(),
+            subject: &rendered.subject,
+            html_body: &rendered.html,
+            text_body: &rendered.text,
+            headers: EmailHeader::one_click_unsubscribe(&unsubscribe_link),
+        };
+        self.send_notification(request_body)
+            .await
+            .map_err(NewsletterError::Unexpected)
+    }
+
+    fn render_newsletter(
+        &self,
+        newsletter: &Newsletter,
+        tokens: &LinkTokens,
+        web_version: Option<&IssueSlug>,
+        base_url: &str,
+        locale: Locale,
+    ) -> NewsletterPreview {
+        let unsubscribe_link = build_unsubscribe_link(base_url, &tokens.unsubscribe);
+        let preferences_link = build_preferences_link(base_url, &tokens.preferences);
+        let web_version_link = web_version
+            .map(|slug| build_web_version_link(base_url, slug))
+            .unwrap_or_default();
+        let rendered = self.templates.render(
+            EmailTemplate::Newsletter,
+            locale,
+            &[
+                ("title", TemplateValue::Plain(newsletter.title.as_str())),
+                (
+                    "content",
+                    TemplateValue::Body {
+                        html: newsletter.content.html.as_str(),
+                        text: newsletter.content.text.as_str(),
+                    },
+                ),
+                ("unsubscribe_link", TemplateValue::Plain(&unsubscribe_link)),
+                ("preferences_link", TemplateValue::Plain(&preferences_link)),
+                ("web_version_link", TemplateValue::Plain(&web_version_link)),
+            ],
+        );
+        NewsletterPreview {
+            subject: rendered.subject,
+            html: rendered.html,
+            text: rendered.text,
+        }
+    }
 }
 
 fn build_unsubscribe_link(base_url: &str, token: &SubscriptionToken) -> String {
@@ -64,3 +146,15 @@ fn build_unsubscribe_link(base_url: &str, token: &SubscriptionToken) -> String {
     );
     unsubscribe_link
 }
+
+fn build_preferences_link(base_url: &str, token: &SubscriptionToken) -> String {
+    format!(
+        "{}/subscriptions/preferences?subscription_token={}",
+        base_url,
+        token.as_str()
+    )
+}
+
+fn build_web_version_link(base_url: &str, slug: &IssueSlug) -> String {
+    format!("{}/newsletters/{}", base_url, slug)
+}
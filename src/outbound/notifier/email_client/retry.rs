@@ -0,0 +1,113 @@
+use crate::configuration::RetrySettings;
+use rand::Rng;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Outcome of a single failed attempt at delivering an email
+#[derive(thiserror::Error, Debug)]
+pub enum SendError {
+    #[error("Transient email delivery failure: {0}")]
+    Transient(anyhow::Error),
+    #[error("Permanent email delivery failure: {0}")]
+    Permanent(anyhow::Error),
+}
+
+impl SendError {
+    pub fn from_status(status: StatusCode, error: reqwest::Error) -> Self {
+        if status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+        {
+            SendError::Transient(error.into())
+        } else {
+            SendError::Permanent(error.into())
+        }
+    }
+
+    pub fn from_request_error(error: reqwest::Error) -> Self {
+        if error.is_timeout() || error.is_connect() || error.is_request() {
+            SendError::Transient(error.into())
+        } else {
+            SendError::Permanent(error.into())
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SendError::Transient(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(settings: &RetrySettings) -> Self {
+        Self {
+            max_attempts: settings.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(settings.initial_backoff_milliseconds),
+            max_backoff: Duration::from_millis(settings.max_backoff_milliseconds),
+        }
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Exponential backoff capped at `max_backoff`, with "equal jitter":
+    /// half of the delay is fixed and the other half is random.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt));
+        let capped = exponential.min(self.max_backoff);
+        let half = capped / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use crate::configuration::RetrySettings;
+    use std::time::Duration;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(&RetrySettings {
+            max_attempts: 5,
+            initial_backoff_milliseconds: 100,
+            max_backoff_milliseconds: 1000,
+        })
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_jitter_bounds() {
+        let policy = policy();
+        for (attempt, expected) in [(0, 100), (1, 200), (2, 400), (3, 800)] {
+            let delay = policy.backoff(attempt);
+            assert!(delay >= Duration::from_millis(expected / 2));
+            assert!(delay <= Duration::from_millis(expected));
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let delay = policy().backoff(30);
+        assert!(delay >= Duration::from_millis(500));
+        assert!(delay <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn at_least_one_attempt_is_made() {
+        let policy = RetryPolicy::new(&RetrySettings {
+            max_attempts: 0,
+            initial_backoff_milliseconds: 100,
+            max_backoff_milliseconds: 1000,
+        });
+        assert_eq!(policy.max_attempts(), 1);
+    }
+}
@@ -7,24 +7,25 @@ impl EmailClient {
     fn build_subscriber_notification(
         &self,
         subscription_token: SubscriptionToken,
+        locale: Locale,
     ) -> Result<EmailMessage, SubscriberError> {
         let confirmation_link = format!(
             "{}/subscriptions/confirm?subscription_token={}",
             self.base_url,
             subscription_token.as_str()
         );
-        let text_content = EmailTextContent::try_from(format!(
-            "Welcome to our newsletter!<br />\
-            Click <a href=\"{}\">here</a> to confirm your subscription.",
-            confirmation_link
-        ))?;
-
-        let html_content = EmailHtmlContent::try_from(format!(
-            "Welcome to our newsletter!\nClick here {} to confirm your subscription.",
-            confirmation_link
-        ))?;
+        let rendered = self.templates.render(
+            EmailTemplate::Confirmation,
+            locale,
+            &[(
+                "confirmation_link",
+                TemplateValue::Plain(&confirmation_link),
+            )],
+        );
+        let html_content = EmailHtmlContent::try_from(rendered.html)?;
+        let text_content = EmailTextContent::try_from(rendered.text)?;
 
-        let subject = EmailSubject::try_from("Welcome")?;
+        let subject = EmailSubject::try_from(rendered.subject.as_str())?;
 
         Ok(EmailMessage::new(subject, html_content, text_content))
     }
@@ -40,8 +41,9 @@ impl SubscriptionNotifier for EmailClient {
         &self,
         recipient: &SubscriberEmail,
         token: SubscriptionToken,
+        locale: Locale,
     ) -> Result<(), SubscriberError> {
-        let message = self.build_subscriber_notification(token)?;
+        let message = self.build_subscriber_notification(token, locale)?;
         let subject = message.subject_as_ref();
         let html_content = message.html_as_ref();
         let text_content = message.text_as_ref();
@@ -51,6 +53,7 @@ impl SubscriptionNotifier for EmailClient {
             subject: subject.as_str(),
             html_body: html_content.as_str(),
             text_body: text_content.as_str(),
+            headers: Vec::new(),
         };
         self.send_notification(request_body)
             .await
@@ -60,8 +63,9 @@ impl SubscriptionNotifier for EmailClient {
 
 #[cfg(test)]
 mod tests {
-    use crate::configuration::EmailClientSettings;
+    use crate::configuration::{EmailClientSettings, RetrySettings};
     use crate::domain::new_subscriber::models::email::SubscriberEmail;
+    use crate::domain::new_subscriber::models::locale::Locale;
     use crate::domain::new_subscriber::models::token::SubscriptionToken;
     use crate::domain::new_subscriber::ports::SubscriptionNotifier;
     use crate::outbound::notifier::email_client::EmailClient;
@@ -82,6 +86,12 @@ mod tests {
             sender_email: email().into(),
             authorization_token: Secret::new(Faker.fake()),
             timeout_milliseconds: 200,
+            retry: RetrySettings {
+                max_attempts: 3,
+                initial_backoff_milliseconds: 10,
+                max_backoff_milliseconds: 20,
+            },
+            templates_directory: None,
         };
         EmailClient::new(configuration)
     }
@@ -120,7 +130,7 @@ mod tests {
         let subscription_token = SubscriptionToken::default();
 
         let _ = email_client
-            .send_subscriber_notification(&email(), subscription_token)
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
             .await;
     }
 
@@ -138,27 +148,27 @@ mod tests {
         let subscription_token = SubscriptionToken::default();
 
         let outcome = email_client
-            .send_subscriber_notification(&email(), subscription_token)
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
             .await;
 
         assert_ok!(outcome);
     }
 
     #[tokio::test]
-    async fn send_email_fails_if_the_server_returns_500() {
+    async fn send_email_fails_if_the_server_keeps_returning_500() {
         let mock_server = MockServer::start().await;
         let email_client = email_client(mock_server.uri());
 
         Mock::given(any())
             .respond_with(ResponseTemplate::new(500))
-            .expect(1)
+            .expect(3)
             .mount(&mock_server)
             .await;
 
         let subscription_token = SubscriptionToken::default();
 
         let outcome = email_client
-            .send_subscriber_notification(&email(), subscription_token)
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
             .await;
 
         assert_err!(outcome);
@@ -173,6 +183,52 @@ mod tests {
         let response = ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(180));
         Mock::given(any())
             .respond_with(response)
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let subscription_token = SubscriptionToken::default();
+
+        let outcome = email_client
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
+            .await;
+
+        assert_err!(outcome);
+    }
+
+    #[tokio::test]
+    async fn send_email_succeeds_if_the_server_recovers_after_a_500() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let subscription_token = SubscriptionToken::default();
+
+        let outcome = email_client
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
+            .await;
+
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn send_email_is_not_retried_if_the_server_returns_400() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(422))
             .expect(1)
             .mount(&mock_server)
             .await;
@@ -180,7 +236,7 @@ mod tests {
         let subscription_token = SubscriptionToken::default();
 
         let outcome = email_client
-            .send_subscriber_notification(&email(), subscription_token)
+            .send_subscriber_notification(&email(), subscription_token, Locale::default())
             .await;
 
         assert_err!(outcome);
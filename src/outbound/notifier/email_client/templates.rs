@@ -0,0 +1,312 @@
+use crate::domain::new_subscriber::models::locale::Locale;
+use anyhow::Context;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_TEMPLATES_DIR: &str = "templates/emails";
+
+/// Transactional emails, each made of a subject, an HTML and a plain text template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailTemplate {
+    Confirmation,
+    Newsletter,
+    Digest,
+}
+
+impl EmailTemplate {
+    const ALL: [EmailTemplate; 3] = [
+        EmailTemplate::Confirmation,
+        EmailTemplate::Newsletter,
+        EmailTemplate::Digest,
+    ];
+
+    fn file_stem(&self) -> &'static str {
+        match self {
+            EmailTemplate::Confirmation => "confirmation",
+            EmailTemplate::Newsletter => "newsletter",
+            EmailTemplate::Digest => "digest",
+        }
+    }
+
+    /// Placeholders both bodies must contain
+    fn required_placeholders(&self) -> &'static [&'static str] {
+        match self {
+            EmailTemplate::Confirmation => &["confirmation_link"],
+            EmailTemplate::Newsletter => &["content", "unsubscribe_link"],
+            EmailTemplate::Digest => &["content", "unsubscribe_link"],
+        }
+    }
+}
+
+/// Value of a placeholder
+pub enum TemplateValue<'a> {
+    /// Text, HTML-escaped in the HTML variant
+    Plain(&'a str),
+    /// Content that already exists in both formats, inserted as is
+    Body { html: &'a str, text: &'a str },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+struct EmailTemplateSource {
+    subject: String,
+    html: String,
+    text: String,
+}
+
+impl EmailTemplateSource {
+    fn new(
+        template: EmailTemplate,
+        subject: String,
+        html: String,
+        text: String,
+    ) -> Result<Self, anyhow::Error> {
+        if subject.trim().is_empty() {
+            anyhow::bail!(
+                "The subject of the {} email template is empty",
+                template.file_stem()
+            );
+        }
+        for (variant, source) in [("HTML", &html), ("text", &text)] {
+            for placeholder in template.required_placeholders() {
+                if !source.contains(&format!("{{{}}}", placeholder)) {
+                    anyhow::bail!(
+                        "The {} variant of the {} email template is missing the {{{}}} placeholder",
+                        variant,
+                        template.file_stem(),
+                        placeholder
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            subject: subject.trim().to_string(),
+            html,
+            text,
+        })
+    }
+}
+
+/// Email templates, read once at startup from `templates/emails/{locale}`.
+/// A deployment can override any of them by putting a file with the same
+/// name in the matching locale folder of the configured templates directory.
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    sources: HashMap<(Locale, EmailTemplate), EmailTemplateSource>,
+}
+
+impl EmailTemplates {
+    /// Every template must exist in every supported locale
+    pub fn load(override_directory: Option<&str>) -> Result<Self, anyhow::Error> {
+        let mut sources = HashMap::new();
+        for locale in Locale::ALL {
+            for template in EmailTemplate::ALL {
+                let read =
+                    |extension| read_template(template, locale, extension, override_directory);
+                let source = EmailTemplateSource::new(
+                    template,
+                    read("subject.txt")?,
+                    read("html")?,
+                    read("txt")?,
+                )
+                .with_context(|| format!("Invalid {} email template", locale.name()))?;
+                sources.insert((locale, template), source);
+            }
+        }
+        Ok(Self { sources })
+    }
+
+    /// Fills in the `{placeholder}`s of the subject and bodies of a template.
+    /// A section written `{?name}...{/name}` is only kept when the value of
+    /// `name` is not empty.
+    pub fn render(
+        &self,
+        template: EmailTemplate,
+        locale: Locale,
+        values: &[(&str, TemplateValue)],
+    ) -> RenderedEmail {
+        let source = &self.sources[&(locale, template)];
+        let mut html_values = Vec::with_capacity(values.len());
+        let mut text_values = Vec::with_capacity(values.len());
+        for (name, value) in values {
+            let (html_value, text_value) = match value {
+                TemplateValue::Plain(value) => {
+                    (htmlescape::encode_minimal(value), value.to_string())
+                }
+                TemplateValue::Body { html, text } => (html.to_string(), text.to_string()),
+            };
+            html_values.push((*name, html_value));
+            text_values.push((*name, text_value));
+        }
+        RenderedEmail {
+            subject: fill_in(&source.subject, &text_values),
+            html: fill_in(&source.html, &html_values),
+            text: fill_in(&source.text, &text_values),
+        }
+    }
+}
+
+fn read_template(
+    template: EmailTemplate,
+    locale: Locale,
+    extension: &str,
+    override_directory: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let file_name =
+        Path::new(locale.as_str()).join(format!("{}.{}", template.file_stem(), extension));
+    let path = override_directory
+        .map(|directory| Path::new(directory).join(&file_name))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_TEMPLATES_DIR).join(&file_name));
+    std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read the email template {}", path.display()))
+}
+
+/// Single pass over the template, so that the values themselves are never
+/// searched for placeholders
+fn fill_in(source: &str, values: &[(&str, String)]) -> String {
+    let value_of = |name: &str| {
+        values
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let mut filled = String::with_capacity(source.len());
+    // Name of the empty optional section being skipped, if any
+    let mut skipped_section: Option<&str> = None;
+    let mut rest = source;
+    while let Some(start) = rest.find('{') {
+        if skipped_section.is_none() {
+            filled.push_str(&rest[..start]);
+        }
+        let after_brace = &rest[start + 1..];
+        let tag = after_brace.find('}').map(|end| &after_brace[..end]);
+        match tag {
+            Some(tag) if tag.starts_with('?') && value_of(&tag[1..]).is_some() => {
+                if skipped_section.is_none() && value_of(&tag[1..]) == Some("") {
+                    skipped_section = Some(&tag[1..]);
+                }
+            }
+            Some(tag) if tag.starts_with('/') && value_of(&tag[1..]).is_some() => {
+                if skipped_section == Some(&tag[1..]) {
+                    skipped_section = None;
+                }
+            }
+            Some(tag) if value_of(tag).is_some() => {
+                if skipped_section.is_none() {
+                    filled.push_str(value_of(tag).unwrap());
+                }
+            }
+            // Not a placeholder: the brace is kept as is
+            _ => {
+                if skipped_section.is_none() {
+                    filled.push('{');
+                }
+                rest = after_brace;
+                continue;
+            }
+        }
+        rest = &after_brace[tag.unwrap().len() + 1..];
+    }
+    if skipped_section.is_none() {
+        filled.push_str(rest);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmailTemplate, EmailTemplateSource, EmailTemplates, TemplateValue};
+    use crate::domain::new_subscriber::models::locale::Locale;
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn default_templates_are_valid() {
+        assert_ok!(EmailTemplates::load(None));
+    }
+
+    #[test]
+    fn templates_missing_a_required_placeholder_are_rejected() {
+        assert_err!(EmailTemplateSource::new(
+            EmailTemplate::Confirmation,
+            "Welcome".into(),
+            "<p>Welcome!</p>".into(),
+            "Welcome! {confirmation_link}".into(),
+        ));
+    }
+
+    #[test]
+    fn templates_without_a_subject_are_rejected() {
+        assert_err!(EmailTemplateSource::new(
+            EmailTemplate::Confirmation,
+            "\n".into(),
+            "<p>{confirmation_link}</p>".into(),
+            "{confirmation_link}".into(),
+        ));
+    }
+
+    #[test]
+    fn templates_are_rendered_in_the_requested_locale() {
+        let templates = EmailTemplates::load(None).unwrap();
+        let values = [(
+            "confirmation_link",
+            TemplateValue::Plain("https://example.com"),
+        )];
+
+        let english = templates.render(EmailTemplate::Confirmation, Locale::English, &values);
+        let french = templates.render(EmailTemplate::Confirmation, Locale::French, &values);
+
+        assert_eq!(english.subject, "Welcome");
+        assert_eq!(french.subject, "Bienvenue");
+        assert!(french.text.contains("https://example.com"));
+    }
+
+    #[test]
+    fn missing_override_files_fall_back_to_the_defaults() {
+        assert_ok!(EmailTemplates::load(Some("does/not/exist")));
+    }
+
+    #[test]
+    fn plain_values_are_escaped_in_the_html_variant_only() {
+        let templates = EmailTemplates::load(None).unwrap();
+
+        let email = templates.render(
+            EmailTemplate::Confirmation,
+            Locale::English,
+            &[(
+                "confirmation_link",
+                TemplateValue::Plain("https://example.com/?a=1&b=2"),
+            )],
+        );
+
+        assert!(email.html.contains("https://example.com/?a=1&amp;b=2"));
+        assert!(email.text.contains("https://example.com/?a=1&b=2"));
+    }
+
+    #[test]
+    fn optional_sections_are_dropped_when_their_value_is_empty() {
+        let source = "A{?link} see {link}{/link}.";
+
+        assert_eq!(super::fill_in(source, &[("link", "x".into())]), "A see x.");
+        assert_eq!(super::fill_in(source, &[("link", "".into())]), "A.");
+    }
+
+    #[test]
+    fn values_are_not_searched_for_placeholders() {
+        let source = "{content} {link} {unknown}";
+
+        assert_eq!(
+            super::fill_in(
+                source,
+                &[("content", "{link}".into()), ("link", "x".into())]
+            ),
+            "{link} x {unknown}"
+        );
+    }
+}
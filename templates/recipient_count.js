@@ -0,0 +1,22 @@
+// Shows how many confirmed subscribers of the list the segment reaches while it is typed.
+// Shared by the forms that have a `list_id` field, a `segment` field and a `recipient_count` span.
+const list = document.getElementById("list_id");
+const segment = document.getElementById("segment");
+const recipientCount = document.getElementById("recipient_count");
+let pendingCount;
+async function updateRecipientCount() {
+    const response = await fetch(
+        "/admin/newsletters/recipients?segment=" + encodeURIComponent(segment.value)
+            + "&list_id=" + encodeURIComponent(list.value)
+    );
+    recipientCount.textContent = response.ok
+        ? `${(await response.json()).recipients} recipient(s)`
+        : "Invalid segment";
+}
+// Counting waits for a pause in the typing rather than running on every keystroke
+segment.addEventListener("input", () => {
+    clearTimeout(pendingCount);
+    pendingCount = setTimeout(updateRecipientCount, 300);
+});
+list.addEventListener("change", updateRecipientCount);
+updateRecipientCount();
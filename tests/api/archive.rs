@@ -0,0 +1,180 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_random_confirmed_subscriber, publish_issue, spawn_app,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn published_issues_are_listed_in_the_public_archive() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let (_, slug) = publish_issue(&app, "Archive me", "<p>Archived body</p>").await;
+    assert!(slug.starts_with("archive-me-"));
+
+    let anonymous_client = reqwest::Client::new();
+    let html_page = anonymous_client
+        .get(format!("{}/newsletters", &app.address))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains(&format!(
+        r#"<a href="/newsletters/{}">Archive me</a>"#,
+        slug
+    )));
+
+    let response = app.get_archived_issue(&slug).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("<h1>Archive me</h1>"));
+    assert!(html_page.contains("<p>Archived body</p>"));
+}
+
+#[tokio::test]
+async fn archived_issues_are_sanitized() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let (_, slug) = publish_issue(
+        &app,
+        "Unsafe issue",
+        r#"<p>Hello</p><script>alert("xss")</script><a href="https://example.com" onclick="steal()">link</a>"#,
+    )
+    .await;
+
+    let html_page = app.get_archived_issue(&slug).await.text().await.unwrap();
+    assert!(html_page.contains("<p>Hello</p>"));
+    assert!(html_page.contains("https://example.com"));
+    assert!(!html_page.contains("<script>"));
+    assert!(!html_page.contains("onclick"));
+}
+
+#[tokio::test]
+async fn hidden_issues_are_not_in_the_archive() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let (issue_id, slug) = publish_issue(&app, "Hide me", "<p>Hidden body</p>").await;
+
+    let response = app.post_issue_visibility(&issue_id, true).await;
+    assert_is_redirect_to(
+        &response,
+        &format!("/admin/newsletters/issues/{}", issue_id),
+    );
+    let html_page = app.get_issue(&issue_id).await.text().await.unwrap();
+    assert!(html_page.contains("<p><i>The issue is now hidden from the archive.</i></p>"));
+
+    assert!(!app.get_archive_html().await.contains("Hide me"));
+    assert_eq!(app.get_archived_issue(&slug).await.status().as_u16(), 404);
+
+    app.post_issue_visibility(&issue_id, false).await;
+    assert!(app.get_archive_html().await.contains("Hide me"));
+    assert_eq!(app.get_archived_issue(&slug).await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn unpublished_issues_are_not_in_the_archive() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    app.post_create_draft(&serde_json::json!({
+        "title": "Draft issue",
+        "text_content": "Draft body",
+        "html_content": "<p>Draft body</p>",
+    }))
+    .await;
+
+    assert!(!app.get_archive_html().await.contains("Draft issue"));
+}
+
+#[tokio::test]
+async fn unknown_issues_return_404() {
+    let app = spawn_app().await;
+
+    assert_eq!(
+        app.get_archived_issue("no-such-issue-0a1b2c3d")
+            .await
+            .status()
+            .as_u16(),
+        404
+    );
+    assert_eq!(
+        app.get_archived_issue("Not%20A%20Slug")
+            .await
+            .status()
+            .as_u16(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn newsletter_emails_link_to_the_archived_issue() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let (_, slug) = publish_issue(&app, "Linked issue", "<p>Linked body</p>").await;
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let web_version_link = format!("{}/newsletters/{}", app.newsletter_state.url(), slug);
+    assert!(body["HtmlBody"]
+        .as_str()
+        .unwrap()
+        .contains(&web_version_link));
+    assert!(body["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains(&web_version_link));
+}
+
+#[tokio::test]
+async fn archived_issues_fill_in_merge_tags_with_generic_values() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let (_, slug) = publish_issue(&app, "Tagged issue", "<p>Hi {{name}}</p>").await;
+
+    let html_page = app.get_archived_issue(&slug).await.text().await.unwrap();
+    assert!(html_page.contains("<p>Hi reader</p>"));
+}
+
+#[tokio::test]
+async fn escaped_merge_tags_are_published_as_written() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let (_, slug) = publish_issue(&app, "Escaped issue", r"<p>Write \{{name}}</p>").await;
+
+    let html_page = app.get_archived_issue(&slug).await.text().await.unwrap();
+    assert!(html_page.contains("<p>Write {{name}}</p>"));
+}
+
+#[tokio::test]
+async fn the_archive_is_shown_in_the_language_of_the_browser() {
+    let app = spawn_app().await;
+
+    let html_page = app
+        .api_client
+        .get(format!("{}/newsletters", &app.address))
+        .header("Accept-Language", "fr")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert!(html_page.contains("<h1>Archives de la newsletter</h1>"));
+}
@@ -0,0 +1,217 @@
+use crate::helpers::{assert_is_redirect_to, mock_email_server, spawn_app, subscribe, TestApp};
+
+async fn sent_emails(app: &TestApp) -> usize {
+    app.email_server.received_requests().await.unwrap().len()
+}
+
+async fn is_stored(app: &TestApp, email: &str) -> bool {
+    sqlx::query!("SELECT id FROM subscriptions WHERE email = $1", email)
+        .fetch_optional(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .is_some()
+}
+
+async fn blocked_id(app: &TestApp, value: &str) -> String {
+    sqlx::query!(
+        "SELECT blocked_id FROM blocked_addresses WHERE value = $1",
+        value
+    )
+    .fetch_one(app.subscription_repo().pool())
+    .await
+    .unwrap()
+    .blocked_id
+    .to_string()
+}
+
+/// Status and last error of every delivery
+async fn delivery_log(app: &TestApp) -> Vec<(String, Option<String>)> {
+    sqlx::query!("SELECT status, last_error FROM issue_delivery_log")
+        .fetch_all(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.status, r.last_error))
+        .collect()
+}
+
+#[tokio::test]
+async fn blocked_addresses_are_quietly_ignored_when_subscribing() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_block("Spammer@Example.com", "Abuse").await;
+    assert_is_redirect_to(&response, "/admin/blocklist");
+
+    let response = subscribe(&app, "spammer@example.com", &[]).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(sent_emails(&app).await, 0);
+    assert!(!is_stored(&app, "spammer@example.com").await);
+}
+
+#[tokio::test]
+async fn blocking_a_domain_blocks_its_subdomains() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    app.post_block("@competitor.com", "Competitor").await;
+
+    subscribe(&app, "spy@competitor.com", &[]).await;
+    subscribe(&app, "spy@mail.competitor.com", &[]).await;
+    subscribe(&app, "reader@notcompetitor.com", &[]).await;
+
+    assert!(!is_stored(&app, "spy@competitor.com").await);
+    assert!(!is_stored(&app, "spy@mail.competitor.com").await);
+    assert!(is_stored(&app, "reader@notcompetitor.com").await);
+    assert_eq!(sent_emails(&app).await, 1);
+}
+
+#[tokio::test]
+async fn blocked_subscribers_receive_no_more_emails() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    subscribe(&app, "ursula_le_guin@gmail.com", &[])
+        .await
+        .error_for_status()
+        .unwrap();
+    app.confirm_subscription().await.unwrap();
+    app.test_user.login(&app).await;
+    app.post_block("ursula_le_guin@gmail.com", "Legal request")
+        .await;
+
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Only the confirmation email went out, and no delivery was queued
+    assert_eq!(sent_emails(&app).await, 1);
+    assert!(delivery_log(&app).await.is_empty());
+}
+
+#[tokio::test]
+async fn deliveries_to_addresses_blocked_after_publishing_are_skipped() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    subscribe(&app, "reader@mail.example.com", &[])
+        .await
+        .error_for_status()
+        .unwrap();
+    app.confirm_subscription().await.unwrap();
+    app.test_user.login(&app).await;
+
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": r#"<p><a href="https://example.com/post">Read</a></p>"#,
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        "tracking": true,
+    }))
+    .await;
+    app.post_block("example.com", "Abuse").await;
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(sent_emails(&app).await, 1);
+    assert_eq!(
+        delivery_log(&app).await,
+        vec![(
+            "skipped".to_string(),
+            Some("Recipient is blocked".to_string())
+        )]
+    );
+    let links = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM newsletter_tracking_links"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    assert_eq!(links.count, 0);
+}
+
+#[tokio::test]
+async fn the_blocklist_is_listed_and_entries_can_be_removed() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    app.post_block("example.com", "Abuse <script>").await;
+
+    let html_page = app.get_blocklist_html().await;
+    assert!(html_page.contains("<p><i>@example.com is blocked.</i></p>"));
+    assert!(html_page.contains("Abuse &lt;script&gt;"));
+
+    let id = blocked_id(&app, "example.com").await;
+    let response = app.post_unblock(&id).await;
+    assert_is_redirect_to(&response, "/admin/blocklist");
+
+    let html_page = app.get_blocklist_html().await;
+    assert!(html_page.contains("<p><i>The entry has been removed from the blocklist.</i></p>"));
+    assert!(!html_page.contains("<td>@example.com</td>"));
+
+    subscribe(&app, "reader@example.com", &[]).await;
+    assert!(is_stored(&app, "reader@example.com").await);
+}
+
+#[tokio::test]
+async fn blocking_the_same_pattern_again_updates_its_reason() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    app.post_block("example.com", "Abuse").await;
+    app.post_block("@EXAMPLE.com", "Legal request").await;
+
+    let html_page = app.get_blocklist_html().await;
+    assert_eq!(html_page.matches("@example.com</td>").count(), 1);
+    assert!(html_page.contains("Legal request"));
+}
+
+#[tokio::test]
+async fn invalid_patterns_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    for pattern in ["", "localhost", "ursula@", "@exa mple.com"] {
+        let response = app.post_block(pattern, "").await;
+
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "{} was not rejected",
+            pattern
+        );
+    }
+}
+
+#[tokio::test]
+async fn removing_an_unknown_entry_returns_404() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_unblock(&uuid::Uuid::new_v4().to_string()).await;
+
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_the_blocklist() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/admin/blocklist", &app.address))
+        .send()
+        .await
+        .unwrap();
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app.post_block("example.com", "").await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app.post_unblock(&uuid::Uuid::new_v4().to_string()).await;
+    assert_is_redirect_to(&response, "/login");
+}
@@ -0,0 +1,222 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_random_confirmed_subscriber, spawn_app, TestApp,
+};
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn build_draft(title: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": title,
+        "text_content": "Draft body as plain text",
+        "html_content": "<p>Draft body as HTML</p>",
+    })
+}
+
+/// Saves a draft and returns its id, taken from the redirect to its edit page
+async fn create_draft(app: &TestApp, body: &serde_json::Value) -> String {
+    let response = app.post_create_draft(body).await;
+    assert_eq!(response.status().as_u16(), 303);
+    let location = response
+        .headers()
+        .get("Location")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    location
+        .strip_prefix("/admin/newsletters/drafts/")
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_the_drafts() {
+    let app = spawn_app().await;
+
+    let response = app.post_create_draft(&build_draft("Draft title")).await;
+
+    assert_is_redirect_to(&response, "/login");
+}
+
+#[tokio::test]
+async fn drafts_can_be_saved_edited_and_listed() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let draft_id = create_draft(&app, &build_draft("Draft title")).await;
+    let html_page = app.get_draft(&draft_id).await.text().await.unwrap();
+    assert!(html_page.contains("<p><i>The draft has been saved.</i></p>"));
+    assert!(html_page.contains(&htmlescape::encode_attribute("Draft title")));
+    assert!(html_page.contains(&htmlescape::encode_attribute("<p>Draft body as HTML</p>")));
+
+    let response = app
+        .post_update_draft(&draft_id, &build_draft("Edited title"))
+        .await;
+    assert_is_redirect_to(
+        &response,
+        &format!("/admin/newsletters/drafts/{}", draft_id),
+    );
+
+    let html_page = app.get_drafts_html().await;
+    assert!(html_page.contains("Edited title"));
+    assert!(!html_page.contains("Draft title"));
+}
+
+#[tokio::test]
+async fn drafts_are_not_delivered() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    create_draft(&app, &build_draft("Draft title")).await;
+    app.release_due_scheduled_issues().await;
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn draft_preview_shows_both_bodies_with_the_unsubscribe_footer() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let draft_id = create_draft(&app, &build_draft("Draft title")).await;
+    let response = app.get_draft_preview(&draft_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("Subject: Draft title"));
+    assert!(html_page.contains("Draft body as plain text"));
+    assert!(html_page.contains(&htmlescape::encode_attribute("<p>Draft body as HTML</p>")));
+    assert!(html_page.contains("/subscriptions/unsubscribe?subscription_token="));
+}
+
+#[tokio::test]
+async fn drafts_written_in_markdown_are_previewed_with_both_generated_bodies() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let draft_id = create_draft(
+        &app,
+        &serde_json::json!({
+            "title": "Markdown draft",
+            "text_content": "",
+            "html_content": "",
+            "markdown_content": "Some **bold** news",
+        }),
+    )
+    .await;
+
+    let html_page = app.get_draft(&draft_id).await.text().await.unwrap();
+    assert!(html_page.contains(&htmlescape::encode_attribute("Some **bold** news")));
+
+    let html_page = app.get_draft_preview(&draft_id).await.text().await.unwrap();
+    assert!(html_page.contains(&htmlescape::encode_attribute(
+        "<p>Some <strong>bold</strong> news</p>"
+    )));
+    assert!(html_page.contains("Some bold news"));
+}
+
+#[tokio::test]
+async fn incomplete_drafts_can_be_saved_but_not_previewed_or_published() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let draft_id = create_draft(
+        &app,
+        &serde_json::json!({
+            "title": "Draft title",
+            "text_content": "",
+            "html_content": "",
+        }),
+    )
+    .await;
+
+    assert_eq!(
+        app.get_draft_preview(&draft_id).await.status().as_u16(),
+        400
+    );
+    assert_eq!(
+        app.post_publish_draft(&draft_id).await.status().as_u16(),
+        400
+    );
+}
+
+#[tokio::test]
+async fn placeholders_written_in_a_draft_are_shown_as_written() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let text = "{html_content} {markdown_content} {tracking_checked} {list_options}";
+
+    let draft_id = create_draft(
+        &app,
+        &serde_json::json!({
+            "title": "Draft title",
+            "text_content": text,
+            "html_content": "<p>{text_content}</p>",
+        }),
+    )
+    .await;
+
+    let html_page = app.get_draft(&draft_id).await.text().await.unwrap();
+    assert!(html_page.contains(&htmlescape::encode_attribute(text)));
+    assert!(html_page.contains(&htmlescape::encode_attribute("<p>{text_content}</p>")));
+}
+
+#[tokio::test]
+async fn drafts_without_a_title_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_create_draft(&build_draft("")).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn publishing_a_draft_delivers_it_to_confirmed_subscribers() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let draft_id = create_draft(&app, &build_draft("Draft title")).await;
+    let response = app.post_publish_draft(&draft_id).await;
+    assert_is_redirect_to(&response, "/admin/newsletters/drafts");
+    app.dispatch_all_pending_emails().await;
+
+    let html_page = app.get_drafts_html().await;
+    assert!(html_page.contains("emails will go out shortly"));
+    assert!(!html_page.contains("Draft title"));
+    assert!(app.get_issues_html().await.contains("Draft title"));
+
+    // A published draft cannot be published a second time
+    assert_eq!(
+        app.post_publish_draft(&draft_id).await.status().as_u16(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn deleted_drafts_are_gone() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let draft_id = create_draft(&app, &build_draft("Draft title")).await;
+    let response = app.post_delete_draft(&draft_id).await;
+    assert_is_redirect_to(&response, "/admin/newsletters/drafts");
+
+    let html_page = app.get_drafts_html().await;
+    assert!(html_page.contains("<p><i>The draft has been deleted.</i></p>"));
+    assert!(!html_page.contains("Draft title"));
+    assert_eq!(app.get_draft(&draft_id).await.status().as_u16(), 404);
+}
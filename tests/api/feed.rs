@@ -0,0 +1,139 @@
+use crate::helpers::{publish_issue, spawn_app, TestApp};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+async fn get_feed(app: &TestApp, feed: &str, headers: &[(&str, &str)]) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{}/{}", &app.address, feed));
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request.send().await.expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn feeds_list_published_issues_with_absolute_links() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (_, slug) = publish_issue(&app, "Fish & Chips", "<p>Feed body</p>").await;
+    let link = format!("{}/newsletters/{}", app.newsletter_state.url(), slug);
+
+    let response = get_feed(&app, "feed.rss", &[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "application/rss+xml; charset=utf-8"
+    );
+    let rss = response.text().await.unwrap();
+    assert!(rss.contains("<title>Fish &amp; Chips</title>"));
+    assert!(rss.contains(&format!("<link>{}</link>", link)));
+    assert!(rss.contains("&lt;p&gt;Feed body&lt;/p&gt;"));
+
+    let response = get_feed(&app, "feed.atom", &[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "application/atom+xml; charset=utf-8"
+    );
+    let atom = response.text().await.unwrap();
+    assert!(atom.contains("<title>Fish &amp; Chips</title>"));
+    assert!(atom.contains(&format!(r#"<link href="{}"/>"#, link)));
+    assert!(atom.contains("&lt;p&gt;Feed body&lt;/p&gt;"));
+}
+
+#[tokio::test]
+async fn feeds_skip_hidden_and_unpublished_issues() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (issue_id, _) = publish_issue(&app, "Hidden issue", "<p>Hidden</p>").await;
+    app.post_issue_visibility(&issue_id, true).await;
+    app.post_create_draft(&serde_json::json!({
+        "title": "Draft issue",
+        "text_content": "Draft body",
+        "html_content": "<p>Draft body</p>",
+    }))
+    .await;
+
+    for feed in ["feed.rss", "feed.atom"] {
+        let body = get_feed(&app, feed, &[]).await.text().await.unwrap();
+        assert!(!body.contains("Hidden issue"));
+        assert!(!body.contains("Draft issue"));
+    }
+}
+
+#[tokio::test]
+async fn feeds_are_not_sent_again_when_the_etag_matches() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    publish_issue(&app, "Cached issue", "<p>Cached</p>").await;
+
+    for feed in ["feed.rss", "feed.atom"] {
+        let response = get_feed(&app, feed, &[]).await;
+        let etag = response.headers()[ETAG].to_str().unwrap().to_owned();
+
+        let response = get_feed(&app, feed, &[(IF_NONE_MATCH.as_str(), &etag)]).await;
+        assert_eq!(response.status().as_u16(), 304);
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+        assert!(response.text().await.unwrap().is_empty());
+
+        let response = get_feed(&app, feed, &[(IF_NONE_MATCH.as_str(), r#""stale""#)]).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+}
+
+#[tokio::test]
+async fn feeds_are_not_sent_again_when_not_modified_since() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    publish_issue(&app, "Dated issue", "<p>Dated</p>").await;
+
+    let response = get_feed(&app, "feed.rss", &[]).await;
+    let last_modified = response.headers()[LAST_MODIFIED]
+        .to_str()
+        .unwrap()
+        .to_owned();
+
+    let response = get_feed(
+        &app,
+        "feed.rss",
+        &[(IF_MODIFIED_SINCE.as_str(), &last_modified)],
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 304);
+
+    let response = get_feed(
+        &app,
+        "feed.rss",
+        &[(IF_MODIFIED_SINCE.as_str(), "Thu, 01 Jan 1970 00:00:00 GMT")],
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn hiding_an_issue_changes_the_feed_etag() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (issue_id, _) = publish_issue(&app, "Soon hidden", "<p>Soon hidden</p>").await;
+
+    let response = get_feed(&app, "feed.atom", &[]).await;
+    let etag = response.headers()[ETAG].to_str().unwrap().to_owned();
+
+    app.post_issue_visibility(&issue_id, true).await;
+
+    let response = get_feed(&app, "feed.atom", &[(IF_NONE_MATCH.as_str(), &etag)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.text().await.unwrap().contains("Soon hidden"));
+}
+
+#[tokio::test]
+async fn feeds_are_valid_when_nothing_was_published() {
+    let app = spawn_app().await;
+
+    let response = get_feed(&app, "feed.rss", &[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().get(LAST_MODIFIED).is_none());
+    assert!(response.text().await.unwrap().ends_with("</rss>"));
+
+    let response = get_feed(&app, "feed.atom", &[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().ends_with("</feed>"));
+}
@@ -1,20 +1,34 @@
 use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
+use fake::faker::internet::en::SafeEmail;
+use fake::faker::name::en::Name;
+use fake::Fake;
 use once_cell::sync::Lazy;
+use secrecy::Secret;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use std::sync::Arc;
 use uuid::Uuid;
-use wiremock::MockServer;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 use zero2prod::domain::auth::service::BlogAuth;
+use zero2prod::domain::idempotency::service::BlogIdempotency;
 use zero2prod::domain::new_subscriber::{
-    models::{subscriber::NewSubscriber, token::SubscriptionToken},
+    models::{
+        subscriber::NewSubscriber,
+        token::{SubscriptionToken, TokenPurpose},
+    },
     ports::SubscriberRepository,
     service::BlogSubscription,
 };
-use zero2prod::domain::newsletter::service::BlogDelivery;
+use zero2prod::domain::newsletter::{
+    models::issue_delivery::ExecutionOutcome, ports::NewsletterService, service::BlogDelivery,
+};
 use zero2prod::inbound::http::state::{SharedNewsletterState, SharedSubscriptionState};
 use zero2prod::inbound::http::Application;
-use zero2prod::outbound::{db::postgres_db::PostgresDb, notifier::email_client::EmailClient};
+use zero2prod::outbound::{
+    db::postgres_db::PostgresDb,
+    notifier::{blocklist::BlocklistNotifier, email_client::EmailClient},
+};
 use zero2prod::{
     configuration::{get_configuration, DatabaseSettings},
     outbound::telemetry::init_logger,
@@ -66,6 +80,9 @@ impl TestUser {
     }
 }
 
+/// The notifier the application sends its emails with
+type Notifier = BlocklistNotifier<EmailClient, PostgresDb>;
+
 #[derive(Debug)]
 pub struct ConfirmationLinks {
     pub html: reqwest::Url,
@@ -74,20 +91,81 @@ pub struct ConfirmationLinks {
 
 pub struct TestApp {
     pub address: String,
-    pub subscription_state: SharedSubscriptionState<BlogSubscription<PostgresDb, EmailClient>>,
-    #[allow(dead_code)]
-    pub newsletter_state: SharedNewsletterState<BlogDelivery<PostgresDb, EmailClient>>,
+    pub subscription_state: SharedSubscriptionState<BlogSubscription<PostgresDb, Notifier>>,
+    pub newsletter_state: SharedNewsletterState<BlogDelivery<PostgresDb, Notifier>>,
     pub email_server: MockServer,
     pub port: u16,
     pub test_user: TestUser,
     pub api_client: reqwest::Client,
+    pub token_hmac_secret: Secret<String>,
+    pub webhook_secret: Secret<String>,
 }
 
 impl TestApp {
-    pub fn subscription_service(&self) -> &BlogSubscription<PostgresDb, EmailClient> {
+    pub fn subscription_service(&self) -> &BlogSubscription<PostgresDb, Notifier> {
         self.subscription_state.subscription_service()
     }
 
+    pub fn newsletter_service(&self) -> &BlogDelivery<PostgresDb, Notifier> {
+        self.newsletter_state.newsletter_service()
+    }
+
+    pub async fn release_due_scheduled_issues(&self) {
+        loop {
+            if let ExecutionOutcome::EmptyQueue = self
+                .newsletter_service()
+                .try_release_scheduled_issue()
+                .await
+                .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    pub async fn send_due_digests(&self) {
+        loop {
+            if let ExecutionOutcome::EmptyQueue = self
+                .newsletter_service()
+                .try_send_digest(self.newsletter_state.url())
+                .await
+                .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    pub async fn dispatch_all_pending_emails(&self) {
+        loop {
+            if let ExecutionOutcome::EmptyQueue = self
+                .newsletter_service()
+                .try_execute_task(self.newsletter_state.url())
+                .await
+                .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Lets pending subscribers get another confirmation email right away
+    pub async fn end_confirmation_cooldown(&self) {
+        sqlx::query!(
+            "UPDATE subscriptions SET last_confirmation_sent_at = now() - interval '1 day'"
+        )
+        .execute(self.subscription_repo().pool())
+        .await
+        .unwrap();
+    }
+
+    /// The hash under which `token` is stored
+    pub fn hash_token(&self, token: &str) -> String {
+        SubscriptionToken::parse(token.into())
+            .unwrap()
+            .hash(&self.token_hmac_secret)
+    }
+
     pub fn subscription_repo(&self) -> Arc<PostgresDb> {
         let subscription_service = self.subscription_service();
         subscription_service.repo.clone()
@@ -95,7 +173,7 @@ impl TestApp {
 
     pub async fn post_subscriptions(&self, body: String) -> reqwest::Response {
         self.api_client
-            .post(&format!("{}/subscriptions", &self.address))
+            .post(format!("{}/subscriptions", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
             .body(body)
             .send()
@@ -105,7 +183,7 @@ impl TestApp {
 
     pub async fn get_subscription_unsubscribe(&self, token: String) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/subscriptions/unsubscribe", &self.address))
+            .get(format!("{}/subscriptions/unsubscribe", &self.address))
             .query(&[("subscription_token", token.as_str())])
             .header("Content-Type", "application/x-www-form-urlencoded")
             .send()
@@ -113,12 +191,71 @@ impl TestApp {
             .expect("Failed to execute unsubscription request.")
     }
 
+    /// Unsubscribes in one click, as mail clients do (RFC 8058)
+    pub async fn post_one_click_unsubscribe(&self, token: &str, body: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/subscriptions/unsubscribe", &self.address))
+            .query(&[("subscription_token", token)])
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute one-click unsubscribe request.")
+    }
+
+    pub async fn get_preferences(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/subscriptions/preferences", &self.address))
+            .query(&[("subscription_token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Fields are sent as pairs since the checked lists repeat the `lists` key
+    pub async fn post_preferences(&self, fields: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/subscriptions/preferences", &self.address))
+            .form(fields)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub fn get_confirmation_links(&self, email_requests: &wiremock::Request) -> ConfirmationLinks {
+        self.get_links_matching(email_requests, |_| true)
+    }
+
+    pub fn get_newsletter_unsubscribe_links(
+        &self,
+        email_requests: &wiremock::Request,
+    ) -> ConfirmationLinks {
+        self.get_links_matching(email_requests, |link| {
+            link.contains("/subscriptions/unsubscribe")
+        })
+    }
+
+    pub fn get_newsletter_preferences_links(
+        &self,
+        email_requests: &wiremock::Request,
+    ) -> ConfirmationLinks {
+        self.get_links_matching(email_requests, |link| {
+            link.contains("/subscriptions/preferences")
+        })
+    }
+
+    /// Extracts the single link matching `predicate` from both email bodies
+    fn get_links_matching(
+        &self,
+        email_requests: &wiremock::Request,
+        predicate: impl Fn(&str) -> bool,
+    ) -> ConfirmationLinks {
         let body: serde_json::Value = serde_json::from_slice(&email_requests.body).unwrap();
         let get_link = |s: &str| {
             let links: Vec<_> = linkify::LinkFinder::new()
                 .links(s)
                 .filter(|l| *l.kind() == linkify::LinkKind::Url)
+                .filter(|l| predicate(l.as_str()))
                 .collect();
             assert_eq!(links.len(), 1);
             let raw_link = links[0].as_str().to_owned();
@@ -128,18 +265,11 @@ impl TestApp {
             confirmation_link
         };
 
-        let html = get_link(&body["HtmlBody"].as_str().unwrap());
-        let plain_text = get_link(&body["TextBody"].as_str().unwrap());
+        let html = get_link(body["HtmlBody"].as_str().unwrap());
+        let plain_text = get_link(body["TextBody"].as_str().unwrap());
         ConfirmationLinks { html, plain_text }
     }
 
-    pub fn get_newsletter_unsubscribe_links(
-        &self,
-        email_requests: &wiremock::Request,
-    ) -> ConfirmationLinks {
-        self.get_confirmation_links(&email_requests)
-    }
-
     pub async fn get_email_requests(&self) -> wiremock::Request {
         self.email_server
             .received_requests()
@@ -149,9 +279,11 @@ impl TestApp {
             .unwrap()
     }
 
+    /// Follows the link of the first confirmation email. Returns the confirmed
+    /// subscriber with an unsubscribe token.
     pub async fn confirm_subscription(&self) -> Option<(NewSubscriber, SubscriptionToken)> {
         let email_request = &self.email_server.received_requests().await.unwrap()[0];
-        let confirmation_links = self.get_confirmation_links(&email_request);
+        let confirmation_links = self.get_confirmation_links(email_request);
         let token = confirmation_links
             .html
             .query()
@@ -167,14 +299,17 @@ impl TestApp {
             .error_for_status()
             .unwrap();
 
-        let saved = self
-            .subscription_service()
-            .repo
+        let repo = &self.subscription_service().repo;
+        let (saved, _) = repo
             .retrieve_from_token(&token)
             .await
             .expect("Failed to fetch saved subscription.");
+        let unsubscribe_token = repo
+            .issue_token(saved.id.unwrap(), TokenPurpose::Unsubscribe)
+            .await
+            .expect("Failed to issue an unsubscribe token.");
 
-        Some((saved, token))
+        Some((saved, unsubscribe_token))
     }
 
     pub async fn post_login<Body>(&self, body: &Body) -> reqwest::Response
@@ -182,7 +317,7 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.api_client
-            .post(&format!("{}/login", &self.address))
+            .post(format!("{}/login", &self.address))
             .form(body)
             .send()
             .await
@@ -191,7 +326,7 @@ impl TestApp {
 
     pub async fn get_login_html(&self) -> String {
         self.api_client
-            .get(&format!("{}/login", &self.address))
+            .get(format!("{}/login", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -202,7 +337,7 @@ impl TestApp {
 
     pub async fn get_admin_dashboard(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/dashboard", &self.address))
+            .get(format!("{}/admin/dashboard", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -214,7 +349,7 @@ impl TestApp {
 
     pub async fn get_change_password(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/password", &self.address))
+            .get(format!("{}/admin/password", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -224,7 +359,7 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.api_client
-            .post(&format!("{}/admin/password", &self.address))
+            .post(format!("{}/admin/password", &self.address))
             .form(body)
             .send()
             .await
@@ -237,7 +372,7 @@ impl TestApp {
 
     pub async fn post_logout(&self) -> reqwest::Response {
         self.api_client
-            .post(&format!("{}/admin/logout", &self.address))
+            .post(format!("{}/admin/logout", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -245,7 +380,7 @@ impl TestApp {
 
     pub async fn get_publish_newsletter(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/newsletters", &self.address))
+            .get(format!("{}/admin/newsletters", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -260,7 +395,400 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.api_client
-            .post(&format!("{}/admin/newsletters", &self.address))
+            .post(format!("{}/admin/newsletters", &self.address))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_send_test_issue<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/admin/newsletters/test", &self.address))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_dead_letters_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/newsletters/dead_letters", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_issues_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/newsletters/issues", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_issue(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/admin/newsletters/issues/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_scheduled_issues_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/newsletters/scheduled", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_reschedule_issue<Body>(
+        &self,
+        newsletter_issue_id: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/scheduled/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_cancel_scheduled_issue(
+        &self,
+        newsletter_issue_id: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/scheduled/{}/cancel",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_drafts_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/newsletters/drafts", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_create_draft<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/admin/newsletters/drafts", &self.address))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_draft(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/admin/newsletters/drafts/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_update_draft<Body>(
+        &self,
+        newsletter_issue_id: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/drafts/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_delete_draft(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/drafts/{}/delete",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_draft_preview(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/admin/newsletters/drafts/{}/preview",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_publish_draft(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/drafts/{}/publish",
+                &self.address, newsletter_issue_id
+            ))
+            // Same form as the one of the draft page, without a segment
+            .form(&serde_json::json!({ "segment": "" }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_archive_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/newsletters", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_archived_issue(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/newsletters/{}", &self.address, slug))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_issue_visibility(
+        &self,
+        newsletter_issue_id: &str,
+        hidden: bool,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/issues/{}/visibility",
+                &self.address, newsletter_issue_id
+            ))
+            .form(&serde_json::json!({ "hidden": hidden }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_translations(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/admin/newsletters/translations/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_translation<Body>(
+        &self,
+        newsletter_issue_id: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/translations/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_recipient_count(&self, segment: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/admin/newsletters/recipients", &self.address))
+            .query(&[("segment", segment)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_lists(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/admin/lists", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_list(&self, name: &str, slug: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/lists", &self.address))
+            .form(&serde_json::json!({ "name": name, "slug": slug }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_subscribers(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/admin/subscribers", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_subscribers_html(&self) -> String {
+        self.get_subscribers().await.text().await.unwrap()
+    }
+
+    pub async fn post_subscriber_tags(&self, subscriber_id: &str, tags: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/subscribers/{}/tags",
+                &self.address, subscriber_id
+            ))
+            .form(&serde_json::json!({ "tags": tags }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_apply_retention(&self) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/subscribers/retention", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_blocklist_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/blocklist", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_block(&self, pattern: &str, reason: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/blocklist", &self.address))
+            .form(&serde_json::json!({ "pattern": pattern, "reason": reason }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_unblock(&self, blocked_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/blocklist/{}/delete",
+                &self.address, blocked_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_suppressions_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/admin/suppressions", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_suppression<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/admin/suppressions", &self.address))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_reinstate_address(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/suppressions/reinstate", &self.address))
+            .form(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Posts an event to the email provider webhook, authenticated with `secret`
+    pub async fn post_postmark_webhook(
+        &self,
+        event: &serde_json::Value,
+        secret: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/webhooks/postmark", &self.address))
+            .header("X-Webhook-Secret", secret)
+            .json(event)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_replay_dead_letter<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!(
+                "{}/admin/newsletters/dead_letters/replay",
+                &self.address
+            ))
             .form(body)
             .send()
             .await
@@ -287,22 +815,41 @@ pub async fn spawn_app() -> TestApp {
         c.database.database_name = Uuid::new_v4().to_string();
         c.application.port = 0;
         c.email_client.base_url = email_server.uri();
+        c.email_client.retry.initial_backoff_milliseconds = 10;
+        c.email_client.retry.max_backoff_milliseconds = 50;
         c
     };
 
     configure_database(&configuration.database).await;
 
-    let email_client = Arc::new(EmailClient::new(configuration.email_client));
-    let repo = Arc::new(PostgresDb::new(&configuration.database));
-    let subscription_service = BlogSubscription::new(Arc::clone(&repo), Arc::clone(&email_client));
-    let newsletter_service = BlogDelivery::new(Arc::clone(&repo), Arc::clone(&email_client));
+    let default_locale = configuration.general.default_locale().unwrap();
+    let repo = Arc::new(PostgresDb::new(
+        &configuration.database,
+        configuration.application.token_hmac_secret.clone(),
+    ));
+    let notifier = Arc::new(BlocklistNotifier::new(
+        Arc::new(EmailClient::new(configuration.email_client)),
+        Arc::clone(&repo),
+    ));
+    let subscription_service = BlogSubscription::new(
+        Arc::clone(&repo),
+        Arc::clone(&notifier),
+        default_locale,
+        configuration.subscriptions.confirmation_policy(),
+        configuration.subscriptions.retention_policy(),
+    );
+    let newsletter_service =
+        BlogDelivery::new(Arc::clone(&repo), Arc::clone(&notifier), default_locale);
     let auth_service = BlogAuth::new(Arc::clone(&repo));
+    let idempotency_service = BlogIdempotency::new(Arc::clone(&repo));
 
     let application = Application::build(
         subscription_service,
         newsletter_service,
         auth_service,
+        idempotency_service,
         configuration.application.clone(),
+        default_locale,
     )
     .await
     .expect("Failed to build application");
@@ -311,7 +858,7 @@ pub async fn spawn_app() -> TestApp {
     let subscription_state = application.subscription_state();
     let newsletter_state = application.newsletter_state();
 
-    let _ = tokio::spawn(application.run_until_stopped());
+    tokio::spawn(application.run_until_stopped());
 
     let client = reqwest::Client::builder()
         .redirect(reqwest::redirect::Policy::none())
@@ -327,6 +874,8 @@ pub async fn spawn_app() -> TestApp {
         email_server,
         test_user: TestUser::generate(),
         api_client: client,
+        token_hmac_secret: configuration.application.token_hmac_secret.clone(),
+        webhook_secret: configuration.application.webhook_secret.clone(),
     };
 
     test_app.test_user.store(repo.clone()).await;
@@ -357,3 +906,159 @@ pub fn assert_is_redirect_to(response: &reqwest::Response, location: &str) {
     assert_eq!(response.status().as_u16(), 303);
     assert_eq!(response.headers().get("Location").unwrap(), location);
 }
+
+/// Mounts an email server that accepts every email
+pub async fn mock_email_server(app: &TestApp) {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+}
+
+/// Posts the subscribe form for `email`. `fields` fills in the other fields of the
+/// form (`list`, `tags`, `locale`...), the name defaulting to "Subscriber".
+pub async fn subscribe(app: &TestApp, email: &str, fields: &[(&str, &str)]) -> reqwest::Response {
+    let mut form = vec![("email", email)];
+    if !fields.iter().any(|(key, _)| *key == "name") {
+        form.push(("name", "Subscriber"));
+    }
+    form.extend_from_slice(fields);
+    app.post_subscriptions(serde_urlencoded::to_string(form).unwrap())
+        .await
+}
+
+/// Subscribes `email` like `subscribe` and follows the link of the confirmation
+/// email. The email server must already accept emails.
+pub async fn create_confirmed_subscriber(app: &TestApp, email: &str, fields: &[(&str, &str)]) {
+    subscribe(app, email, fields)
+        .await
+        .error_for_status()
+        .unwrap();
+
+    let email_request = app.get_email_requests().await;
+    let confirmation_links = app.get_confirmation_links(&email_request);
+    reqwest::get(confirmation_links.html)
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+}
+
+/// Subscribes a subscriber with a random name and email. The email server only
+/// expects the confirmation email while the subscription is made.
+pub async fn create_random_subscriber(app: &TestApp) -> ConfirmationLinks {
+    let name: String = Name().fake();
+    let email: String = SafeEmail().fake();
+    let body = serde_urlencoded::to_string(serde_json::json!({
+        "name": name,
+        "email": email
+    }))
+    .unwrap();
+
+    let _mock_guard = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Create unconfirmed subscriber")
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body)
+        .await
+        .error_for_status()
+        .unwrap();
+
+    let email_request = &app.get_email_requests().await;
+    app.get_confirmation_links(email_request)
+}
+
+/// Same as `create_random_subscriber`, then follows the confirmation link
+pub async fn create_random_confirmed_subscriber(app: &TestApp) {
+    let confirmation_links = create_random_subscriber(app).await;
+    reqwest::get(confirmation_links.html)
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+}
+
+/// Publishes an issue and returns its id and slug
+pub async fn publish_issue(app: &TestApp, title: &str, html_content: &str) -> (String, String) {
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": title,
+            "text_content": "Newsletter body as plain text",
+            "html_content": html_content,
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let record = sqlx::query!(
+        r#"SELECT newsletter_issue_id, slug AS "slug!" FROM newsletter_issues WHERE title = $1"#,
+        title
+    )
+    .fetch_one(app.subscription_repo().pool())
+    .await
+    .unwrap();
+    (record.newsletter_issue_id.to_string(), record.slug)
+}
+
+/// Creates a list from the admin UI, as a logged in user, and returns its id
+pub async fn create_list(app: &TestApp, name: &str, slug: &str) -> Uuid {
+    app.post_list(name, slug).await.error_for_status().unwrap();
+    sqlx::query!("SELECT list_id FROM lists WHERE slug = $1", slug)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .list_id
+}
+
+/// Id of the subscription of `email` to the list with the given slug, the
+/// default list when it is empty
+pub async fn subscription_id(app: &TestApp, email: &str, list: &str) -> Uuid {
+    sqlx::query!(
+        r#"SELECT s.id
+        FROM subscriptions s
+        JOIN lists l ON l.list_id = s.list_id
+        WHERE s.email = $1 AND l.slug = $2"#,
+        email,
+        if list.is_empty() { "newsletter" } else { list }
+    )
+    .fetch_one(app.subscription_repo().pool())
+    .await
+    .unwrap()
+    .id
+}
+
+/// Bodies of the emails sent so far with the given subject
+pub async fn emails_with_subject(app: &TestApp, subject: &str) -> Vec<serde_json::Value> {
+    app.email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice::<serde_json::Value>(&r.body).unwrap())
+        .filter(|body| body["Subject"] == subject)
+        .collect()
+}
+
+/// Sorted recipients of the emails sent so far with the given subject
+pub async fn recipients_of(app: &TestApp, subject: &str) -> Vec<String> {
+    let mut recipients: Vec<String> = emails_with_subject(app, subject)
+        .await
+        .iter()
+        .map(|body| body["To"].as_str().unwrap().to_string())
+        .collect();
+    recipients.sort();
+    recipients
+}
+
+/// Number of recipients of an issue sent to `segment` on the default list
+pub async fn recipient_count(app: &TestApp, segment: &str) -> u64 {
+    let response = app.get_recipient_count(segment).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["recipients"].as_u64().unwrap()
+}
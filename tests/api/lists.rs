@@ -0,0 +1,212 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, create_list, mock_email_server,
+    recipients_of, spawn_app, subscribe, subscription_id, TestApp,
+};
+use zero2prod::domain::new_subscriber::models::token::TokenPurpose;
+use zero2prod::domain::new_subscriber::ports::SubscriberRepository;
+
+/// Status of the subscription of `email` to each list, keyed by list slug
+async fn subscription_statuses(app: &TestApp, email: &str) -> Vec<(String, String)> {
+    sqlx::query!(
+        r#"SELECT l.slug, s.status
+        FROM subscriptions s
+        JOIN lists l ON l.list_id = s.list_id
+        WHERE s.email = $1
+        ORDER BY l.slug"#,
+        email
+    )
+    .fetch_all(app.subscription_repo().pool())
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|r| (r.slug, r.status))
+    .collect()
+}
+
+fn list_issue(list_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": "Release notes issue",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "list_id": list_id,
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    })
+}
+
+#[tokio::test]
+async fn lists_can_be_created_from_the_admin_ui() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_list("Release notes", "release-notes").await;
+    assert_is_redirect_to(&response, "/admin/lists");
+
+    let html_page = app.get_lists().await.text().await.unwrap();
+    assert!(html_page.contains("<p><i>The list Release notes has been created.</i></p>"));
+    assert!(html_page.contains("release-notes"));
+    // The default list created by the migrations is listed too
+    assert!(html_page.contains("newsletter"));
+}
+
+#[tokio::test]
+async fn invalid_or_duplicate_lists_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let test_cases = [
+        ("", "release-notes", "an empty name"),
+        ("Release notes", "Release Notes", "an invalid slug"),
+        (
+            "Newsletter again",
+            "newsletter",
+            "a slug that is already used",
+        ),
+    ];
+
+    for (name, slug, description) in test_cases {
+        let response = app.post_list(name, slug).await;
+
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a list with {}",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_same_email_can_subscribe_to_several_lists() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    create_list(&app, "Release notes", "release-notes").await;
+
+    create_confirmed_subscriber(&app, "reader@example.com", &[]).await;
+    let response = subscribe(&app, "reader@example.com", &[("list", "release-notes")]).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(
+        subscription_statuses(&app, "reader@example.com").await,
+        [
+            ("newsletter".to_string(), "confirmed".to_string()),
+            (
+                "release-notes".to_string(),
+                "pending_confirmation".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn issues_published_to_a_list_only_reach_its_subscribers() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    let list_id = create_list(&app, "Release notes", "release-notes")
+        .await
+        .to_string();
+    create_confirmed_subscriber(&app, "reader@example.com", &[]).await;
+    create_confirmed_subscriber(&app, "user@example.com", &[("list", "release-notes")]).await;
+
+    let response = app.post_publish_newsletter(&list_issue(&list_id)).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(
+        recipients_of(&app, "Release notes issue").await,
+        ["user@example.com"]
+    );
+    let issue_list_id = sqlx::query!("SELECT list_id FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .list_id;
+    assert_eq!(issue_list_id.map(|id| id.to_string()), Some(list_id));
+}
+
+#[tokio::test]
+async fn the_recipient_count_follows_the_list() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    let list_id = create_list(&app, "Release notes", "release-notes")
+        .await
+        .to_string();
+    create_confirmed_subscriber(&app, "reader@example.com", &[]).await;
+    create_confirmed_subscriber(&app, "user@example.com", &[("list", "release-notes")]).await;
+    create_confirmed_subscriber(&app, "other@example.com", &[("list", "release-notes")]).await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/admin/newsletters/recipients", &app.address))
+        .query(&[("segment", ""), ("list_id", &list_id)])
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["recipients"], 2);
+
+    let body: serde_json::Value = app.get_recipient_count("").await.json().await.unwrap();
+    assert_eq!(body["recipients"], 1);
+}
+
+#[tokio::test]
+async fn unsubscribing_from_a_list_keeps_the_other_subscriptions() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    create_list(&app, "Release notes", "release-notes").await;
+    create_confirmed_subscriber(&app, "reader@example.com", &[]).await;
+    create_confirmed_subscriber(&app, "reader@example.com", &[("list", "release-notes")]).await;
+    let subscriber_id = subscription_id(&app, "reader@example.com", "release-notes").await;
+    let token = app
+        .subscription_repo()
+        .issue_token(subscriber_id, TokenPurpose::Unsubscribe)
+        .await
+        .unwrap();
+
+    // The first request asks for a confirmation, the second one removes the subscription
+    app.get_subscription_unsubscribe(token.as_str().into())
+        .await;
+    app.get_subscription_unsubscribe(token.as_str().into())
+        .await;
+
+    assert_eq!(
+        subscription_statuses(&app, "reader@example.com").await,
+        [("newsletter".to_string(), "confirmed".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn unknown_lists_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = subscribe(&app, "reader@example.com", &[("list", "unknown")]).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app
+        .post_publish_newsletter(&list_issue(&uuid::Uuid::new_v4().to_string()))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .api_client
+        .get(format!("{}/admin/subscribers", &app.address))
+        .query(&[("list_id", uuid::Uuid::new_v4().to_string())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_lists() {
+    let app = spawn_app().await;
+
+    let response = app.get_lists().await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app.post_list("Release notes", "release-notes").await;
+    assert_is_redirect_to(&response, "/login");
+}
@@ -1,9 +1,20 @@
 mod admin_dashboard;
+mod archive;
+mod blocklist;
 mod change_password;
+mod drafts;
+mod feed;
 mod health_check;
 mod helpers;
+mod lists;
 mod login;
 mod newsletter;
+mod preferences;
+mod retention;
+mod segments;
 mod subscriptions;
 mod subscriptions_confirm;
+mod suppressions;
+mod tracking;
+mod translations;
 mod unsubscribe;
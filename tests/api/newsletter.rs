@@ -1,41 +1,22 @@
-use crate::helpers::{assert_is_redirect_to, spawn_app, ConfirmationLinks, TestApp};
+use crate::helpers::{
+    assert_is_redirect_to, create_random_confirmed_subscriber, create_random_subscriber, spawn_app,
+    TestApp,
+};
 use wiremock::matchers::{any, method, path};
 use wiremock::{Mock, ResponseTemplate};
-
-async fn create_unconfirmed_subscriber(app: &TestApp) -> ConfirmationLinks {
-    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
-
-    let _mock_guard = Mock::given(path("/email"))
-        .and(method("POST"))
-        .respond_with(ResponseTemplate::new(200))
-        .named("Create unconfirmed subscriber")
-        .expect(1)
-        .mount_as_scoped(&app.email_server)
-        .await;
-
-    app.post_subscriptions(body.into())
-        .await
-        .error_for_status()
-        .unwrap();
-
-    let email_request = &app.get_email_requests().await;
-    app.get_confirmation_links(&email_request)
-}
-
-async fn create_confirmed_subscriber(app: &TestApp) {
-    let confirmation_links = create_unconfirmed_subscriber(app).await;
-    reqwest::get(confirmation_links.html)
-        .await
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
+use zero2prod::domain::idempotency::models::{IdempotencyKey, IdempotentResponse, SavedResponse};
+use zero2prod::domain::newsletter::errors::NewsletterError;
+use zero2prod::domain::newsletter::models::newsletter::NewsletterDto;
+use zero2prod::domain::newsletter::models::segment::Segment;
+use zero2prod::domain::newsletter::ports::NewsletterService;
 
 #[derive(Clone, Debug)]
 struct Newsletter(serde_json::Value);
 impl Newsletter {
     fn new() -> Newsletter {
-        Newsletter(serde_json::json!({}))
+        Newsletter(serde_json::json!({
+            "idempotency_key": uuid::Uuid::new_v4().to_string()
+        }))
     }
     fn title(mut self) -> Newsletter {
         let title_value = "Newsletter title";
@@ -71,14 +52,42 @@ fn build_newsletter() -> serde_json::Value {
         "title": "Newsletter title",
         "text_content": "Newsletter body as plain text",
         "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
     })
 }
 
+fn build_scheduled_newsletter(send_in: chrono::Duration) -> serde_json::Value {
+    let mut body = build_newsletter();
+    body["scheduled_at"] = (chrono::Utc::now() + send_in)
+        .format("%Y-%m-%dT%H:%M")
+        .to_string()
+        .into();
+    body
+}
+
+async fn make_scheduled_issues_due(app: &TestApp) {
+    sqlx::query!(
+        "UPDATE newsletter_issues SET scheduled_at = now() - interval '1 minute' WHERE published_at IS NULL"
+    )
+    .execute(app.subscription_repo().pool())
+    .await
+    .unwrap();
+}
+
+async fn get_scheduled_issue_id(app: &TestApp) -> String {
+    sqlx::query!("SELECT newsletter_issue_id FROM newsletter_issues WHERE published_at IS NULL")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .newsletter_issue_id
+        .to_string()
+}
+
 #[tokio::test]
 async fn newsletters_are_not_delivered_to_unconfirmed_subscribers() {
     // Arrange
     let app = spawn_app().await;
-    create_unconfirmed_subscriber(&app).await;
+    create_random_subscriber(&app).await;
     app.test_user.login(&app).await;
 
     Mock::given(any())
@@ -94,14 +103,17 @@ async fn newsletters_are_not_delivered_to_unconfirmed_subscribers() {
 
     // Assert
     let html_page = app.get_publish_newsletter_html().await;
-    assert!(html_page.contains("<p><i>The newsletter issue has been published!</i></p>"));
+    assert!(html_page.contains(
+        "<p><i>The newsletter issue has been accepted - emails will go out shortly.</i></p>"
+    ));
+    app.dispatch_all_pending_emails().await;
     // Mock verifies on Drop that we haven't sent the newsletter email
 }
 
 #[tokio::test]
 async fn newsletters_are_delivered_to_confirmed_subscribers() {
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
     app.test_user.login(&app).await;
 
     Mock::given(path("/email"))
@@ -117,13 +129,155 @@ async fn newsletters_are_delivered_to_confirmed_subscribers() {
     assert_is_redirect_to(&response, "/admin/newsletters");
 
     let html_page = app.get_publish_newsletter_html().await;
-    assert!(html_page.contains("<p><i>The newsletter issue has been published!</i></p>"));
+    assert!(html_page.contains(
+        "<p><i>The newsletter issue has been accepted - emails will go out shortly.</i></p>"
+    ));
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn newsletters_written_in_markdown_are_delivered_as_html_and_text() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Markdown issue",
+        "text_content": "",
+        "html_content": "",
+        "markdown_content": "# News\n\nRead [the docs](https://example.com/docs).",
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let html_body = body["HtmlBody"].as_str().unwrap();
+    assert!(html_body.contains(
+        r#"<h1>News</h1>
+<p>Read <a href="https://example.com/docs">the docs</a>.</p>"#
+    ));
+    let text_body = body["TextBody"].as_str().unwrap();
+    assert!(text_body.contains("News\n====\n\nRead the docs (https://example.com/docs)."));
+}
+
+#[tokio::test]
+async fn newsletter_footers_match_the_format_of_each_body() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app.post_publish_newsletter(&build_newsletter()).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let html_body = body["HtmlBody"].as_str().unwrap();
+    assert!(html_body.contains(&format!(
+        r#"<a href="{}/subscriptions/unsubscribe?subscription_token="#,
+        app.newsletter_state.url()
+    )));
+    let text_body = body["TextBody"].as_str().unwrap();
+    assert!(text_body.starts_with("Newsletter body as plain text"));
+    assert!(!text_body.contains("<a href="));
+}
+
+#[tokio::test]
+async fn merge_tags_are_filled_in_for_each_subscriber() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+    let subscriber = sqlx::query!("SELECT email, name FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Personal issue",
+        "text_content": "Hi {{name}}, past issues: {{archive_url}}",
+        "html_content": r#"<p>Hi {{ name }} ({{email}})</p><a href="{{unsubscribe_url}}">Leave</a>"#,
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let html_body = body["HtmlBody"].as_str().unwrap();
+    assert!(html_body.contains(&format!(
+        "<p>Hi {} ({})</p>",
+        htmlescape::encode_minimal(&subscriber.name),
+        htmlescape::encode_minimal(&subscriber.email)
+    )));
+    assert!(html_body.contains(&format!(
+        r#"<a href="{}/subscriptions/unsubscribe?subscription_token="#,
+        app.newsletter_state.url()
+    )));
+    let text_body = body["TextBody"].as_str().unwrap();
+    assert!(text_body.contains(&format!(
+        "Hi {}, past issues: {}/newsletters",
+        subscriber.name,
+        app.newsletter_state.url()
+    )));
+}
+
+#[tokio::test]
+async fn issues_with_unknown_merge_tags_are_rejected() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Broken issue",
+        "text_content": "Hi {{nickname}}",
+        "html_content": "<p>Hi {{name}}</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let stored_issues = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    assert_eq!(stored_issues.count, 0);
+    app.dispatch_all_pending_emails().await;
 }
 
 #[tokio::test]
 async fn newsletter_returns_400_for_invalid_data() {
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
     app.test_user.login(&app).await;
 
     let test_cases = vec![
@@ -166,7 +320,7 @@ async fn newsletter_returns_400_for_invalid_data() {
 #[tokio::test]
 async fn both_unsubscribe_links_in_newsletter_return_a_200_if_called() {
     let app: TestApp = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
     let response = app.test_user.login(&app).await;
     assert_is_redirect_to(&response, "/admin/dashboard");
 
@@ -181,12 +335,13 @@ async fn both_unsubscribe_links_in_newsletter_return_a_200_if_called() {
 
     let response = app.post_publish_newsletter(&newsletter_request_body).await;
     assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
 
     let email_newsletter = &app.get_email_requests().await;
-    let confirmation_links = app.get_newsletter_unsubscribe_links(&email_newsletter);
+    let confirmation_links = app.get_newsletter_unsubscribe_links(email_newsletter);
 
     let response_text = reqwest::Client::new()
-        .get(&format!("{}", confirmation_links.plain_text))
+        .get(format!("{}", confirmation_links.plain_text))
         .send()
         .await
         .expect("Failed to execute request");
@@ -199,7 +354,7 @@ async fn both_unsubscribe_links_in_newsletter_return_a_200_if_called() {
     );
 
     let response_html = reqwest::Client::new()
-        .get(&format!("{}", confirmation_links.html))
+        .get(format!("{}", confirmation_links.html))
         .send()
         .await
         .expect("Failed to execute request");
@@ -211,3 +366,611 @@ async fn both_unsubscribe_links_in_newsletter_return_a_200_if_called() {
         confirmation_links.html
     );
 }
+
+#[tokio::test]
+async fn newsletters_carry_one_click_unsubscribe_headers() {
+    let app: TestApp = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app.post_publish_newsletter(&build_newsletter()).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = &app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let header = |name: &str| {
+        body["Headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|header| header["Name"] == name)
+            .map(|header| header["Value"].as_str().unwrap().to_string())
+            .unwrap()
+    };
+    let unsubscribe_links = app.get_newsletter_unsubscribe_links(email_request);
+    // The links of the email are rewritten to reach the test server's port
+    let mut emailed_link = unsubscribe_links.html.clone();
+    emailed_link.set_port(None).unwrap();
+    assert_eq!(header("List-Unsubscribe"), format!("<{}>", emailed_link));
+    assert_eq!(
+        header("List-Unsubscribe-Post"),
+        "List-Unsubscribe=One-Click"
+    );
+
+    // Mail clients POST to the link of the header to unsubscribe in one click
+    let response = reqwest::Client::new()
+        .post(unsubscribe_links.html)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("List-Unsubscribe=One-Click")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let remaining = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM subscriptions"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn publishing_an_issue_does_not_send_emails_until_the_queue_is_drained() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let repo = app.subscription_repo();
+    let queued = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM issue_delivery_queue")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    assert_eq!(queued.count, 2);
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    let queued = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM issue_delivery_queue")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    assert_eq!(queued.count, 0);
+}
+
+#[tokio::test]
+async fn a_failed_delivery_does_not_stop_the_remaining_deliveries() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(422))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn newsletter_creation_is_idempotent() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Submit newsletter form
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let html_page = app.get_publish_newsletter_html().await;
+    assert!(html_page.contains(
+        "<p><i>The newsletter issue has been accepted - emails will go out shortly.</i></p>"
+    ));
+
+    // Submit newsletter form again
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let html_page = app.get_publish_newsletter_html().await;
+    assert!(html_page.contains(
+        "<p><i>The newsletter issue has been accepted - emails will go out shortly.</i></p>"
+    ));
+    app.dispatch_all_pending_emails().await;
+    // Mock verifies on Drop that we have sent the newsletter email once
+}
+
+#[tokio::test]
+async fn concurrent_form_submission_is_handled_gracefully() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Submit two newsletter forms concurrently
+    let newsletter_request_body = build_newsletter();
+    let response1 = app.post_publish_newsletter(&newsletter_request_body);
+    let response2 = app.post_publish_newsletter(&newsletter_request_body);
+    let (response1, response2) = tokio::join!(response1, response2);
+
+    assert_eq!(response1.status(), response2.status());
+    assert_eq!(
+        response1.headers().get("Location"),
+        response2.headers().get("Location")
+    );
+    assert_eq!(
+        response1.text().await.unwrap(),
+        response2.text().await.unwrap()
+    );
+
+    app.dispatch_all_pending_emails().await;
+    // Mock verifies on Drop that we have sent the newsletter email once
+}
+
+#[tokio::test]
+async fn an_issue_is_not_published_again_once_its_response_was_saved() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // A request that took over the key before the first one saved its response
+    let dto: NewsletterDto = serde_json::from_value(newsletter_request_body).unwrap();
+    let key = IdempotencyKey::parse(dto.idempotency_key.clone()).unwrap();
+    let newsletter = dto.try_into().unwrap();
+    let outcome = app
+        .newsletter_service()
+        .publish_newsletter(
+            newsletter,
+            None,
+            Segment::parse("").unwrap(),
+            false,
+            &IdempotentResponse {
+                user_id: app.test_user.user_id,
+                key,
+                response: SavedResponse {
+                    status_code: 303,
+                    headers: vec![],
+                    body: vec![],
+                },
+            },
+        )
+        .await;
+    assert!(matches!(outcome, Err(NewsletterError::Conflict(_))));
+
+    let issues = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    assert_eq!(issues.count, 1);
+    app.dispatch_all_pending_emails().await;
+    // Mock verifies on Drop that we have sent the newsletter email once
+}
+
+#[tokio::test]
+async fn newsletter_form_contains_an_idempotency_key() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let html_page = app.get_publish_newsletter_html().await;
+
+    assert!(html_page.contains(r#"name="idempotency_key""#));
+    assert!(!html_page.contains("{idempotency_key}"));
+}
+
+#[tokio::test]
+async fn deliveries_that_keep_failing_are_dead_lettered_and_can_be_replayed() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let failing_mock = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount_as_scoped(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+    drop(failing_mock);
+
+    let repo = app.subscription_repo();
+    let dead_letter = sqlx::query!(
+        "SELECT newsletter_issue_id, subscriber_email FROM issue_delivery_dead_letters"
+    )
+    .fetch_one(repo.pool())
+    .await
+    .unwrap();
+    let html_page = app.get_dead_letters_html().await;
+    assert!(html_page.contains(&dead_letter.subscriber_email));
+
+    let response = app
+        .post_replay_dead_letter(&serde_json::json!({
+            "newsletter_issue_id": dead_letter.newsletter_issue_id,
+            "subscriber_email": dead_letter.subscriber_email,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters/dead_letters");
+    let html_page = app.get_dead_letters_html().await;
+    assert!(html_page.contains("<p><i>The delivery has been queued again.</i></p>"));
+    assert!(!html_page.contains(&dead_letter.subscriber_email));
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn issue_page_reports_delivery_totals_and_undelivered_recipients() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(422))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = build_newsletter();
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    let repo = app.subscription_repo();
+    let issue = sqlx::query!("SELECT newsletter_issue_id FROM newsletter_issues")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    let issue_id = issue.newsletter_issue_id.to_string();
+
+    let html_page = app.get_issue(&issue_id).await.text().await.unwrap();
+    assert!(html_page.contains("<li>Recipients: 2</li>"));
+    assert!(html_page.contains("<li>Queued: 2</li>"));
+
+    app.dispatch_all_pending_emails().await;
+
+    let html_page = app.get_issues_html().await;
+    assert!(html_page.contains(&format!("/admin/newsletters/issues/{}", issue_id)));
+
+    let failed = sqlx::query!(
+        "SELECT subscriber_email, attempts FROM issue_delivery_log WHERE status = 'failed'"
+    )
+    .fetch_one(repo.pool())
+    .await
+    .unwrap();
+    assert_eq!(failed.attempts, 1);
+    let sent = sqlx::query!(
+        "SELECT subscriber_email, sent_at FROM issue_delivery_log WHERE status = 'sent'"
+    )
+    .fetch_one(repo.pool())
+    .await
+    .unwrap();
+    assert!(sent.sent_at.is_some());
+
+    let html_page = app.get_issue(&issue_id).await.text().await.unwrap();
+    assert!(html_page.contains("<li>Sent: 1</li>"));
+    assert!(html_page.contains("<li>Failed: 1</li>"));
+    assert!(html_page.contains("<li>Queued: 0</li>"));
+    assert!(html_page.contains(&failed.subscriber_email));
+    assert!(!html_page.contains(&sent.subscriber_email));
+}
+
+#[tokio::test]
+async fn deliveries_to_subscribers_who_left_are_reported_as_skipped() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app.post_publish_newsletter(&build_newsletter()).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // The subscriber cancels before the worker gets to the delivery
+    let repo = app.subscription_repo();
+    sqlx::query!("UPDATE subscriptions SET status = 'cancellation_confirmed'")
+        .execute(repo.pool())
+        .await
+        .unwrap();
+    app.dispatch_all_pending_emails().await;
+
+    let skipped = sqlx::query!("SELECT status, last_error FROM issue_delivery_log")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    assert_eq!(skipped.status, "skipped");
+    assert_eq!(
+        skipped.last_error.as_deref(),
+        Some("Subscriber is no longer confirmed")
+    );
+    let issue = sqlx::query!("SELECT newsletter_issue_id FROM newsletter_issues")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    let html_page = app
+        .get_issue(&issue.newsletter_issue_id.to_string())
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("<li>Skipped: 1</li>"));
+    assert!(html_page.contains("<li>Failed: 0</li>"));
+}
+
+#[tokio::test]
+async fn issue_page_returns_404_for_an_unknown_issue() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app.get_issue(&uuid::Uuid::new_v4().to_string()).await;
+
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn scheduled_issues_are_not_delivered_before_they_are_due() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app
+        .post_publish_newsletter(&build_scheduled_newsletter(chrono::Duration::hours(1)))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters/scheduled");
+
+    app.release_due_scheduled_issues().await;
+    app.dispatch_all_pending_emails().await;
+
+    let html_page = app.get_scheduled_issues_html().await;
+    assert!(html_page.contains("has been scheduled for"));
+    assert!(html_page.contains("Newsletter title"));
+}
+
+#[tokio::test]
+async fn due_scheduled_issues_are_delivered_to_confirmed_subscribers() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_publish_newsletter(&build_scheduled_newsletter(chrono::Duration::hours(1)))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters/scheduled");
+    make_scheduled_issues_due(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    app.release_due_scheduled_issues().await;
+    app.dispatch_all_pending_emails().await;
+
+    let html_page = app.get_scheduled_issues_html().await;
+    assert!(!html_page.contains("Newsletter title"));
+}
+
+#[tokio::test]
+async fn cancelled_scheduled_issues_are_never_delivered() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    app.post_publish_newsletter(&build_scheduled_newsletter(chrono::Duration::hours(1)))
+        .await;
+    let newsletter_issue_id = get_scheduled_issue_id(&app).await;
+
+    let response = app.post_cancel_scheduled_issue(&newsletter_issue_id).await;
+    assert_is_redirect_to(&response, "/admin/newsletters/scheduled");
+    let html_page = app.get_scheduled_issues_html().await;
+    assert!(html_page.contains("<p><i>The scheduled issue has been cancelled.</i></p>"));
+    assert!(!html_page.contains("Newsletter title"));
+
+    make_scheduled_issues_due(&app).await;
+    app.release_due_scheduled_issues().await;
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn scheduled_issues_can_be_edited_and_rescheduled() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    app.post_publish_newsletter(&build_scheduled_newsletter(chrono::Duration::hours(1)))
+        .await;
+    let newsletter_issue_id = get_scheduled_issue_id(&app).await;
+
+    let send_at = (chrono::Utc::now() + chrono::Duration::days(2))
+        .format("%Y-%m-%dT%H:%M")
+        .to_string();
+    let response = app
+        .post_reschedule_issue(
+            &newsletter_issue_id,
+            &serde_json::json!({
+                "title": "Updated title",
+                "text_content": "Updated body as plain text",
+                "html_content": "<p>Updated body as HTML</p>",
+                "scheduled_at": send_at,
+            }),
+        )
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters/scheduled");
+
+    let html_page = app.get_scheduled_issues_html().await;
+    assert!(html_page.contains("<p><i>The scheduled issue has been updated.</i></p>"));
+    assert!(html_page.contains("Updated title"));
+    assert!(html_page.contains(&send_at.replace('T', " ")));
+}
+
+#[tokio::test]
+async fn issues_cannot_be_scheduled_in_the_past() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_publish_newsletter(&build_scheduled_newsletter(-chrono::Duration::hours(1)))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_issues_are_only_sent_to_the_test_recipients() {
+    let app = spawn_app().await;
+    create_random_confirmed_subscriber(&app).await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+
+    let mut body = build_newsletter();
+    body["test_recipients"] = "editor@example.com, reviewer@example.com".into();
+    let response = app.post_send_test_issue(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains(
+        "<p><i>A test issue has been sent to editor@example.com, reviewer@example.com.</i></p>"
+    ));
+    // The form keeps the content of the issue being written
+    assert!(html_page.contains(&htmlescape::encode_attribute("Newsletter title")));
+
+    // Test issues are not stored nor queued for the subscribers
+    app.dispatch_all_pending_emails().await;
+    let repo = app.subscription_repo();
+    let issues = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM newsletter_issues")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    assert_eq!(issues.count, 0);
+    let deliveries = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM issue_delivery_log")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap();
+    assert_eq!(deliveries.count, 0);
+
+    // The last two requests are the test issues, the first one confirmed the subscriber
+    let email_requests = app.email_server.received_requests().await.unwrap();
+    let recipients: Vec<String> = email_requests[email_requests.len() - 2..]
+        .iter()
+        .map(|r| {
+            let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+            assert!(body["TextBody"]
+                .as_str()
+                .unwrap()
+                .contains("/subscriptions/unsubscribe?subscription_token="));
+            body["To"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert!(recipients.contains(&"editor@example.com".to_string()));
+    assert!(recipients.contains(&"reviewer@example.com".to_string()));
+}
+
+#[tokio::test]
+async fn test_issues_with_an_invalid_recipient_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let mut body = build_newsletter();
+    body["test_recipients"] = "editor@example.com, not-an-email".into();
+    let response = app.post_send_test_issue(&body).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
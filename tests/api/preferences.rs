@@ -0,0 +1,428 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, create_list, emails_with_subject,
+    mock_email_server, publish_issue, spawn_app, subscription_id, TestApp,
+};
+use secrecy::ExposeSecret;
+use wiremock::matchers::any;
+use wiremock::{Mock, ResponseTemplate};
+use zero2prod::domain::new_subscriber::models::token::TokenPurpose;
+use zero2prod::domain::new_subscriber::ports::SubscriberRepository;
+
+/// Subscribes and confirms `email` to the default list and returns a preferences
+/// token of that subscription
+async fn create_confirmed_subscriber_with_token(app: &TestApp, email: &str) -> String {
+    create_confirmed_subscriber(app, email, &[]).await;
+    let subscriber_id = subscription_id(app, email, "").await;
+    app.subscription_repo()
+        .issue_token(subscriber_id, TokenPurpose::Preferences)
+        .await
+        .unwrap()
+        .as_str()
+        .to_string()
+}
+
+/// Slugs of the lists `email` is subscribed to
+async fn subscribed_lists(app: &TestApp, email: &str) -> Vec<String> {
+    sqlx::query!(
+        r#"SELECT l.slug
+        FROM subscriptions s
+        JOIN lists l ON l.list_id = s.list_id
+        WHERE s.email = $1 AND s.status = 'confirmed'
+        ORDER BY l.slug"#,
+        email
+    )
+    .fetch_all(app.subscription_repo().pool())
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|r| r.slug)
+    .collect()
+}
+
+#[tokio::test]
+async fn the_preferences_page_requires_the_token_of_a_confirmed_subscription() {
+    let app = spawn_app().await;
+
+    let response = app.get_preferences("").await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.get_preferences("unknowntoken1234567890abc").await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn subscribers_can_change_their_name() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+
+    let response = app
+        .post_preferences(&[
+            ("subscription_token", &token),
+            ("name", "Ursula Le Guin"),
+            ("frequency", "every_issue"),
+            ("pause_weeks", ""),
+        ])
+        .await;
+    assert_is_redirect_to(
+        &response,
+        &format!("/subscriptions/preferences?subscription_token={}", token),
+    );
+
+    let html_page = app.get_preferences(&token).await.text().await.unwrap();
+    assert!(html_page.contains("<p><i>Your preferences have been saved.</i></p>"));
+    assert!(html_page.contains(&htmlescape::encode_attribute("Ursula Le Guin")));
+}
+
+#[tokio::test]
+async fn subscribers_can_join_and_leave_lists() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    let release_notes = create_list(&app, "Release notes", "release-notes")
+        .await
+        .to_string();
+    let events = create_list(&app, "Events", "events").await.to_string();
+    create_confirmed_subscriber(&app, "reader@example.com", &[("list", "events")]).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("lists", &release_notes),
+        ("frequency", "every_issue"),
+    ])
+    .await;
+
+    // The list of the token stays, the unchecked one is left
+    assert_eq!(
+        subscribed_lists(&app, "reader@example.com").await,
+        ["newsletter", "release-notes"]
+    );
+    let html_page = app.get_preferences(&token).await.text().await.unwrap();
+    assert!(html_page.contains(&format!(r#"value="{}" checked>"#, release_notes)));
+    assert!(html_page.contains(&format!(r#"value="{}">"#, events)));
+}
+
+#[tokio::test]
+async fn suppressed_addresses_cannot_change_their_preferences() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    app.post_postmark_webhook(
+        &serde_json::json!({
+            "RecordType": "SpamComplaint",
+            "Type": "SpamComplaint",
+            "Description": "The subscriber explicitly marked this message as spam.",
+            "Email": "reader@example.com",
+        }),
+        app.webhook_secret.expose_secret(),
+    )
+    .await
+    .error_for_status()
+    .unwrap();
+
+    let response = app
+        .post_preferences(&[
+            ("subscription_token", &token),
+            ("name", "Subscriber"),
+            ("frequency", "every_issue"),
+        ])
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(subscribed_lists(&app, "reader@example.com")
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn suppressed_subscriptions_are_neither_left_nor_confirmed_from_the_preferences() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    let events = create_list(&app, "Events", "events").await.to_string();
+    create_confirmed_subscriber(&app, "reader@example.com", &[("list", "events")]).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    sqlx::query!(
+        "UPDATE subscriptions SET status = 'bounced'
+        WHERE list_id = (SELECT list_id FROM lists WHERE slug = 'events')"
+    )
+    .execute(app.subscription_repo().pool())
+    .await
+    .unwrap();
+
+    // Unchecking the list, then checking it again
+    for lists in [None, Some(events.as_str())] {
+        let mut fields = vec![
+            ("subscription_token", token.as_str()),
+            ("name", "Subscriber"),
+            ("frequency", "every_issue"),
+        ];
+        fields.extend(lists.map(|list| ("lists", list)));
+        app.post_preferences(&fields)
+            .await
+            .error_for_status()
+            .unwrap();
+
+        let status = sqlx::query!(
+            "SELECT s.status FROM subscriptions s JOIN lists l ON l.list_id = s.list_id
+            WHERE l.slug = 'events'"
+        )
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .status;
+        assert_eq!(status, "bounced");
+    }
+}
+
+#[tokio::test]
+async fn weekly_digests_replace_the_immediate_issues() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("frequency", "weekly_digest"),
+    ])
+    .await
+    .error_for_status()
+    .unwrap();
+    app.test_user.login(&app).await;
+
+    publish_issue(&app, "First issue", "<p>First body</p>").await;
+    publish_issue(&app, "Second issue", "<p>Second body</p>").await;
+    app.dispatch_all_pending_emails().await;
+    assert!(emails_with_subject(&app, "First issue").await.is_empty());
+
+    // Nothing is sent before a week has gone by
+    app.send_due_digests().await;
+    assert!(emails_with_subject(&app, "Your weekly digest")
+        .await
+        .is_empty());
+
+    sqlx::query!("UPDATE subscriptions SET last_digest_at = now() - interval '8 days'",)
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE newsletter_issues SET published_at = now() - interval '1 day'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    app.send_due_digests().await;
+
+    let digests = emails_with_subject(&app, "Your weekly digest").await;
+    assert_eq!(digests.len(), 1);
+    let html = digests[0]["HtmlBody"].as_str().unwrap();
+    assert!(html.contains("<h1>First issue</h1>"));
+    assert!(html.contains("<p>Second body</p>"));
+    assert!(html.contains("/subscriptions/preferences?subscription_token="));
+
+    // The digest is only sent once a week
+    app.send_due_digests().await;
+    assert_eq!(
+        emails_with_subject(&app, "Your weekly digest").await.len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn weekly_digests_that_fail_to_send_are_dead_lettered_and_can_be_replayed() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("frequency", "weekly_digest"),
+    ])
+    .await
+    .error_for_status()
+    .unwrap();
+    app.test_user.login(&app).await;
+    let (issue_id, _) = publish_issue(&app, "First issue", "<p>First body</p>").await;
+    app.dispatch_all_pending_emails().await;
+    sqlx::query!("UPDATE subscriptions SET last_digest_at = now() - interval '8 days'",)
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE newsletter_issues SET published_at = now() - interval '1 day'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+
+    app.email_server.reset().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&app.email_server)
+        .await;
+    app.send_due_digests().await;
+
+    // The digest is not retried: its issues wait in dead letters instead
+    let html_page = app.get_dead_letters_html().await;
+    assert!(html_page.contains("First issue"));
+    assert!(html_page.contains("reader@example.com"));
+    app.email_server.reset().await;
+    mock_email_server(&app).await;
+    sqlx::query!("UPDATE subscriptions SET digest_locked_until = now() - interval '1 second'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    app.send_due_digests().await;
+    assert!(emails_with_subject(&app, "Your weekly digest")
+        .await
+        .is_empty());
+
+    let response = app
+        .post_replay_dead_letter(&serde_json::json!({
+            "newsletter_issue_id": issue_id,
+            "subscriber_email": "reader@example.com",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters/dead_letters");
+    app.dispatch_all_pending_emails().await;
+    assert_eq!(emails_with_subject(&app, "First issue").await.len(), 1);
+}
+
+#[tokio::test]
+async fn weekly_digests_without_issues_issue_no_link_tokens() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("frequency", "weekly_digest"),
+    ])
+    .await
+    .error_for_status()
+    .unwrap();
+    sqlx::query!("UPDATE subscriptions SET last_digest_at = now() - interval '8 days'",)
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    let count_tokens = || async {
+        sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM subscription_tokens"#)
+            .fetch_one(app.subscription_repo().pool())
+            .await
+            .unwrap()
+            .count
+    };
+    let tokens_before = count_tokens().await;
+
+    app.send_due_digests().await;
+
+    assert!(emails_with_subject(&app, "Your weekly digest")
+        .await
+        .is_empty());
+    assert_eq!(count_tokens().await, tokens_before);
+}
+
+#[tokio::test]
+async fn paused_subscribers_do_not_receive_issues() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("frequency", "every_issue"),
+        ("pause_weeks", "2"),
+    ])
+    .await
+    .error_for_status()
+    .unwrap();
+    app.test_user.login(&app).await;
+
+    publish_issue(&app, "Paused issue", "<p>Body</p>").await;
+    app.dispatch_all_pending_emails().await;
+    assert!(emails_with_subject(&app, "Paused issue").await.is_empty());
+    let html_page = app.get_preferences(&token).await.text().await.unwrap();
+    assert!(html_page.contains("Your subscriptions are paused until"));
+
+    app.post_preferences(&[
+        ("subscription_token", &token),
+        ("name", "Subscriber"),
+        ("frequency", "every_issue"),
+        ("pause_weeks", "0"),
+    ])
+    .await
+    .error_for_status()
+    .unwrap();
+    publish_issue(&app, "Resumed issue", "<p>Body</p>").await;
+    app.dispatch_all_pending_emails().await;
+    assert_eq!(emails_with_subject(&app, "Resumed issue").await.len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_preferences_are_rejected() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    let token = create_confirmed_subscriber_with_token(&app, "reader@example.com").await;
+    let test_cases = [
+        (("frequency", "daily"), "an unknown frequency"),
+        (("pause_weeks", "60"), "a pause longer than a year"),
+        (("lists", "not-a-list"), "a malformed list"),
+        (("name", ""), "an empty name"),
+    ];
+
+    for ((key, value), description) in test_cases {
+        let mut fields = vec![
+            ("subscription_token", token.as_str()),
+            ("frequency", "every_issue"),
+        ];
+        if key != "name" {
+            fields.push(("name", "Subscriber"));
+        }
+        fields.push((key, value));
+
+        let response = app.post_preferences(&fields).await;
+
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject {}",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn issues_link_to_the_preferences_page() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "reader@example.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    publish_issue(&app, "Issue", "<p>Body</p>").await;
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.get_email_requests().await;
+    let links = app.get_newsletter_preferences_links(&email_request);
+    for link in [links.html, links.plain_text] {
+        let html_page = reqwest::get(link)
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(html_page.contains("Preferences of reader@example.com"));
+    }
+}
+
+#[tokio::test]
+async fn other_tokens_cannot_open_the_preferences_page() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.post_subscriptions("name=le%20guin&email=ursula_le_guin%40gmail.com".into())
+        .await;
+    let (_, unsubscribe_token) = app.confirm_subscription().await.unwrap();
+
+    let response = app.get_preferences(unsubscribe_token.as_str()).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
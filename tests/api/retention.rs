@@ -0,0 +1,170 @@
+use crate::helpers::{assert_is_redirect_to, mock_email_server, spawn_app, subscribe};
+use sqlx::PgPool;
+use zero2prod::domain::new_subscriber::ports::SubscriptionService;
+
+async fn remaining_emails(pool: &PgPool) -> Vec<String> {
+    sqlx::query!("SELECT email FROM subscriptions ORDER BY email")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.email)
+        .collect()
+}
+
+async fn orphan_tokens(pool: &PgPool) -> i64 {
+    sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM subscription_tokens
+        WHERE subscriber_id NOT IN (SELECT id FROM subscriptions)"#
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+    .count
+}
+
+#[tokio::test]
+async fn unconfirmed_subscriptions_are_removed_once_they_are_too_old() {
+    let app = spawn_app().await;
+
+    mock_email_server(&app).await;
+
+    subscribe(&app, "ursula_le_guin@gmail.com", &[("name", "le guin")])
+        .await
+        .error_for_status()
+        .unwrap();
+    app.confirm_subscription().await.unwrap();
+    subscribe(&app, "octavia_butler@gmail.com", &[("name", "octavia")])
+        .await
+        .error_for_status()
+        .unwrap();
+    subscribe(&app, "ted_chiang@gmail.com", &[("name", "ted")])
+        .await
+        .error_for_status()
+        .unwrap();
+    let pool = app.subscription_repo().pool().clone();
+    sqlx::query!(
+        "UPDATE subscriptions SET subscribed_at = now() - interval '8 days'
+        WHERE email <> 'ted_chiang@gmail.com'"
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = app.subscription_service().apply_retention().await.unwrap();
+
+    assert_eq!(report.unconfirmed_subscriptions, 1);
+    assert_eq!(report.pending_cancellations, 0);
+    assert_eq!(report.tokens, 1);
+    assert_eq!(
+        remaining_emails(&pool).await,
+        ["ted_chiang@gmail.com", "ursula_le_guin@gmail.com"]
+    );
+    assert_eq!(orphan_tokens(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn unconfirmed_cancellations_are_completed_once_they_are_too_old() {
+    let app = spawn_app().await;
+
+    mock_email_server(&app).await;
+
+    subscribe(&app, "ursula_le_guin@gmail.com", &[("name", "le guin")])
+        .await
+        .error_for_status()
+        .unwrap();
+    let (_, token) = app.confirm_subscription().await.unwrap();
+    app.get_subscription_unsubscribe(token.as_str().into())
+        .await
+        .error_for_status()
+        .unwrap();
+    let pool = app.subscription_repo().pool().clone();
+    let requested_at = sqlx::query!("SELECT cancellation_requested_at FROM subscriptions")
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .cancellation_requested_at;
+    assert!(requested_at.is_some());
+
+    // A recent cancellation is left for the subscriber to confirm
+    let report = app.subscription_service().apply_retention().await.unwrap();
+    assert!(report.is_empty());
+
+    sqlx::query!("UPDATE subscriptions SET cancellation_requested_at = now() - interval '31 days'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let report = app.subscription_service().apply_retention().await.unwrap();
+
+    assert_eq!(report.pending_cancellations, 1);
+    assert!(remaining_emails(&pool).await.is_empty());
+    assert_eq!(orphan_tokens(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn expired_tokens_are_removed() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    subscribe(&app, "ursula_le_guin@gmail.com", &[("name", "le guin")])
+        .await
+        .error_for_status()
+        .unwrap();
+    app.confirm_subscription().await.unwrap();
+    let pool = app.subscription_repo().pool().clone();
+    sqlx::query!(
+        "UPDATE subscription_tokens SET expires_at = now() - interval '1 day'
+        WHERE purpose = 'confirm'"
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = app.subscription_service().apply_retention().await.unwrap();
+
+    assert_eq!(report.expired_tokens, 1);
+    assert_eq!(report.tokens, 0);
+    assert_eq!(remaining_emails(&pool).await, ["ursula_le_guin@gmail.com"]);
+    let purposes: Vec<String> = sqlx::query!("SELECT purpose FROM subscription_tokens")
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.purpose)
+        .collect();
+    assert_eq!(purposes, ["unsubscribe"]);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_apply_the_retention_policy() {
+    let app = spawn_app().await;
+
+    let response = app.post_apply_retention().await;
+
+    assert_is_redirect_to(&response, "/login");
+}
+
+#[tokio::test]
+async fn the_retention_policy_can_be_applied_on_demand() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    mock_email_server(&app).await;
+
+    subscribe(&app, "ursula_le_guin@gmail.com", &[("name", "le guin")])
+        .await
+        .error_for_status()
+        .unwrap();
+    sqlx::query!("UPDATE subscriptions SET subscribed_at = now() - interval '8 days'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+
+    let response = app.post_apply_retention().await;
+    assert_is_redirect_to(&response, "/admin/subscribers");
+
+    let html_page = app.get_subscribers_html().await;
+    assert!(html_page.contains(
+        "<p><i>Removed 1 unconfirmed subscriptions and 0 pending cancellations.</i></p>"
+    ));
+    assert!(!html_page.contains("ursula_le_guin@gmail.com"));
+}
@@ -0,0 +1,216 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, mock_email_server, recipient_count,
+    recipients_of, spawn_app, subscription_id,
+};
+
+fn segmented_issue(segment: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": "Segmented issue",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "segment": segment,
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    })
+}
+
+#[tokio::test]
+async fn issues_published_to_a_segment_only_reach_matching_subscribers() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "rustacean@example.com", &[("tags", "rust")]).await;
+    create_confirmed_subscriber(&app, "unpaid@example.com", &[("tags", "rust, unpaid")]).await;
+    create_confirmed_subscriber(&app, "gopher@example.com", &[("tags", "go")]).await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_publish_newsletter(&segmented_issue("rust AND NOT unpaid"))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(
+        recipients_of(&app, "Segmented issue").await,
+        ["rustacean@example.com"]
+    );
+}
+
+#[tokio::test]
+async fn published_issues_record_their_segment() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    app.post_publish_newsletter(&segmented_issue("beta or (rust and not unpaid)"))
+        .await;
+
+    let record = sqlx::query!("SELECT newsletter_issue_id, segment FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    assert_eq!(
+        record.segment.as_deref(),
+        Some("beta OR rust AND NOT unpaid")
+    );
+    let html_page = app
+        .get_issue(&record.newsletter_issue_id.to_string())
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("<code>beta OR rust AND NOT unpaid</code>"));
+}
+
+#[tokio::test]
+async fn issues_published_without_a_segment_reach_every_confirmed_subscriber() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "rustacean@example.com", &[("tags", "rust")]).await;
+    create_confirmed_subscriber(&app, "untagged@example.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    app.post_publish_newsletter(&segmented_issue("")).await;
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(
+        recipients_of(&app, "Segmented issue").await,
+        ["rustacean@example.com", "untagged@example.com"]
+    );
+    let segment = sqlx::query!("SELECT segment FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .segment;
+    assert_eq!(segment, None);
+}
+
+#[tokio::test]
+async fn invalid_segments_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    for segment in ["rust AND", "(rust", "rust & go", "not"] {
+        let response = app.post_publish_newsletter(&segmented_issue(segment)).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject the segment {}",
+            segment
+        );
+
+        let response = app.get_recipient_count(segment).await;
+        assert_eq!(response.status().as_u16(), 400);
+    }
+}
+
+#[tokio::test]
+async fn the_recipient_count_follows_the_segment() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "rustacean@example.com", &[("tags", "rust")]).await;
+    create_confirmed_subscriber(&app, "unpaid@example.com", &[("tags", "rust, unpaid")]).await;
+    create_confirmed_subscriber(&app, "gopher@example.com", &[("tags", "go")]).await;
+    app.test_user.login(&app).await;
+
+    assert_eq!(recipient_count(&app, "").await, 3);
+    assert_eq!(recipient_count(&app, "rust").await, 2);
+    assert_eq!(recipient_count(&app, "rust AND NOT unpaid").await, 1);
+    assert_eq!(recipient_count(&app, "go OR unpaid").await, 2);
+    assert_eq!(recipient_count(&app, "beta").await, 0);
+    assert_eq!(recipient_count(&app, "NOT (go OR unpaid)").await, 1);
+}
+
+#[tokio::test]
+async fn the_publish_forms_share_the_recipient_count_script() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let html_page = app.get_publish_newsletter_html().await;
+    assert!(html_page.contains(r#"<script src="/admin/newsletters/recipients.js"></script>"#));
+
+    let response = app
+        .api_client
+        .get(format!("{}/admin/newsletters/recipients.js", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("updateRecipientCount"));
+}
+
+#[tokio::test]
+async fn subscribers_can_be_tagged_from_the_admin_ui() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "rustacean@example.com", &[("tags", "rust")]).await;
+    app.test_user.login(&app).await;
+    let subscriber_id = subscription_id(&app, "rustacean@example.com", "")
+        .await
+        .to_string();
+
+    let response = app
+        .post_subscriber_tags(&subscriber_id, "Beta, early-adopter")
+        .await;
+    assert_is_redirect_to(&response, "/admin/subscribers");
+
+    let html_page = app.get_subscribers_html().await;
+    assert!(html_page.contains("<p><i>The tags have been saved.</i></p>"));
+    assert!(html_page.contains(&htmlescape::encode_attribute("beta, early-adopter")));
+    assert_eq!(recipient_count(&app, "rust").await, 0);
+    assert_eq!(recipient_count(&app, "beta AND early-adopter").await, 1);
+}
+
+#[tokio::test]
+async fn invalid_tags_are_rejected() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "rustacean@example.com", &[("tags", "rust")]).await;
+    app.test_user.login(&app).await;
+    let subscriber_id = subscription_id(&app, "rustacean@example.com", "")
+        .await
+        .to_string();
+
+    let response = app.post_subscriber_tags(&subscriber_id, "c++").await;
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.post_subscriber_tags(&subscriber_id, "rust, not").await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let body = serde_urlencoded::to_string(serde_json::json!({
+        "name": "Subscriber",
+        "email": "tagged@example.com",
+        "tags": "early adopter!",
+    }))
+    .unwrap();
+    let response = app.post_subscriptions(body).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn tagging_an_unknown_subscriber_returns_404() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_subscriber_tags(&uuid::Uuid::new_v4().to_string(), "rust")
+        .await;
+
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_subscribers_and_segments() {
+    let app = spawn_app().await;
+
+    let response = app.get_subscribers().await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app
+        .post_subscriber_tags(&uuid::Uuid::new_v4().to_string(), "rust")
+        .await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app.get_recipient_count("rust").await;
+    assert_is_redirect_to(&response, "/login");
+}
@@ -1,12 +1,12 @@
-use crate::helpers::spawn_app;
-use serde_urlencoded;
+use crate::helpers::{spawn_app, TestApp};
+use chrono::TimeDelta;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, ResponseTemplate};
 use zero2prod::domain::new_subscriber::models::subscriber::{
     NewSubscriberRequest, SubscriberStatus,
 };
 use zero2prod::domain::new_subscriber::models::token::SubscriptionToken;
-use zero2prod::domain::new_subscriber::ports::SubscriberRepository;
+use zero2prod::domain::new_subscriber::ports::{ListRepository, SubscriberRepository};
 
 #[tokio::test]
 async fn subscribe_returns_200_for_valid_form_data() {
@@ -23,6 +23,49 @@ async fn subscribe_returns_200_for_valid_form_data() {
     assert_eq!(200, response.status().as_u16());
 }
 
+#[tokio::test]
+async fn subscribe_renders_a_page_asking_to_check_the_inbox() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("<h1>Thanks for subscribing!</h1>"));
+    assert!(html_page.contains("Check your inbox"));
+}
+
+#[tokio::test]
+async fn subscribe_renders_the_page_in_the_language_of_the_browser() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    let response = app
+        .api_client
+        .post(format!("{}/subscriptions", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept-Language", "fr-FR, en;q=0.5")
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains(r#"<html lang="fr">"#));
+    assert!(html_page.contains("<h1>Merci de votre inscription !</h1>"));
+}
+
 #[tokio::test]
 async fn subscribe_persists_the_new_subscriber() {
     let app = spawn_app().await;
@@ -38,10 +81,15 @@ async fn subscribe_persists_the_new_subscriber() {
 
     app.post_subscriptions(body.into()).await;
 
-    let (updated_subscriber, _) = app
-        .subscription_service()
-        .repo
-        .retrieve_or_insert(subscriber_request, token)
+    let repo = &app.subscription_service().repo;
+    let default_list = repo.get_default_list().await.unwrap();
+    let (updated_subscriber, _) = repo
+        .retrieve_or_insert(
+            default_list.list_id,
+            subscriber_request,
+            token,
+            TimeDelta::minutes(10),
+        )
         .await
         .expect("Failed to fetch saved subscription.");
 
@@ -128,13 +176,50 @@ async fn subscribe_sends_a_confirmation_email_with_a_link() {
     app.post_subscriptions(body.into()).await;
 
     let email_request = &app.email_server.received_requests().await.unwrap()[0];
-    let confirmation_links = app.get_confirmation_links(&email_request);
+    let confirmation_links = app.get_confirmation_links(email_request);
 
     assert_eq!(confirmation_links.html, confirmation_links.plain_text);
 }
 
 #[tokio::test]
-async fn subscribe_sends_two_confirmation_emails_for_valid_data_sent_twice() {
+async fn confirmation_email_has_an_html_link_and_a_plain_text_link() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert!(body["HtmlBody"].as_str().unwrap().contains("<a href="));
+    assert!(!body["TextBody"].as_str().unwrap().contains('<'));
+}
+
+#[tokio::test]
+async fn confirmation_emails_have_no_list_unsubscribe_header() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert!(body.get("Headers").is_none());
+}
+
+#[tokio::test]
+async fn subscribe_sends_two_confirmation_emails_for_valid_data_sent_twice_after_the_cooldown() {
     let app = spawn_app().await;
     let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
 
@@ -146,6 +231,7 @@ async fn subscribe_sends_two_confirmation_emails_for_valid_data_sent_twice() {
         .await;
 
     app.post_subscriptions(body.into()).await;
+    app.end_confirmation_cooldown().await;
     app.post_subscriptions(body.into()).await;
 
     let email_n_request = app.email_server.received_requests().await.unwrap().len();
@@ -153,6 +239,59 @@ async fn subscribe_sends_two_confirmation_emails_for_valid_data_sent_twice() {
     assert_eq!(email_n_request, 2);
 }
 
+#[tokio::test]
+async fn subscribing_again_within_the_cooldown_sends_no_email() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let first_response = app.post_subscriptions(body.into()).await;
+    let second_response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(first_response.status().as_u16(), 200);
+    assert_eq!(second_response.status().as_u16(), 200);
+    assert_eq!(
+        first_response.text().await.unwrap(),
+        second_response.text().await.unwrap()
+    );
+    let tokens = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM subscription_tokens"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(tokens, 1);
+}
+
+#[tokio::test]
+async fn subscribing_again_records_when_the_confirmation_email_was_sent() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    app.end_confirmation_cooldown().await;
+    app.post_subscriptions(body.into()).await;
+
+    let last_sent_at = sqlx::query!("SELECT last_confirmation_sent_at FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .last_confirmation_sent_at
+        .unwrap();
+    assert!(chrono::Utc::now() - last_sent_at < TimeDelta::minutes(1));
+}
+
 #[tokio::test]
 async fn subscribe_fails_if_there_is_a_fatal_database_error() {
     // Arrange
@@ -171,3 +310,93 @@ async fn subscribe_fails_if_there_is_a_fatal_database_error() {
     // Assert
     assert_eq!(response.status().as_u16(), 500);
 }
+
+async fn stored_locale(app: &TestApp) -> Option<String> {
+    sqlx::query!("SELECT locale FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .expect("Failed to fetch saved subscription.")
+        .locale
+}
+
+#[tokio::test]
+async fn subscribe_sends_the_confirmation_email_in_the_locale_picked_on_the_form() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com&locale=fr";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["Subject"], "Bienvenue");
+    assert!(body["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains("confirmer votre inscription"));
+    assert_eq!(stored_locale(&app).await.as_deref(), Some("fr"));
+}
+
+#[tokio::test]
+async fn subscribe_negotiates_the_locale_from_accept_language() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.api_client
+        .post(format!("{}/subscriptions", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept-Language", "de-CH, de;q=0.9, fr-FR;q=0.8, en;q=0.5")
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(stored_locale(&app).await.as_deref(), Some("fr"));
+}
+
+#[tokio::test]
+async fn subscribe_falls_back_to_the_default_locale() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.api_client
+        .post(format!("{}/subscriptions", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept-Language", "de-CH, de;q=0.9")
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["Subject"], "Welcome");
+    assert_eq!(stored_locale(&app).await, None);
+}
+
+#[tokio::test]
+async fn subscribe_returns_a_400_for_an_unsupported_locale() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com&locale=xx";
+
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
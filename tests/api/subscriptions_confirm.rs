@@ -3,6 +3,8 @@ use futures::stream::{self, StreamExt};
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, ResponseTemplate};
 use zero2prod::domain::new_subscriber::models::subscriber::SubscriberStatus;
+use zero2prod::domain::new_subscriber::models::token::{SubscriptionToken, TokenPurpose};
+use zero2prod::domain::new_subscriber::ports::SubscriberRepository;
 
 #[tokio::test]
 async fn confirmation_without_token_is_rejected_with_a_400() {
@@ -42,7 +44,7 @@ async fn the_link_returned_by_subscribe_returns_a_200_if_called() {
 
     app.post_subscriptions(body.into()).await;
     let email_request = &app.email_server.received_requests().await.unwrap()[0];
-    let confirmation_links = app.get_confirmation_links(&email_request);
+    let confirmation_links = app.get_confirmation_links(email_request);
 
     let response = reqwest::get(confirmation_links.html).await.unwrap();
 
@@ -62,10 +64,11 @@ async fn both_links_returned_by_subscribe_return_a_200_if_called() {
         .await;
 
     app.post_subscriptions(body.into()).await;
+    app.end_confirmation_cooldown().await;
     app.post_subscriptions(body.into()).await;
 
     let email_requests = &app.email_server.received_requests().await.unwrap();
-    stream::iter(email_requests.into_iter())
+    stream::iter(email_requests.iter())
         .for_each_concurrent(None, |r| async {
             let confirmation_links = app.get_confirmation_links(r);
 
@@ -134,7 +137,7 @@ async fn if_the_link_returned_by_subscribe_doesnt_exist_return_401() {
 
     app.post_subscriptions(body.into()).await;
     let email_request = &app.email_server.received_requests().await.unwrap()[0];
-    let confirmation_links = app.get_confirmation_links(&email_request);
+    let confirmation_links = app.get_confirmation_links(email_request);
     let token = confirmation_links
         .html
         .query()
@@ -143,12 +146,13 @@ async fn if_the_link_returned_by_subscribe_doesnt_exist_return_401() {
         .nth(1)
         .unwrap();
 
-    if let Some(_) = confirmation_links.html.query() {
+    if confirmation_links.html.query().is_some() {
         let repo = app.subscription_repo();
         let pool = repo.pool();
+        let token_hash = app.hash_token(token);
         sqlx::query!(
-            "DELETE FROM subscription_tokens WHERE subscription_token=$1",
-            token
+            "DELETE FROM subscription_tokens WHERE token_hash=$1",
+            token_hash
         )
         .execute(pool)
         .await
@@ -159,3 +163,304 @@ async fn if_the_link_returned_by_subscribe_doesnt_exist_return_401() {
 
     assert_eq!(response.status().as_u16(), 401);
 }
+
+#[tokio::test]
+async fn expired_confirmation_links_are_rejected_with_401() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    sqlx::query!("UPDATE subscription_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    let response = reqwest::get(confirmation_links.html).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+    let status = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .status;
+    assert_eq!(status, "pending_confirmation");
+}
+
+#[tokio::test]
+async fn tokens_issued_for_another_purpose_cannot_confirm_a_subscription() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let subscriber_id = sqlx::query!("SELECT id FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .id;
+    let token = app
+        .subscription_repo()
+        .issue_token(subscriber_id, TokenPurpose::Unsubscribe)
+        .await
+        .unwrap();
+
+    let response = reqwest::get(format!(
+        "{}/subscriptions/confirm?subscription_token={}",
+        app.address,
+        token.as_str()
+    ))
+    .await
+    .unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn a_used_confirmation_link_cannot_confirm_again_after_unsubscribing() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (_, unsubscribe_token) = app.confirm_subscription().await.unwrap();
+    app.get_subscription_unsubscribe(unsubscribe_token.as_str().into())
+        .await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    let response = reqwest::get(confirmation_links.html).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn only_the_hash_of_confirmation_tokens_is_stored() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+    let (_, token) = confirmation_links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .unwrap();
+
+    let record = sqlx::query!("SELECT token_hash, legacy_token FROM subscription_tokens")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+
+    assert_eq!(record.token_hash, Some(app.hash_token(&token)));
+    assert_ne!(record.token_hash.as_deref(), Some(token.as_ref()));
+    assert_eq!(record.legacy_token, None);
+}
+
+#[tokio::test]
+async fn tokens_stored_before_hashing_keep_working_once_hashed() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let pool = app.subscription_repo().pool().clone();
+    let subscriber_id = sqlx::query!("SELECT id FROM subscriptions")
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .id;
+    let legacy_token = SubscriptionToken::new();
+    sqlx::query!(
+        r#"INSERT INTO subscription_tokens (legacy_token, subscriber_id, purpose, expires_at)
+        VALUES ($1, $2, 'confirm', now() + interval '1 day')"#,
+        legacy_token.as_str(),
+        subscriber_id,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    // The application refuses to start until the tokens are hashed
+    assert!(app
+        .subscription_repo()
+        .ensure_no_legacy_tokens()
+        .await
+        .is_err());
+
+    let hashed = app.subscription_repo().hash_legacy_tokens().await.unwrap();
+
+    assert_eq!(hashed, 1);
+    app.subscription_repo()
+        .ensure_no_legacy_tokens()
+        .await
+        .unwrap();
+    let response = reqwest::get(format!(
+        "{}/subscriptions/confirm?subscription_token={}",
+        app.address,
+        legacy_token.as_str()
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn confirmation_links_render_a_page_for_each_outcome() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    let response = reqwest::get(confirmation_links.html.clone()).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/html; charset=utf-8"
+    );
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<h1>Subscription confirmed</h1>"));
+
+    let response = reqwest::get(confirmation_links.html).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<h1>Already confirmed</h1>"));
+}
+
+#[tokio::test]
+async fn expired_confirmation_links_render_an_error_page_without_asking_for_credentials() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    sqlx::query!("UPDATE subscription_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    let response = reqwest::get(confirmation_links.html.clone()).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(response.headers().get("www-authenticate").is_none());
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("<h1>Invalid or expired link</h1>"));
+    assert!(html_page.contains("This link has expired"));
+
+    let response = reqwest::Client::new()
+        .get(confirmation_links.html)
+        .header("Accept-Language", "fr")
+        .send()
+        .await
+        .unwrap();
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("Lien invalide ou expiré"));
+    assert!(html_page.contains(&htmlescape::encode_minimal("Ce lien a expiré")));
+    assert!(!html_page.contains("This link has expired"));
+}
+
+#[tokio::test]
+async fn malformed_confirmation_links_render_an_error_page() {
+    let app = spawn_app().await;
+
+    let response = reqwest::get(format!("{}/subscriptions/confirm", app.address))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<h1>Invalid request</h1>"));
+}
+
+#[tokio::test]
+async fn api_clients_get_the_outcome_of_a_confirmation_as_json() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(confirmation_links.html.clone())
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["outcome"], "confirmed");
+
+    let response = client
+        .get(format!(
+            "{}/subscriptions/confirm?subscription_token={}",
+            app.address,
+            SubscriptionToken::new().as_str()
+        ))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "This link is not valid");
+}
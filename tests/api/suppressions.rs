@@ -0,0 +1,437 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, mock_email_server, recipient_count,
+    spawn_app, subscribe, TestApp,
+};
+use secrecy::ExposeSecret;
+
+async fn statuses(app: &TestApp, email: &str) -> Vec<String> {
+    sqlx::query!(
+        "SELECT status FROM subscriptions WHERE email = $1 ORDER BY status",
+        email
+    )
+    .fetch_all(app.subscription_repo().pool())
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|r| r.status)
+    .collect()
+}
+
+fn bounce(email: &str, kind: &str, inactive: bool) -> serde_json::Value {
+    serde_json::json!({
+        "RecordType": "Bounce",
+        "ID": 4323372036854775807u64,
+        "Type": kind,
+        "TypeCode": 1,
+        "MessageID": "883953f4-6105-42a2-a16a-77a8eac79483",
+        "Description": "The server was unable to deliver your message (ex: unknown user, mailbox not found).",
+        "Details": "Test bounce details",
+        "Email": email,
+        "BouncedAt": "2024-12-06T16:33:54.9070259Z",
+        "Inactive": inactive,
+    })
+}
+
+fn spam_complaint(email: &str) -> serde_json::Value {
+    serde_json::json!({
+        "RecordType": "SpamComplaint",
+        "ID": 42,
+        "Type": "SpamComplaint",
+        "TypeCode": 512,
+        "MessageID": "00000000-0000-0000-0000-000000000000",
+        "Description": "The subscriber explicitly marked this message as spam.",
+        "Email": email,
+        "BouncedAt": "2024-12-06T16:33:54.9070259Z",
+        "Inactive": true,
+    })
+}
+
+#[tokio::test]
+async fn webhooks_without_the_shared_secret_are_rejected() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    let event = bounce("ursula_le_guin@gmail.com", "HardBounce", true);
+
+    let response = app.post_postmark_webhook(&event, "not-the-secret").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .api_client
+        .post(format!("{}/webhooks/postmark", &app.address))
+        .json(&event)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["confirmed"]
+    );
+}
+
+#[tokio::test]
+async fn hard_bounces_suppress_every_subscription_of_the_address() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    app.post_list("Rust weekly", "rust-weekly").await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[("list", "rust-weekly")]).await;
+    create_confirmed_subscriber(&app, "octavia_butler@gmail.com", &[]).await;
+    assert_eq!(recipient_count(&app, "").await, 2);
+
+    let response = app
+        .post_postmark_webhook(
+            &bounce("ursula_le_guin@gmail.com", "HardBounce", true),
+            app.webhook_secret.expose_secret(),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["suppressed_subscriptions"], 2);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["bounced", "bounced"]
+    );
+    assert_eq!(
+        statuses(&app, "octavia_butler@gmail.com").await,
+        vec!["confirmed"]
+    );
+    assert_eq!(recipient_count(&app, "").await, 1);
+}
+
+#[tokio::test]
+async fn hard_bounces_mark_the_deliveries_sent_to_the_address_as_bounced() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    create_confirmed_subscriber(&app, "octavia_butler@gmail.com", &[]).await;
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
+    }))
+    .await;
+    app.dispatch_all_pending_emails().await;
+
+    app.post_postmark_webhook(
+        &spam_complaint("octavia_butler@gmail.com"),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+    let event = bounce("ursula_le_guin@gmail.com", "HardBounce", true);
+    app.post_postmark_webhook(&event, app.webhook_secret.expose_secret())
+        .await;
+
+    let log = sqlx::query!(
+        "SELECT subscriber_email, status, last_error FROM issue_delivery_log
+        ORDER BY subscriber_email"
+    )
+    .fetch_all(app.subscription_repo().pool())
+    .await
+    .unwrap();
+    assert_eq!(log[0].subscriber_email, "octavia_butler@gmail.com");
+    assert_eq!(log[0].status, "sent");
+    assert_eq!(log[1].subscriber_email, "ursula_le_guin@gmail.com");
+    assert_eq!(log[1].status, "bounced");
+    assert_eq!(log[1].last_error.as_deref(), event["Description"].as_str());
+
+    let issue = sqlx::query!("SELECT newsletter_issue_id FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap();
+    let html_page = app
+        .get_issue(&issue.newsletter_issue_id.to_string())
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("<li>Bounced: 1</li>"));
+    assert!(html_page.contains("ursula_le_guin@gmail.com"));
+}
+
+#[tokio::test]
+async fn soft_bounces_leave_the_subscription_alone() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+
+    let response = app
+        .post_postmark_webhook(
+            &bounce("ursula_le_guin@gmail.com", "SoftBounce", false),
+            app.webhook_secret.expose_secret(),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["confirmed"]
+    );
+}
+
+#[tokio::test]
+async fn spam_complaints_suppress_the_address() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+
+    let response = app
+        .post_postmark_webhook(
+            &spam_complaint("ursula_le_guin@gmail.com"),
+            app.webhook_secret.expose_secret(),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["complained"]
+    );
+}
+
+#[tokio::test]
+async fn events_for_unknown_addresses_are_acknowledged() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_postmark_webhook(
+            &spam_complaint("nobody@gmail.com"),
+            app.webhook_secret.expose_secret(),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["suppressed_subscriptions"], 0);
+}
+
+#[tokio::test]
+async fn addresses_suppressed_before_subscribing_get_no_confirmation_email() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.post_postmark_webhook(
+        &bounce("ursula_le_guin@gmail.com", "HardBounce", true),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+
+    let response = subscribe(&app, "ursula_le_guin@gmail.com", &[]).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(statuses(&app, "ursula_le_guin@gmail.com").await.is_empty());
+}
+
+#[tokio::test]
+async fn suppressed_addresses_cannot_subscribe_to_another_list() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.test_user.login(&app).await;
+    app.post_list("Rust weekly", "rust-weekly").await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.post_postmark_webhook(
+        &spam_complaint("ursula_le_guin@gmail.com"),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+
+    let response = subscribe(&app, "ursula_le_guin@gmail.com", &[("list", "rust-weekly")]).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let emails = app.email_server.received_requests().await.unwrap();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["complained"]
+    );
+}
+
+#[tokio::test]
+async fn suppressed_addresses_signing_up_again_get_no_confirmation_email() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.post_postmark_webhook(
+        &spam_complaint("ursula_le_guin@gmail.com"),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+    app.end_confirmation_cooldown().await;
+
+    let body = "name=Subscriber&email=ursula_le_guin%40gmail.com";
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let emails = app.email_server.received_requests().await.unwrap();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["complained"]
+    );
+}
+
+#[tokio::test]
+async fn following_the_unsubscribe_link_keeps_an_address_suppressed() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.post_subscriptions("name=le%20guin&email=ursula_le_guin%40gmail.com".into())
+        .await;
+    let (_, unsubscribe_token) = app.confirm_subscription().await.unwrap();
+    app.post_postmark_webhook(
+        &bounce("ursula_le_guin@gmail.com", "HardBounce", true),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+
+    let response = app
+        .post_one_click_unsubscribe(unsubscribe_token.as_str(), "List-Unsubscribe=One-Click")
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["bounced"]
+    );
+}
+
+#[tokio::test]
+async fn suppressed_addresses_are_listed_and_can_be_reinstated() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.post_postmark_webhook(
+        &bounce("ursula_le_guin@gmail.com", "HardBounce", true),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+    app.test_user.login(&app).await;
+
+    let html_page = app.get_suppressions_html().await;
+    assert!(html_page.contains("ursula_le_guin@gmail.com"));
+    assert!(html_page.contains("bounced"));
+    assert!(html_page.contains("unknown user, mailbox not found"));
+
+    let response = app.post_reinstate_address("ursula_le_guin@gmail.com").await;
+    assert_is_redirect_to(&response, "/admin/suppressions");
+
+    let html_page = app.get_suppressions_html().await;
+    assert!(html_page.contains("<p><i>The address has been reinstated.</i></p>"));
+    assert!(!html_page.contains("ursula_le_guin@gmail.com"));
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["confirmed"]
+    );
+}
+
+#[tokio::test]
+async fn reinstated_addresses_can_subscribe_again() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    app.post_postmark_webhook(
+        &bounce("ursula_le_guin@gmail.com", "HardBounce", true),
+        app.webhook_secret.expose_secret(),
+    )
+    .await;
+    app.test_user.login(&app).await;
+    let html_page = app.get_suppressions_html().await;
+    assert!(html_page.contains("ursula_le_guin@gmail.com"));
+
+    app.post_reinstate_address("ursula_le_guin@gmail.com").await;
+    subscribe(&app, "ursula_le_guin@gmail.com", &[])
+        .await
+        .error_for_status()
+        .unwrap();
+
+    assert_eq!(app.email_server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["pending_confirmation"]
+    );
+}
+
+#[tokio::test]
+async fn reinstating_an_address_that_is_not_suppressed_returns_404() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_reinstate_address("ursula_le_guin@gmail.com").await;
+
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn administrators_can_suppress_an_address() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_suppression(&serde_json::json!({
+            "email": "ursula_le_guin@gmail.com",
+            "reason": "complained",
+            "description": "Asked us by email to stop",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/suppressions");
+
+    let html_page = app.get_suppressions_html().await;
+    assert!(html_page.contains("Suppressed 1 subscriptions of ursula_le_guin@gmail.com."));
+    assert!(html_page.contains("Asked us by email to stop"));
+    assert_eq!(
+        statuses(&app, "ursula_le_guin@gmail.com").await,
+        vec!["complained"]
+    );
+}
+
+#[tokio::test]
+async fn suppressing_with_an_unknown_reason_returns_400() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    let response = app
+        .post_suppression(&serde_json::json!({
+            "email": "ursula_le_guin@gmail.com",
+            "reason": "annoyed",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_suppressions() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/admin/suppressions", &app.address))
+        .send()
+        .await
+        .unwrap();
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app
+        .post_suppression(&serde_json::json!({
+            "email": "ursula_le_guin@gmail.com",
+            "reason": "bounced",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app.post_reinstate_address("ursula_le_guin@gmail.com").await;
+    assert_is_redirect_to(&response, "/login");
+}
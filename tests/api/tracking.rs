@@ -0,0 +1,251 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, mock_email_server, spawn_app, TestApp,
+};
+use wiremock::matchers::any;
+use wiremock::{Mock, ResponseTemplate};
+
+const ISSUE_HTML: &str = r#"<p>Read <a href="https://example.com/post?a=1&amp;b=2">the post</a> or <a href="/about">about us</a></p>"#;
+
+fn issue(tracking: Option<bool>) -> serde_json::Value {
+    let mut issue = serde_json::json!({
+        "title": "Tracked issue",
+        "text_content": "Newsletter body as plain text",
+        "html_content": ISSUE_HTML,
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    if let Some(tracking) = tracking {
+        issue["tracking"] = tracking.into();
+    }
+    issue
+}
+
+async fn issue_id(app: &TestApp) -> String {
+    sqlx::query!("SELECT newsletter_issue_id FROM newsletter_issues")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .newsletter_issue_id
+        .to_string()
+}
+
+async fn delivered_html(app: &TestApp) -> String {
+    let email_request = app.get_email_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    body["HtmlBody"].as_str().unwrap().to_string()
+}
+
+/// Tracking links of an email body, in order of appearance: the open pixel comes last
+fn tracking_links(app: &TestApp, html: &str) -> Vec<reqwest::Url> {
+    let prefix = format!("{}/t/", app.newsletter_state.url());
+    linkify::LinkFinder::new()
+        .links(html)
+        .filter(|l| l.as_str().starts_with(&prefix))
+        .map(|l| {
+            let mut link = reqwest::Url::parse(l.as_str()).unwrap();
+            link.set_port(Some(app.port)).unwrap();
+            link
+        })
+        .collect()
+}
+
+async fn follow(app: &TestApp, link: reqwest::Url) -> reqwest::Response {
+    // The client does not follow redirects, so clicks stop at the tracker
+    app.api_client.get(link).send().await.unwrap()
+}
+
+#[tokio::test]
+async fn issues_are_not_tracked_by_default() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_publish_newsletter(&issue(None)).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let html = delivered_html(&app).await;
+    assert!(html.contains(r#"<a href="https://example.com/post?a=1&amp;b=2">"#));
+    assert!(tracking_links(&app, &html).is_empty());
+    let html_page = app
+        .get_issue(&issue_id(&app).await)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("Opens and clicks are not tracked for this issue."));
+}
+
+#[tokio::test]
+async fn opens_and_clicks_of_tracked_issues_are_recorded() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    let response = app.post_publish_newsletter(&issue(Some(true))).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    let html = delivered_html(&app).await;
+    assert!(!html.contains("https://example.com/post"));
+    assert!(html.contains(r#"<a href="/about">"#));
+    let links = tracking_links(&app, &html);
+    assert_eq!(links.len(), 2);
+    let (click, open) = (links[0].clone(), links[1].clone());
+
+    let response = follow(&app, click.clone()).await;
+    assert_eq!(response.status().as_u16(), 302);
+    assert_eq!(
+        response.headers().get("Location").unwrap(),
+        "https://example.com/post?a=1&b=2"
+    );
+    follow(&app, click).await;
+    for _ in 0..2 {
+        let response = follow(&app, open.clone()).await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/gif");
+    }
+
+    let html_page = app
+        .get_issue(&issue_id(&app).await)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("<li>Opens: 2 (1 unique)</li>"));
+    assert!(html_page.contains("<li>Clicks: 2 (1 unique)</li>"));
+    assert!(html_page.contains("<td>https://example.com/post?a=1&amp;b=2</td>"));
+}
+
+#[tokio::test]
+async fn personalized_links_are_not_tracked() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+    let mut tracked_issue = issue(Some(true));
+    tracked_issue["html_content"] = format!(
+        r#"{}<p><a href="{{{{unsubscribe_url}}}}">Unsubscribe</a></p>"#,
+        ISSUE_HTML
+    )
+    .into();
+
+    app.post_publish_newsletter(&tracked_issue).await;
+    app.dispatch_all_pending_emails().await;
+
+    let html = delivered_html(&app).await;
+    assert!(html.contains("subscription_token="));
+    let stored_urls: Vec<Option<String>> =
+        sqlx::query!("SELECT url FROM newsletter_tracking_links")
+            .fetch_all(app.subscription_repo().pool())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.url)
+            .collect();
+    assert_eq!(stored_urls.len(), 2);
+    assert!(stored_urls
+        .iter()
+        .flatten()
+        .all(|url| !url.contains("subscription_token=")));
+}
+
+#[tokio::test]
+async fn drafts_keep_their_tracking_choice_until_published() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+    let draft = serde_json::json!({
+        "title": "Tracked draft",
+        "text_content": "Draft body as plain text",
+        "html_content": ISSUE_HTML,
+        "tracking": true,
+    });
+
+    let response = app.post_create_draft(&draft).await;
+    assert_eq!(response.status().as_u16(), 303);
+    let draft_id = issue_id(&app).await;
+    let html_page = app.get_draft(&draft_id).await.text().await.unwrap();
+    assert!(html_page.contains(r#"name="tracking" value="true" checked"#));
+
+    app.post_publish_draft(&draft_id).await;
+    app.dispatch_all_pending_emails().await;
+
+    let html = delivered_html(&app).await;
+    assert_eq!(tracking_links(&app, &html).len(), 2);
+}
+
+async fn stored_tracking_links(app: &TestApp) -> i64 {
+    sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM newsletter_tracking_links"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .count
+}
+
+#[tokio::test]
+async fn retried_deliveries_reuse_the_tracking_links_of_the_recipient() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+    app.post_publish_newsletter(&issue(Some(true))).await;
+
+    app.email_server.reset().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+    app.email_server.reset().await;
+    mock_email_server(&app).await;
+    app.post_replay_dead_letter(&serde_json::json!({
+        "newsletter_issue_id": issue_id(&app).await,
+        "subscriber_email": "ursula_le_guin@gmail.com",
+    }))
+    .await;
+    app.dispatch_all_pending_emails().await;
+
+    let html = delivered_html(&app).await;
+    assert_eq!(tracking_links(&app, &html).len(), 2);
+    assert_eq!(stored_tracking_links(&app).await, 2);
+}
+
+#[tokio::test]
+async fn tracking_links_are_deleted_with_the_subscription() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "ursula_le_guin@gmail.com", &[]).await;
+    app.test_user.login(&app).await;
+    app.post_publish_newsletter(&issue(Some(true))).await;
+    app.dispatch_all_pending_emails().await;
+    assert_eq!(stored_tracking_links(&app).await, 2);
+
+    let pool = app.subscription_repo().pool().clone();
+    sqlx::query!("DELETE FROM subscription_tokens")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM subscriptions")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(stored_tracking_links(&app).await, 0);
+}
+
+#[tokio::test]
+async fn unknown_tracking_links_return_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/t/{}", &app.address, uuid::Uuid::new_v4()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 404);
+}
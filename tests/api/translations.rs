@@ -0,0 +1,194 @@
+use crate::helpers::{
+    assert_is_redirect_to, create_confirmed_subscriber, mock_email_server, publish_issue,
+    spawn_app, TestApp,
+};
+
+/// Bodies of the emails sent to `recipient`
+async fn emails_sent_to(app: &TestApp, recipient: &str) -> Vec<serde_json::Value> {
+    app.email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice::<serde_json::Value>(&r.body).unwrap())
+        .filter(|body| body["To"] == recipient)
+        .collect()
+}
+
+fn french_translation() -> serde_json::Value {
+    serde_json::json!({
+        "locale": "fr",
+        "title": "Numéro traduit",
+        "markdown_content": "Bonjour {{name}} !",
+    })
+}
+
+#[tokio::test]
+async fn translated_issues_are_sent_to_the_subscribers_of_that_locale() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "marie@example.com", &[("locale", "fr")]).await;
+    create_confirmed_subscriber(&app, "mary@example.com", &[("locale", "en")]).await;
+    app.test_user.login(&app).await;
+
+    let (newsletter_issue_id, _) = publish_issue(&app, "Original issue", "<p>Hello</p>").await;
+    let response = app
+        .post_translation(&newsletter_issue_id, &french_translation())
+        .await;
+    assert_is_redirect_to(
+        &response,
+        &format!("/admin/newsletters/translations/{}", newsletter_issue_id),
+    );
+    app.dispatch_all_pending_emails().await;
+
+    let french = emails_sent_to(&app, "marie@example.com").await;
+    let issue = french.last().unwrap();
+    assert_eq!(issue["Subject"], "Numéro traduit");
+    assert!(issue["TextBody"]
+        .as_str()
+        .unwrap()
+        .starts_with("Bonjour Subscriber !"));
+    assert!(issue["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains("pour vous désinscrire"));
+
+    let english = emails_sent_to(&app, "mary@example.com").await;
+    let issue = english.last().unwrap();
+    assert_eq!(issue["Subject"], "Original issue");
+    assert!(issue["HtmlBody"].as_str().unwrap().contains("<p>Hello</p>"));
+}
+
+#[tokio::test]
+async fn subscribers_of_a_locale_without_translation_get_the_issue_as_written() {
+    let app = spawn_app().await;
+    mock_email_server(&app).await;
+    create_confirmed_subscriber(&app, "marie@example.com", &[("locale", "fr")]).await;
+    app.test_user.login(&app).await;
+
+    publish_issue(&app, "Original issue", "<p>Hello</p>").await;
+    app.dispatch_all_pending_emails().await;
+
+    let french = emails_sent_to(&app, "marie@example.com").await;
+    let issue = french.last().unwrap();
+    assert_eq!(issue["Subject"], "Original issue");
+    // The footer is still written in the locale of the subscriber
+    assert!(issue["HtmlBody"]
+        .as_str()
+        .unwrap()
+        .contains("pour vous désinscrire"));
+}
+
+#[tokio::test]
+async fn saved_translations_are_shown_on_the_translations_page() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (newsletter_issue_id, _) = publish_issue(&app, "Original issue", "<p>Hello</p>").await;
+
+    app.post_translation(&newsletter_issue_id, &french_translation())
+        .await;
+
+    let html_page = app
+        .get_translations(&newsletter_issue_id)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("<p><i>The translation has been saved.</i></p>"));
+    assert!(html_page.contains(&htmlescape::encode_attribute("Numéro traduit")));
+    assert!(html_page.contains("Bonjour {{name}} !"));
+}
+
+#[tokio::test]
+async fn blank_translations_are_removed() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (newsletter_issue_id, _) = publish_issue(&app, "Original issue", "<p>Hello</p>").await;
+    app.post_translation(&newsletter_issue_id, &french_translation())
+        .await;
+
+    let response = app
+        .post_translation(
+            &newsletter_issue_id,
+            &serde_json::json!({
+                "locale": "fr",
+                "title": "",
+                "markdown_content": "",
+                "text_content": "",
+                "html_content": "",
+            }),
+        )
+        .await;
+    assert_is_redirect_to(
+        &response,
+        &format!("/admin/newsletters/translations/{}", newsletter_issue_id),
+    );
+
+    let count = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM newsletter_issue_translations"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn invalid_translations_are_rejected() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let (newsletter_issue_id, _) = publish_issue(&app, "Original issue", "<p>Hello</p>").await;
+    let test_cases = [
+        (
+            serde_json::json!({"locale": "en", "title": "Title", "markdown_content": "Body"}),
+            "the default locale",
+        ),
+        (
+            serde_json::json!({"locale": "xx", "title": "Title", "markdown_content": "Body"}),
+            "an unsupported locale",
+        ),
+        (
+            serde_json::json!({"locale": "fr", "title": "Title"}),
+            "a missing body",
+        ),
+    ];
+
+    for (body, description) in test_cases {
+        let response = app.post_translation(&newsletter_issue_id, &body).await;
+
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a translation into {}",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn translations_of_unknown_issues_return_404() {
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let newsletter_issue_id = uuid::Uuid::new_v4().to_string();
+
+    let response = app.get_translations(&newsletter_issue_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app
+        .post_translation(&newsletter_issue_id, &french_translation())
+        .await;
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_translate_an_issue() {
+    let app = spawn_app().await;
+    let newsletter_issue_id = uuid::Uuid::new_v4().to_string();
+
+    let response = app.get_translations(&newsletter_issue_id).await;
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app
+        .post_translation(&newsletter_issue_id, &french_translation())
+        .await;
+    assert_is_redirect_to(&response, "/login");
+}
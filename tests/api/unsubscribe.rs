@@ -156,3 +156,190 @@ async fn sucessful_confirmed_unsubscription_deletes_subscriber() {
         panic!("Subscription wasnt confirmed")
     }
 }
+
+#[tokio::test]
+async fn confirmation_tokens_cannot_be_used_to_unsubscribe() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+    let (_, confirmation_token) = confirmation_links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .unwrap();
+
+    let response = app
+        .get_subscription_unsubscribe(confirmation_token.into_owned())
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn expired_unsubscribe_tokens_are_rejected_with_401() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (subscriber, token) = app.confirm_subscription().await.unwrap();
+    sqlx::query!("UPDATE subscription_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(app.subscription_repo().pool())
+        .await
+        .unwrap();
+
+    let response = app
+        .get_subscription_unsubscribe(token.as_str().into())
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    let record = sqlx::query!(
+        "SELECT status FROM subscriptions WHERE email = $1",
+        subscriber.email.as_str()
+    )
+    .fetch_one(app.subscription_repo().pool())
+    .await
+    .unwrap();
+    assert_eq!(record.status, "confirmed");
+}
+
+#[tokio::test]
+async fn one_click_unsubscribe_removes_the_subscription_right_away() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (_, token) = app.confirm_subscription().await.unwrap();
+
+    let response = app
+        .post_one_click_unsubscribe(token.as_str(), "List-Unsubscribe=One-Click")
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let remaining = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM subscriptions"#)
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn one_click_unsubscribe_without_the_one_click_body_is_rejected_with_400() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (_, token) = app.confirm_subscription().await.unwrap();
+
+    let response = app.post_one_click_unsubscribe(token.as_str(), "").await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    let status = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(app.subscription_repo().pool())
+        .await
+        .unwrap()
+        .status;
+    assert_eq!(status, "confirmed");
+}
+
+#[tokio::test]
+async fn one_click_unsubscribe_with_unknown_token_is_rejected_with_401() {
+    let app = spawn_app().await;
+    let token = SubscriptionToken::default();
+
+    let response = app
+        .post_one_click_unsubscribe(token.as_str(), "List-Unsubscribe=One-Click")
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn unsubscribe_links_render_a_page_for_each_step() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (_, token) = app.confirm_subscription().await.unwrap();
+
+    let response = app
+        .get_subscription_unsubscribe(token.as_str().into())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("<h1>Confirm your cancellation</h1>"));
+    assert!(html_page.contains(&format!(
+        r#"href="/subscriptions/unsubscribe?subscription_token={}""#,
+        token.as_str()
+    )));
+
+    let response = app
+        .get_subscription_unsubscribe(token.as_str().into())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<h1>Unsubscribed</h1>"));
+}
+
+#[tokio::test]
+async fn api_clients_get_the_outcome_of_an_unsubscription_as_json() {
+    let app = spawn_app().await;
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into()).await;
+    let (_, token) = app.confirm_subscription().await.unwrap();
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/subscriptions/unsubscribe", app.address))
+        .query(&[("subscription_token", token.as_str())])
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["outcome"], "cancellation_requested");
+}